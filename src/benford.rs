@@ -0,0 +1,641 @@
+/*
+ * Test the numeric columns of delimited text files, such as CSV and TSV
+ * exports from spreadsheets and accounting systems, against Benford's law.
+ * Financial figures that follow it are real records worth protecting, and
+ * ones that do not may have been made up.
+ */
+
+use crate::findings::{Finding, Severity};
+use crate::frequency_analysis::{
+    benford_diff, benford_probabilities, digit_count_at_idx_radix, first_two_digit_count,
+    first_two_digit_probabilities,
+};
+use std::error::Error;
+use std::path::Path;
+
+/// Columns with fewer values than this are too small for the tests to mean
+/// anything.
+const MIN_VALUES: usize = 100;
+
+/// Benford's law only describes data spanning at least an order of
+/// magnitude.
+const MIN_MAGNITUDE_RATIO: f64 = 10.0;
+
+/// At least this fraction of a column's cells must be numbers.
+const MIN_NUMERIC_FRACTION: f64 = 0.9;
+
+/// Lines read to work out how the fields are separated.
+const DELIMITER_SAMPLE_LINES: usize = 20;
+
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+const CURRENCY_SYMBOLS: [char; 5] = ['$', '€', '£', '¥', '₹'];
+
+/// Column names that mark a column as money.
+const FINANCIAL_HEADERS: [&str; 16] = [
+    "amount", "total", "price", "cost", "balance", "payment", "payout", "invoice", "salary",
+    "revenue", "debit", "credit", "expense", "fee", "paid", "value",
+];
+
+/// Nigrini's mean absolute deviation limits for close, acceptable and
+/// marginally acceptable conformity of decimal digits.
+const FIRST_DIGIT_MAD_LIMITS: [f64; 3] = [0.006, 0.012, 0.015];
+const SECOND_DIGIT_MAD_LIMITS: [f64; 3] = [0.008, 0.010, 0.012];
+const FIRST_TWO_DIGITS_MAD_LIMITS: [f64; 3] = [0.0012, 0.0018, 0.0022];
+
+/// The standard normal quantile with 5% of the distribution above it.
+const NORMAL_QUANTILE_95: f64 = 1.6449;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub(crate) enum Conformity {
+    Close,
+    Acceptable,
+    Marginal,
+    Nonconforming,
+}
+
+impl Conformity {
+    fn from_mad(mad: f64, limits: &[f64; 3]) -> Self {
+        return if mad <= limits[0] {
+            Conformity::Close
+        } else if mad <= limits[1] {
+            Conformity::Acceptable
+        } else if mad <= limits[2] {
+            Conformity::Marginal
+        } else {
+            Conformity::Nonconforming
+        };
+    }
+
+    fn description(&self) -> &'static str {
+        return match self {
+            Conformity::Close => "close conformity",
+            Conformity::Acceptable => "acceptable conformity",
+            Conformity::Marginal => "marginally acceptable conformity",
+            Conformity::Nonconforming => "nonconformity",
+        };
+    }
+}
+
+/// Which significant digits of the numbers are compared with the law.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum DigitTest {
+    First,
+    Second,
+    FirstTwo,
+}
+
+impl DigitTest {
+    /// How many values the digits can take in decimal, the radix Nigrini's
+    /// limits were set for.
+    fn decimal_categories(&self) -> f64 {
+        return match self {
+            DigitTest::First => 9.0,
+            DigitTest::Second => 10.0,
+            DigitTest::FirstTwo => 90.0,
+        };
+    }
+
+    fn mad_limits(&self) -> &'static [f64; 3] {
+        return match self {
+            DigitTest::First => &FIRST_DIGIT_MAD_LIMITS,
+            DigitTest::Second => &SECOND_DIGIT_MAD_LIMITS,
+            DigitTest::FirstTwo => &FIRST_TWO_DIGITS_MAD_LIMITS,
+        };
+    }
+
+    fn description(&self) -> &'static str {
+        return match self {
+            DigitTest::First => "first digit",
+            DigitTest::Second => "second digit",
+            DigitTest::FirstTwo => "first two digits",
+        };
+    }
+}
+
+/// The chi-square value exceeded by chance only 5% of the time, from the
+/// Wilson-Hilferty approximation.
+fn chi_square_critical(degrees_of_freedom: f64) -> f64 {
+    let k = 2.0 / (9.0 * degrees_of_freedom);
+    return degrees_of_freedom * (1.0 - k + NORMAL_QUANTILE_95 * k.sqrt()).powi(3);
+}
+
+/// The result of testing some digits of a set of numbers against Benford's
+/// law.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct BenfordTest {
+    pub(crate) test: DigitTest,
+    pub(crate) radix: u32,
+    /// How many of the numbers had the digits tested.
+    pub(crate) values: usize,
+    pub(crate) mad: f64,
+    pub(crate) chi_square: f64,
+    /// One less than the number of values the digits can take.
+    pub(crate) degrees_of_freedom: usize,
+    pub(crate) conformity: Conformity,
+}
+
+impl BenfordTest {
+    /// Test the digits of numbers written in a radix from 2 to 36. Nigrini's
+    /// limits are for decimal, so in other radices they are scaled by how
+    /// many values the digits can take.
+    pub(crate) fn new(numbers: &Vec<String>, test: DigitTest, radix: u32) -> Option<Self> {
+        let (counts, probs) = match test {
+            DigitTest::First => (
+                digit_count_at_idx_radix(numbers, 0, radix),
+                benford_probabilities(0, radix),
+            ),
+            DigitTest::Second => (
+                digit_count_at_idx_radix(numbers, 1, radix),
+                benford_probabilities(1, radix),
+            ),
+            DigitTest::FirstTwo => (
+                first_two_digit_count(numbers, radix),
+                first_two_digit_probabilities(radix),
+            ),
+        };
+        let values = counts.iter().sum::<usize>();
+        if values == 0 {
+            return None;
+        }
+
+        let frequencies: Vec<f64> = counts.iter().map(|x| *x as f64 / values as f64).collect();
+        /* Digit values the law rules out, like a leading zero, never occur. */
+        let categories = probs.iter().filter(|x| **x > 0.0).count();
+        let deviation = match test {
+            DigitTest::First => benford_diff(&frequencies, 0),
+            DigitTest::Second => benford_diff(&frequencies, 1),
+            DigitTest::FirstTwo => frequencies
+                .iter()
+                .zip(probs.iter())
+                .map(|(freq, prob)| (freq - prob).abs())
+                .sum(),
+        };
+        let mad = deviation / categories as f64;
+        let chi_square = counts
+            .iter()
+            .zip(probs.iter())
+            .filter(|(_, prob)| **prob > 0.0)
+            .map(|(count, prob)| {
+                let expected = prob * values as f64;
+                (*count as f64 - expected).powi(2) / expected
+            })
+            .sum();
+
+        let scale = test.decimal_categories() / categories as f64;
+        return Some(BenfordTest {
+            test,
+            radix,
+            values,
+            mad,
+            chi_square,
+            degrees_of_freedom: categories - 1,
+            conformity: Conformity::from_mad(mad, &test.mad_limits().map(|x| x * scale)),
+        });
+    }
+
+    /// Is the chi-square statistic beyond the 5% critical value. With
+    /// thousands of values this happens even for real data, which is why
+    /// conformity is judged on the MAD.
+    pub(crate) fn chi_square_significant(&self) -> bool {
+        return self.chi_square > chi_square_critical(self.degrees_of_freedom as f64);
+    }
+}
+
+/// A column of numbers taken from a delimited text file.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct NumericColumn {
+    pub(crate) name: String,
+    /// Each value with currency symbols and thousands separators removed.
+    pub(crate) values: Vec<String>,
+    /// Ten, or sixteen for columns of hexadecimal values.
+    pub(crate) radix: u32,
+    /// Whether the name or currency symbols mark the column as money.
+    pub(crate) financial: bool,
+}
+
+impl NumericColumn {
+    /// Can Benford's law be expected to describe the column. Identifiers
+    /// that count up and values packed into a narrow range cannot be tested.
+    pub(crate) fn is_testable(&self) -> bool {
+        let numbers: Vec<f64> = self
+            .values
+            .iter()
+            .filter_map(|x| magnitude(x, self.radix))
+            .filter(|x| *x > 0.0)
+            .collect();
+        if numbers.len() < MIN_VALUES {
+            return false;
+        }
+
+        let sequential = numbers.windows(2).all(|x| x[1] - x[0] == 1.0);
+        let mut distinct = self.values.clone();
+        distinct.sort();
+        distinct.dedup();
+
+        let smallest = numbers.iter().cloned().fold(f64::INFINITY, f64::min);
+        let largest = numbers.iter().cloned().fold(0.0, f64::max);
+        return !sequential
+            && distinct.len() * 4 >= self.values.len()
+            && largest / smallest >= MIN_MAGNITUDE_RATIO;
+    }
+}
+
+/// The size of a cleaned value, ignoring its sign.
+fn magnitude(value: &str, radix: u32) -> Option<f64> {
+    if radix == 10 {
+        return value.parse::<f64>().ok().map(f64::abs);
+    }
+    let digits = value.trim_start_matches('-').trim_start_matches("0x");
+    return u128::from_str_radix(digits, radix).ok().map(|x| x as f64);
+}
+
+/// Split one line of a delimited file into fields, honouring double quotes.
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(chr) = chars.next() {
+        match chr {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            x if x == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            x => field.push(x),
+        }
+    }
+    fields.push(field);
+    return fields;
+}
+
+/// Pick the delimiter that splits the first lines into the same number of
+/// fields, preferring the one giving the most fields and then the more
+/// common delimiter.
+fn detect_delimiter(lines: &[&str]) -> Option<char> {
+    let sample: Vec<&str> = lines.iter().take(DELIMITER_SAMPLE_LINES).cloned().collect();
+
+    /* The last of several equal maximums wins, so search from the back. */
+    return DELIMITERS
+        .iter()
+        .rev()
+        .filter_map(|delimiter| {
+            let mut counts = sample.iter().map(|x| split_record(x, *delimiter).len());
+            let first = counts.next()?;
+            (first > 1 && counts.all(|x| x == first)).then_some((first, *delimiter))
+        })
+        .max_by_key(|(count, _)| *count)
+        .map(|(_, delimiter)| delimiter);
+}
+
+/// Turn a cell into a plain number, returning whether it carried a currency
+/// symbol. Accounting style negatives in brackets are accepted.
+fn clean_number(cell: &str) -> Option<(String, bool)> {
+    let mut text = cell.trim();
+    let currency = text.contains(CURRENCY_SYMBOLS);
+    let negative = text.starts_with('(') && text.ends_with(')');
+    if negative {
+        text = &text[1..text.len() - 1];
+    }
+
+    let cleaned: String = text
+        .chars()
+        .filter(|x| !CURRENCY_SYMBOLS.contains(x) && *x != ',' && !x.is_whitespace())
+        .collect();
+    /* Exponents, infinities and NaN are not figures anyone typed. */
+    if !cleaned
+        .chars()
+        .all(|x| x.is_ascii_digit() || matches!(x, '.' | '-' | '+'))
+        || cleaned.parse::<f64>().is_err()
+    {
+        return None;
+    }
+    return Some((
+        if negative {
+            format!("-{cleaned}")
+        } else {
+            cleaned
+        },
+        currency,
+    ));
+}
+
+/// Recognise a hexadecimal value written with a 0x prefix, as blockchain
+/// and low level tools export them.
+fn clean_hex(cell: &str) -> Option<String> {
+    let text = cell.trim();
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))?;
+    if digits.is_empty() || !digits.chars().all(|x| x.is_ascii_hexdigit()) {
+        return None;
+    }
+    return Some(format!("0x{}", digits.to_lowercase()));
+}
+
+/// Pull the numeric columns out of delimited text. The first line is taken
+/// as a header when any of its fields is not a number.
+pub(crate) fn numeric_columns(text: &str) -> Vec<NumericColumn> {
+    let lines: Vec<&str> = text.lines().filter(|x| !x.trim().is_empty()).collect();
+    let Some(delimiter) = detect_delimiter(&lines) else {
+        return Vec::new();
+    };
+    let rows: Vec<Vec<String>> = lines.iter().map(|x| split_record(x, delimiter)).collect();
+
+    let has_header = rows[0]
+        .iter()
+        .any(|x| clean_number(x).is_none() && clean_hex(x).is_none());
+    let (names, data) = if has_header {
+        (rows[0].clone(), &rows[1..])
+    } else {
+        (
+            (1..=rows[0].len()).map(|x| format!("column {x}")).collect(),
+            &rows[..],
+        )
+    };
+
+    let mut columns = Vec::new();
+    for (idx, name) in names.iter().enumerate() {
+        let cells: Vec<&str> = data
+            .iter()
+            .filter_map(|x| x.get(idx))
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .collect();
+        let mostly = |found: usize| {
+            !cells.is_empty() && found as f64 >= cells.len() as f64 * MIN_NUMERIC_FRACTION
+        };
+
+        let numbers: Vec<(String, bool)> = cells.iter().filter_map(|x| clean_number(x)).collect();
+        let hex_numbers: Vec<String> = cells.iter().filter_map(|x| clean_hex(x)).collect();
+        let (values, radix, currency_cells) = if mostly(numbers.len()) {
+            let currency_cells = numbers.iter().filter(|(_, currency)| *currency).count();
+            (
+                numbers.into_iter().map(|(x, _)| x).collect(),
+                10,
+                currency_cells,
+            )
+        } else if mostly(hex_numbers.len()) {
+            (hex_numbers, 16, 0)
+        } else {
+            continue;
+        };
+
+        let lower_name = name.to_lowercase();
+        columns.push(NumericColumn {
+            name: String::from(name.trim()),
+            financial: FINANCIAL_HEADERS.iter().any(|x| lower_name.contains(x))
+                || currency_cells * 2 >= values.len(),
+            values,
+            radix,
+        });
+    }
+    return columns;
+}
+
+/// Test every numeric column of a delimited text file. Financial columns
+/// are reported either way, other columns only when they deviate from the
+/// law.
+pub(crate) fn benford_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = std::fs::read(file)?;
+    /* Spreadsheet exports are often cp1252, which matches Latin-1 for the
+    currency symbols that matter here. */
+    let text = match String::from_utf8(data) {
+        Ok(text) => text,
+        Err(error) => error.into_bytes().iter().map(|x| *x as char).collect(),
+    };
+    let mut findings = Vec::new();
+
+    for column in numeric_columns(&text) {
+        if !column.is_testable() {
+            continue;
+        }
+        let tests: Vec<BenfordTest> = [DigitTest::First, DigitTest::Second, DigitTest::FirstTwo]
+            .iter()
+            .filter_map(|x| BenfordTest::new(&column.values, *x, column.radix))
+            .collect();
+        /* The first two digits test needs far more values than most files
+        have before its limits mean anything, so it is only reported. */
+        let deviates = tests
+            .iter()
+            .filter(|x| x.test != DigitTest::FirstTwo)
+            .any(|x| x.conformity == Conformity::Nonconforming);
+
+        let mut finding = match (column.financial, deviates) {
+            (true, true) => Finding::new(
+                file,
+                Severity::High,
+                "Financial figures that do not follow Benford's law",
+            ),
+            (true, false) => Finding::new(file, Severity::Medium, "Financial figures"),
+            (false, true) => Finding::new(
+                file,
+                Severity::Low,
+                "Numbers that do not follow Benford's law",
+            ),
+            (false, false) => continue,
+        };
+        finding.add_detail("column", column.name.clone());
+        finding.add_detail("values", column.values.len().to_string());
+        if column.radix != 10 {
+            finding.add_detail("radix", column.radix.to_string());
+        }
+        for test in &tests {
+            let digits = test.test.description();
+            finding.add_detail(&format!("{digits} MAD"), format!("{:.4}", test.mad));
+            finding.add_detail(
+                &format!("{digits} conformity"),
+                String::from(test.conformity.description()),
+            );
+            finding.add_detail(
+                &format!("{digits} chi-square"),
+                format!(
+                    "{:.2}{}",
+                    test.chi_square,
+                    if test.chi_square_significant() {
+                        " (significant at 5%)"
+                    } else {
+                        ""
+                    }
+                ),
+            );
+        }
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BENFORD: &str = "./tests/testing_files/benford";
+
+    fn findings(file: &str) -> Vec<Finding> {
+        return benford_findings(&Path::new(BENFORD).join(file)).unwrap();
+    }
+
+    #[test]
+    fn split_quoted_records() {
+        assert_eq!(
+            split_record(r#"1,"Globex, Inc.","say ""hi""",,x"#, ','),
+            vec!["1", "Globex, Inc.", "say \"hi\"", "", "x"]
+        );
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(detect_delimiter(&["a,b;c", "1,2;3"]), Some(','));
+        assert_eq!(detect_delimiter(&["a\tb\tc", "1\t2,5\t3"]), Some('\t'));
+        assert_eq!(detect_delimiter(&["no delimiters", "here"]), None);
+    }
+
+    #[test]
+    fn clean_numbers() {
+        assert_eq!(
+            clean_number(" $1,234.50 "),
+            Some((String::from("1234.50"), true))
+        );
+        assert_eq!(
+            clean_number("(£20.00)"),
+            Some((String::from("-20.00"), true))
+        );
+        assert_eq!(clean_number("42"), Some((String::from("42"), false)));
+        assert_eq!(clean_number("2024-01-01"), None);
+        assert_eq!(clean_number("inf"), None);
+    }
+
+    #[test]
+    fn benford_sequence_conforms() {
+        /* Powers of two follow Benford's law closely. */
+        let numbers: Vec<String> = (0..1000).map(|x| 2f64.powi(x).to_string()).collect();
+        let test = BenfordTest::new(&numbers, DigitTest::First, 10).unwrap();
+        assert_eq!(test.values, 1000);
+        assert_eq!(test.conformity, Conformity::Close);
+        assert!(!test.chi_square_significant());
+    }
+
+    #[test]
+    fn uniform_digits_do_not_conform() {
+        let numbers: Vec<String> = (0..900).map(|x| (100 + x).to_string()).collect();
+        let test = BenfordTest::new(&numbers, DigitTest::First, 10).unwrap();
+        assert_eq!(test.conformity, Conformity::Nonconforming);
+        assert!(test.chi_square_significant());
+        assert!(0.0596 < test.mad && test.mad < 0.0598);
+
+        /* Every second digit appears equally often, which is near enough. */
+        let test = BenfordTest::new(&numbers, DigitTest::Second, 10).unwrap();
+        assert_eq!(test.conformity, Conformity::Acceptable);
+
+        let test = BenfordTest::new(&numbers, DigitTest::FirstTwo, 10).unwrap();
+        assert_eq!(test.degrees_of_freedom, 89);
+        assert_eq!(test.conformity, Conformity::Nonconforming);
+    }
+
+    #[test]
+    fn columns_from_csv() {
+        let text = std::fs::read_to_string(Path::new(BENFORD).join("ledger.csv")).unwrap();
+        let columns = numeric_columns(&text);
+        let names: Vec<(&str, bool, bool)> = columns
+            .iter()
+            .map(|x| (x.name.as_str(), x.financial, x.is_testable()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Invoice", true, false),
+                ("Amount", true, true),
+                ("Tax Rate", false, false),
+            ]
+        );
+        assert_eq!(columns[1].values.len(), 2000);
+    }
+
+    #[test]
+    fn real_financial_figures() {
+        let found = findings("ledger.csv");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(found[0].description, "Financial figures");
+        assert_eq!(found[0].detail("column"), Some(&String::from("Amount")));
+        assert_eq!(
+            found[0].detail("first digit conformity"),
+            Some(&String::from("acceptable conformity"))
+        );
+    }
+
+    #[test]
+    fn non_utf8_files() {
+        let found = findings("ledger_cp1252.csv");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].description, "Financial figures");
+        assert_eq!(found[0].detail("column"), Some(&String::from("Amount")));
+
+        assert!(
+            benford_findings(Path::new("./tests/testing_files/entropy/backup.hc"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn fabricated_financial_figures() {
+        let found = findings("claims.tsv");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(found[0].detail("column"), Some(&String::from("payout")));
+        assert_eq!(
+            found[0].detail("first digit conformity"),
+            Some(&String::from("nonconformity"))
+        );
+        assert!(
+            found[0]
+                .detail("first digit chi-square")
+                .unwrap()
+                .ends_with("(significant at 5%)")
+        );
+    }
+
+    #[test]
+    fn non_financial_columns() {
+        let found = findings("survey.csv");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::Low);
+        assert_eq!(found[0].detail("column"), Some(&String::from("reading")));
+    }
+
+    #[test]
+    fn chi_square_critical_values() {
+        assert!((chi_square_critical(8.0) - 15.507).abs() < 0.05);
+        assert!((chi_square_critical(9.0) - 16.919).abs() < 0.05);
+        assert!((chi_square_critical(89.0) - 112.022).abs() < 0.1);
+    }
+
+    #[test]
+    fn hexadecimal_columns() {
+        assert_eq!(clean_hex(" 0xAB12 "), Some(String::from("0xab12")));
+        assert_eq!(clean_hex("AB12"), None);
+        assert_eq!(clean_hex("0x"), None);
+
+        let found = findings("transfers.csv");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(found[0].detail("column"), Some(&String::from("value_wei")));
+        assert_eq!(found[0].detail("radix"), Some(&String::from("16")));
+        assert!(found[0].detail("first two digits MAD").is_some());
+        assert_eq!(found[1].severity, Severity::Low);
+        assert_eq!(found[1].detail("column"), Some(&String::from("nonce")));
+        assert_eq!(
+            found[1].detail("first digit conformity"),
+            Some(&String::from("nonconformity"))
+        );
+    }
+
+    #[test]
+    fn too_few_values() {
+        assert!(findings("short.csv").is_empty());
+    }
+}
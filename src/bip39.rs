@@ -0,0 +1,267 @@
+/*
+ * Find BIP-39 mnemonic seed phrases written into text files. A run of words
+ * from the English wordlist is only reported as a seed when its checksum
+ * matches, which rules out almost all ordinary prose.
+ */
+
+use crate::crypto::sha256;
+use crate::findings::{Finding, Severity};
+use std::error::Error;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// The 2048 word English list from the BIP-39 specification, in order.
+const ENGLISH_WORDLIST: &str = include_str!("bip39_english.txt");
+
+/// Mnemonics encode 128 to 256 bits of entropy in steps of 32 bits.
+const VALID_LENGTHS: [usize; 5] = [24, 21, 18, 15, 12];
+
+/// Runs of wordlist words longer than this are a copy of the wordlist or a
+/// word game rather than a seed backup, and would match checksums by chance.
+const MAX_RUN_LENGTH: usize = 48;
+
+fn wordlist() -> &'static Vec<&'static str> {
+    static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+    return WORDLIST.get_or_init(|| ENGLISH_WORDLIST.split_whitespace().collect());
+}
+
+/// The position of a word in the English wordlist.
+pub(crate) fn word_index(word: &str) -> Option<usize> {
+    return wordlist().binary_search(&word).ok();
+}
+
+/// Check the checksum bits that end a mnemonic. The last word carries
+/// `words / 3` bits of the SHA-256 of the entropy the other bits encode.
+pub(crate) fn checksum_matches(words: &[&str]) -> bool {
+    if !VALID_LENGTHS.contains(&words.len()) {
+        return false;
+    }
+    let Some(indexes) = words
+        .iter()
+        .map(|x| word_index(x))
+        .collect::<Option<Vec<usize>>>()
+    else {
+        return false;
+    };
+
+    let bits: Vec<bool> = indexes
+        .iter()
+        .flat_map(|index| (0..11).rev().map(move |bit| (index >> bit) & 1 == 1))
+        .collect();
+    let checksum_bits = words.len() / 3;
+    let (entropy_bits, checksum) = bits.split_at(bits.len() - checksum_bits);
+
+    let entropy: Vec<u8> = entropy_bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, bit| (acc << 1) | *bit as u8))
+        .collect();
+    let hash = sha256(&entropy);
+
+    return checksum
+        .iter()
+        .enumerate()
+        .all(|(idx, bit)| ((hash[idx / 8] >> (7 - idx % 8)) & 1 == 1) == *bit);
+}
+
+/// A run of wordlist words found in a text file.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct SeedPhrase {
+    /// The line the phrase starts on, counted from one.
+    pub(crate) line: usize,
+    pub(crate) word_count: usize,
+    pub(crate) checksum_valid: bool,
+}
+
+/// Search text for seed phrases. Words may be split across lines and numbered,
+/// as they usually are when a backup is typed up, but any other word ends the
+/// run.
+pub(crate) fn find_seed_phrases(text: &str) -> Vec<SeedPhrase> {
+    let mut phrases = Vec::new();
+    let mut run: Vec<(String, usize)> = Vec::new();
+
+    let lines = text.lines().enumerate().map(|(idx, line)| (idx + 1, line));
+    for (line_num, line) in lines.chain(std::iter::once((0, ""))) {
+        let mut tokens: Vec<String> = line
+            .split(|x: char| !x.is_ascii_alphanumeric())
+            .filter(|x| !x.is_empty() && !x.chars().all(|c| c.is_ascii_digit()))
+            .map(|x| x.to_ascii_lowercase())
+            .collect();
+        /* A sentinel token flushes the final run once the text ends. */
+        if line_num == 0 {
+            tokens.push(String::new());
+        }
+
+        for token in tokens {
+            if word_index(&token).is_some() {
+                run.push((token, line_num));
+                continue;
+            }
+            if run.len() >= VALID_LENGTHS[VALID_LENGTHS.len() - 1] && run.len() <= MAX_RUN_LENGTH {
+                phrases.extend(phrases_in_run(&run));
+            }
+            run.clear();
+        }
+    }
+    return phrases;
+}
+
+/// Pick the mnemonics out of a run, preferring the longest at each position.
+/// When none have a valid checksum a run of exactly mnemonic length is still
+/// reported, as it may be a backup with a mistyped word.
+fn phrases_in_run(run: &[(String, usize)]) -> Vec<SeedPhrase> {
+    let words: Vec<&str> = run.iter().map(|(x, _)| x.as_str()).collect();
+    let mut phrases = Vec::new();
+
+    let mut start = 0;
+    while start < words.len() {
+        let found = VALID_LENGTHS
+            .iter()
+            .filter(|length| start + *length <= words.len())
+            .find(|length| checksum_matches(&words[start..start + *length]));
+
+        match found {
+            Some(length) => {
+                phrases.push(SeedPhrase {
+                    line: run[start].1,
+                    word_count: *length,
+                    checksum_valid: true,
+                });
+                start += length;
+            }
+            None => start += 1,
+        }
+    }
+
+    if phrases.is_empty() && VALID_LENGTHS.contains(&words.len()) {
+        phrases.push(SeedPhrase {
+            line: run[0].1,
+            word_count: words.len(),
+            checksum_valid: false,
+        });
+    }
+    return phrases;
+}
+
+/// Report each seed phrase in a text file. The words themselves are never
+/// included in the finding.
+pub(crate) fn seed_phrase_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(file)?;
+    let mut findings = Vec::new();
+
+    for phrase in find_seed_phrases(&text) {
+        let mut finding = if phrase.checksum_valid {
+            Finding::new(file, Severity::Critical, "BIP-39 seed phrase")
+        } else {
+            Finding::new(
+                file,
+                Severity::Medium,
+                "Word sequence resembling a BIP-39 seed phrase",
+            )
+        };
+        finding.add_detail("line", phrase.line.to_string());
+        finding.add_detail("words", phrase.word_count.to_string());
+        finding.add_detail(
+            "checksum",
+            String::from(if phrase.checksum_valid {
+                "valid"
+            } else {
+                "invalid"
+            }),
+        );
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wordlist_is_complete() {
+        assert_eq!(wordlist().len(), 2048);
+        assert_eq!(word_index("abandon"), Some(0));
+        assert_eq!(word_index("zoo"), Some(2047));
+        assert_eq!(word_index("satoshi"), Some(1531));
+        assert_eq!(word_index("bitcoin"), None);
+    }
+
+    #[test]
+    fn checksum_test_vectors() {
+        /* Vectors from the reference implementation. */
+        assert!(checksum_matches(
+            &"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+                .split(' ')
+                .collect::<Vec<&str>>()
+        ));
+        assert!(checksum_matches(
+            &"legal winner thank year wave sausage worth useful legal winner thank yellow"
+                .split(' ')
+                .collect::<Vec<&str>>()
+        ));
+        assert!(checksum_matches(
+            &concat!(
+                "hamster diagram private dutch cause delay private meat slide toddler razor book ",
+                "happy fancy gospel tennis maple dilemma loan word shrug inflict delay length"
+            )
+            .split(' ')
+            .collect::<Vec<&str>>()
+        ));
+        assert!(!checksum_matches(
+            &"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+                .split(' ')
+                .collect::<Vec<&str>>()
+        ));
+    }
+
+    #[test]
+    fn numbered_backup() {
+        let phrases = find_seed_phrases(concat!(
+            "Recovery words:\n",
+            "1. legal 2. winner 3. thank 4. year\n",
+            "5. wave 6. sausage 7. worth 8. useful\n",
+            "9. legal 10. winner 11. thank 12. yellow\n",
+            "Written on 2024-01-01\n",
+        ));
+        assert_eq!(
+            phrases,
+            vec![SeedPhrase {
+                line: 2,
+                word_count: 12,
+                checksum_valid: true
+            }]
+        );
+    }
+
+    #[test]
+    fn prose_is_ignored() {
+        assert!(
+            find_seed_phrases(
+                "The quick brown fox jumps over the lazy dog and then runs away into the forest."
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn seed_phrase_files() {
+        let findings = seed_phrase_findings(Path::new(
+            "./tests/testing_files/seed_phrases/wallet_backup.txt",
+        ))
+        .unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].detail("words"), Some(&String::from("24")));
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert_eq!(
+            findings[1].detail("checksum"),
+            Some(&String::from("invalid"))
+        );
+
+        assert!(
+            seed_phrase_findings(Path::new("./tests/testing_files/seed_phrases/wordlist.txt"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
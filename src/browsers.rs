@@ -0,0 +1,501 @@
+/*
+ * Discover the profiles of Chromium and Gecko based browsers and mail
+ * clients, wherever their data directories are, and report which of the
+ * files holding saved logins, cookies and keys each profile has. Nothing is
+ * decrypted or read from the databases themselves.
+ */
+
+use crate::deployment::ini_values;
+use crate::findings::{Finding, Severity};
+use crate::json::JsonValue;
+use crate::os_interactions::all_files;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Every Chromium user data directory holds this file, next to the profiles.
+const CHROMIUM_LOCAL_STATE: &str = "Local State";
+
+/// Every Chromium profile directory holds this file.
+const CHROMIUM_PREFERENCES: &str = "Preferences";
+
+/// Gecko applications list their profiles in this file.
+const GECKO_PROFILES_INI: &str = "profiles.ini";
+
+/// The files in a profile that hold secrets, in the order they are
+/// reported. The first in each list holds saved logins. Chromium moved the
+/// cookie database into a Network directory in version 96.
+const CHROMIUM_ARTEFACTS: [(&str, &[&str]); 3] = [
+    ("Login Data", &["Login Data"]),
+    ("Cookies", &["Network/Cookies", "Cookies"]),
+    ("Web Data", &["Web Data"]),
+];
+const GECKO_ARTEFACTS: [(&str, &[&str]); 2] =
+    [("logins.json", &["logins.json"]), ("key4.db", &["key4.db"])];
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum BrowserFamily {
+    Chromium,
+    Gecko,
+}
+
+/// Where a browser keeps its data, as the trailing components of the path
+/// on Windows, Linux and macOS.
+struct BrowserLocation {
+    name: &'static str,
+    family: BrowserFamily,
+    paths: &'static [&'static str],
+}
+
+const BROWSER_LOCATIONS: [BrowserLocation; 8] = [
+    BrowserLocation {
+        name: "Google Chrome",
+        family: BrowserFamily::Chromium,
+        paths: &["Google/Chrome/User Data", "google-chrome", "Google/Chrome"],
+    },
+    BrowserLocation {
+        name: "Microsoft Edge",
+        family: BrowserFamily::Chromium,
+        paths: &[
+            "Microsoft/Edge/User Data",
+            "microsoft-edge",
+            "Microsoft Edge",
+        ],
+    },
+    BrowserLocation {
+        name: "Brave",
+        family: BrowserFamily::Chromium,
+        paths: &[
+            "BraveSoftware/Brave-Browser/User Data",
+            "BraveSoftware/Brave-Browser",
+        ],
+    },
+    BrowserLocation {
+        name: "Opera",
+        family: BrowserFamily::Chromium,
+        paths: &[
+            "Opera Software/Opera Stable",
+            "opera",
+            "com.operasoftware.Opera",
+        ],
+    },
+    BrowserLocation {
+        name: "Vivaldi",
+        family: BrowserFamily::Chromium,
+        paths: &["Vivaldi/User Data", "vivaldi", "Vivaldi"],
+    },
+    BrowserLocation {
+        name: "Chromium",
+        family: BrowserFamily::Chromium,
+        paths: &["Chromium/User Data", "chromium", "Chromium"],
+    },
+    BrowserLocation {
+        name: "Firefox",
+        family: BrowserFamily::Gecko,
+        paths: &["Mozilla/Firefox", ".mozilla/firefox", "Firefox"],
+    },
+    BrowserLocation {
+        name: "Thunderbird",
+        family: BrowserFamily::Gecko,
+        paths: &["Thunderbird", ".thunderbird"],
+    },
+];
+
+/// A single browser profile and the secret holding files found in it.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct BrowserProfile {
+    pub(crate) browser: String,
+    pub(crate) family: BrowserFamily,
+    pub(crate) directory: PathBuf,
+    /// The name the user gave the profile, if the browser records one.
+    pub(crate) name: Option<String>,
+    /// Each expected file and its size in bytes, if present.
+    pub(crate) artefacts: Vec<(String, Option<u64>)>,
+    /// Whether Local State holds the key Chromium encrypts saved logins and
+    /// cookies with. `None` for Gecko, or when Local State is unreadable.
+    pub(crate) encrypted_key: Option<bool>,
+}
+
+impl BrowserProfile {
+    /// Does the profile hold any saved logins.
+    pub(crate) fn has_logins(&self) -> bool {
+        return self
+            .artefacts
+            .first()
+            .is_some_and(|(_, size)| size.is_some());
+    }
+}
+
+/// Name the browser a data directory belongs to from the end of its path.
+fn browser_name(directory: &Path, family: BrowserFamily) -> String {
+    let components: Vec<String> = directory
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+
+    let location = BROWSER_LOCATIONS
+        .iter()
+        .filter(|x| x.family == family)
+        .find(|x| {
+            x.paths.iter().any(|path| {
+                let suffix: Vec<String> = path.split('/').map(|x| x.to_lowercase()).collect();
+                components.ends_with(&suffix)
+            })
+        });
+
+    return String::from(match (location, family) {
+        (Some(location), _) => location.name,
+        (None, BrowserFamily::Chromium) => "Chromium based browser",
+        (None, BrowserFamily::Gecko) => "Gecko based application",
+    });
+}
+
+/// The size of the first of several possible paths that exists in a profile.
+fn artefact_sizes(directory: &Path, artefacts: &[(&str, &[&str])]) -> Vec<(String, Option<u64>)> {
+    return artefacts
+        .iter()
+        .map(|(name, paths)| {
+            let size = paths
+                .iter()
+                .filter_map(|x| std::fs::metadata(directory.join(x)).ok())
+                .find(|x| x.is_file())
+                .map(|x| x.len());
+            (String::from(*name), size)
+        })
+        .collect();
+}
+
+/// Is a directory name one Chromium gives to a user's profile. Guest and
+/// system profiles are thrown away when the browser closes.
+fn is_chromium_profile_name(name: &str) -> bool {
+    return name == "Default"
+        || name
+            .strip_prefix("Profile ")
+            .is_some_and(|x| !x.is_empty() && x.chars().all(|c| c.is_ascii_digit()));
+}
+
+/// Find the profiles in a Chromium user data directory. Opera keeps its only
+/// profile in the user data directory itself.
+fn chromium_profiles(user_data: &Path) -> Result<Vec<BrowserProfile>, Box<dyn Error>> {
+    let browser = browser_name(user_data, BrowserFamily::Chromium);
+    let local_state = std::fs::read_to_string(user_data.join(CHROMIUM_LOCAL_STATE))
+        .ok()
+        .and_then(|x| JsonValue::parse(&x).ok());
+    let encrypted_key = local_state.as_ref().map(|x| {
+        x.path(&["os_crypt", "encrypted_key"])
+            .and_then(|x| x.as_str())
+            .is_some_and(|x| !x.is_empty())
+    });
+
+    let mut directories = Vec::new();
+    if user_data.join(CHROMIUM_PREFERENCES).is_file() {
+        directories.push(user_data.to_path_buf());
+    }
+    let mut subdirectories: Vec<PathBuf> = std::fs::read_dir(user_data)?
+        .filter_map(|x| x.ok())
+        .map(|x| x.path())
+        .filter(|x| x.is_dir())
+        .filter(|x| {
+            x.file_name()
+                .and_then(|x| x.to_str())
+                .is_some_and(is_chromium_profile_name)
+        })
+        .collect();
+    subdirectories.sort();
+    directories.extend(subdirectories);
+
+    return Ok(directories
+        .into_iter()
+        .map(|directory| {
+            let dir_name = directory
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = local_state
+                .as_ref()
+                .and_then(|x| x.path(&["profile", "info_cache", &dir_name, "name"]))
+                .and_then(|x| x.as_str())
+                .map(String::from);
+
+            BrowserProfile {
+                browser: browser.clone(),
+                family: BrowserFamily::Chromium,
+                artefacts: artefact_sizes(&directory, &CHROMIUM_ARTEFACTS),
+                directory,
+                name,
+                encrypted_key,
+            }
+        })
+        .collect());
+}
+
+/// Find the profiles listed in a Gecko profiles.ini. Profiles that no
+/// longer exist on disk are left out.
+fn gecko_profiles(profiles_ini: &Path) -> Result<Vec<BrowserProfile>, Box<dyn Error>> {
+    let root = profiles_ini.parent().unwrap_or(Path::new(""));
+    let browser = browser_name(root, BrowserFamily::Gecko);
+    let values = ini_values(&String::from_utf8_lossy(&std::fs::read(profiles_ini)?));
+
+    let sections: BTreeSet<&String> = values
+        .iter()
+        .map(|(section, _, _)| section)
+        .filter(|x| x.starts_with("Profile"))
+        .collect();
+
+    let mut profiles = Vec::new();
+    for section in sections {
+        let lookup = |key: &str| {
+            values
+                .iter()
+                .find(|(x, y, _)| x == section && y.eq_ignore_ascii_case(key))
+                .map(|(_, _, value)| value.as_str())
+        };
+        let Some(path) = lookup("Path") else {
+            continue;
+        };
+        let directory = if lookup("IsRelative") == Some("0") {
+            PathBuf::from(path)
+        } else {
+            path.split('/').fold(root.to_path_buf(), |x, y| x.join(y))
+        };
+        if !directory.is_dir() {
+            continue;
+        }
+
+        profiles.push(BrowserProfile {
+            browser: browser.clone(),
+            family: BrowserFamily::Gecko,
+            name: lookup("Name").map(String::from),
+            artefacts: artefact_sizes(&directory, &GECKO_ARTEFACTS),
+            directory,
+            encrypted_key: None,
+        });
+    }
+    return Ok(profiles);
+}
+
+/// Find every Chromium and Gecko profile below a directory, such as the
+/// root of a disk image or a single user's home.
+pub(crate) fn discover_browser_profiles(
+    directory: &Path,
+) -> Result<Vec<BrowserProfile>, Box<dyn Error>> {
+    let files = all_files(directory)?;
+    let mut profiles = Vec::new();
+
+    let user_data_directories: BTreeSet<&Path> = files
+        .iter()
+        .filter(|x| x.file_name().is_some_and(|x| x == CHROMIUM_LOCAL_STATE))
+        .filter_map(|x| x.parent())
+        .collect();
+    for user_data in user_data_directories {
+        profiles.extend(chromium_profiles(user_data)?);
+    }
+
+    let mut profile_lists: Vec<&PathBuf> = files
+        .iter()
+        .filter(|x| x.file_name().is_some_and(|x| x == GECKO_PROFILES_INI))
+        .collect();
+    profile_lists.sort();
+    for profiles_ini in profile_lists {
+        profiles.extend(gecko_profiles(profiles_ini)?);
+    }
+    return Ok(profiles);
+}
+
+/// Report every browser profile below a directory, one finding each, with
+/// the size of each file that holds logins, cookies or keys.
+pub(crate) fn browser_profile_findings(directory: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let mut findings = Vec::new();
+
+    for profile in discover_browser_profiles(directory)? {
+        let severity = if profile.has_logins() {
+            Severity::High
+        } else if profile.artefacts.iter().any(|(_, size)| size.is_some()) {
+            Severity::Medium
+        } else {
+            Severity::Low
+        };
+        let mut finding = Finding::new(
+            &profile.directory,
+            severity,
+            &format!("{} profile", profile.browser),
+        );
+
+        finding.add_detail(
+            "family",
+            String::from(match profile.family {
+                BrowserFamily::Chromium => "Chromium",
+                BrowserFamily::Gecko => "Gecko",
+            }),
+        );
+        if let Some(name) = &profile.name {
+            finding.add_detail("profile name", name.clone());
+        }
+        for (artefact, size) in &profile.artefacts {
+            finding.add_detail(
+                artefact,
+                match size {
+                    Some(size) => format!("{size} bytes"),
+                    None => String::from("not found"),
+                },
+            );
+        }
+        if profile.family == BrowserFamily::Chromium {
+            finding.add_detail(
+                "encrypted key",
+                String::from(match profile.encrypted_key {
+                    Some(true) => "present",
+                    Some(false) => "not found",
+                    None => "unreadable",
+                }),
+            );
+        }
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROWSERS: &str = "./tests/testing_files/browsers";
+
+    #[test]
+    fn chromium_profile_names() {
+        assert!(is_chromium_profile_name("Default"));
+        assert!(is_chromium_profile_name("Profile 12"));
+        assert!(!is_chromium_profile_name("Profile "));
+        assert!(!is_chromium_profile_name("Guest Profile"));
+        assert!(!is_chromium_profile_name("System Profile"));
+    }
+
+    #[test]
+    fn browser_names() {
+        assert_eq!(
+            browser_name(
+                Path::new("C:/Users/a/AppData/Local/Microsoft/Edge/User Data"),
+                BrowserFamily::Chromium
+            ),
+            "Microsoft Edge"
+        );
+        assert_eq!(
+            browser_name(
+                Path::new("/Users/a/Library/Application Support/Google/Chrome"),
+                BrowserFamily::Chromium
+            ),
+            "Google Chrome"
+        );
+        assert_eq!(
+            browser_name(
+                Path::new("/home/a/.config/vivaldi"),
+                BrowserFamily::Chromium
+            ),
+            "Vivaldi"
+        );
+        assert_eq!(
+            browser_name(
+                Path::new("/home/a/.config/thorium"),
+                BrowserFamily::Chromium
+            ),
+            "Chromium based browser"
+        );
+        assert_eq!(
+            browser_name(Path::new("/home/a/.thunderbird"), BrowserFamily::Gecko),
+            "Thunderbird"
+        );
+    }
+
+    #[test]
+    fn discover_profiles() {
+        let profiles = discover_browser_profiles(Path::new(BROWSERS)).unwrap();
+        let summary: Vec<(&str, Option<&str>)> = profiles
+            .iter()
+            .map(|x| (x.browser.as_str(), x.name.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Google Chrome", Some("Work")),
+                ("Google Chrome", Some("Personal")),
+                ("Microsoft Edge", None),
+                ("Opera", None),
+                ("Brave", None),
+                ("Firefox", Some("default-release")),
+                ("Firefox", Some("default")),
+                ("Thunderbird", Some("default")),
+            ]
+        );
+    }
+
+    #[test]
+    fn chromium_artefacts() {
+        let profiles = chromium_profiles(
+            &Path::new(BROWSERS).join("Users/alice/AppData/Local/Google/Chrome/User Data"),
+        )
+        .unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(
+            profiles[0].artefacts,
+            vec![
+                (String::from("Login Data"), Some(4096)),
+                (String::from("Cookies"), Some(2048)),
+                (String::from("Web Data"), Some(1024)),
+            ]
+        );
+        assert_eq!(profiles[0].encrypted_key, Some(true));
+        assert!(profiles[0].has_logins());
+        assert!(!profiles[1].has_logins());
+    }
+
+    #[test]
+    fn profile_findings() {
+        let findings = browser_profile_findings(Path::new(BROWSERS)).unwrap();
+        assert_eq!(findings.len(), 8);
+
+        assert_eq!(findings[0].description, "Google Chrome profile");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(
+            findings[0].detail("encrypted key"),
+            Some(&String::from("present"))
+        );
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert_eq!(
+            findings[1].detail("Login Data"),
+            Some(&String::from("not found"))
+        );
+
+        /* Edge has an old style cookie database and no key in Local State. */
+        assert_eq!(
+            findings[2].detail("Cookies"),
+            Some(&String::from("300 bytes"))
+        );
+        assert_eq!(
+            findings[2].detail("encrypted key"),
+            Some(&String::from("not found"))
+        );
+        assert_eq!(
+            findings[4].detail("encrypted key"),
+            Some(&String::from("unreadable"))
+        );
+
+        assert_eq!(findings[5].description, "Firefox profile");
+        assert_eq!(findings[5].severity, Severity::High);
+        assert_eq!(
+            findings[5].detail("key4.db"),
+            Some(&String::from("294912 bytes"))
+        );
+        assert_eq!(findings[5].detail("encrypted key"), None);
+        assert_eq!(findings[6].severity, Severity::Low);
+        assert_eq!(findings[7].severity, Severity::Medium);
+    }
+
+    #[test]
+    fn no_profiles() {
+        assert!(
+            browser_profile_findings(Path::new("./tests/testing_files/wallets"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,147 @@
+/*
+ * A cursor for pulling fixed size fields out of binary file formats.
+ */
+
+use std::io::{Error, ErrorKind};
+
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        return ByteReader { data, position: 0 };
+    }
+
+    /// The number of bytes left after the current position.
+    pub(crate) fn remaining(&self) -> usize {
+        return self.data.len().saturating_sub(self.position);
+    }
+
+    /// Move the cursor to an absolute position in the data.
+    pub(crate) fn seek(&mut self, position: usize) -> Result<(), Error> {
+        if position > self.data.len() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("Cannot seek to {position} past the end of the data"),
+            ));
+        }
+        self.position = position;
+        return Ok(());
+    }
+
+    /// Move the cursor forward without reading.
+    pub(crate) fn skip(&mut self, num_bytes: usize) -> Result<(), Error> {
+        self.read_bytes(num_bytes)?;
+        return Ok(());
+    }
+
+    /// Read a slice of bytes and advance the cursor past them.
+    pub(crate) fn read_bytes(&mut self, num_bytes: usize) -> Result<&'a [u8], Error> {
+        if num_bytes > self.remaining() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "Needed {num_bytes} bytes at {} but only {} remain",
+                    self.position,
+                    self.remaining()
+                ),
+            ));
+        }
+        let slice = &self.data[self.position..self.position + num_bytes];
+        self.position += num_bytes;
+        return Ok(slice);
+    }
+
+    /// Read a fixed size array of bytes.
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        return Ok(array);
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, Error> {
+        return Ok(self.read_array::<1>()?[0]);
+    }
+
+    pub(crate) fn read_u16_le(&mut self) -> Result<u16, Error> {
+        return Ok(u16::from_le_bytes(self.read_array()?));
+    }
+
+    pub(crate) fn read_u32_le(&mut self) -> Result<u32, Error> {
+        return Ok(u32::from_le_bytes(self.read_array()?));
+    }
+
+    pub(crate) fn read_u64_le(&mut self) -> Result<u64, Error> {
+        return Ok(u64::from_le_bytes(self.read_array()?));
+    }
+
+    pub(crate) fn read_u16_be(&mut self) -> Result<u16, Error> {
+        return Ok(u16::from_be_bytes(self.read_array()?));
+    }
+
+    pub(crate) fn read_u32_be(&mut self) -> Result<u32, Error> {
+        return Ok(u32::from_be_bytes(self.read_array()?));
+    }
+
+    pub(crate) fn read_u64_be(&mut self) -> Result<u64, Error> {
+        return Ok(u64::from_be_bytes(self.read_array()?));
+    }
+}
+
+/// Create the error returned when a binary structure is not as expected.
+pub(crate) fn invalid_data(message: &str) -> Error {
+    return Error::new(ErrorKind::InvalidData, String::from(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_mixed_endian() {
+        let data = vec![0x01, 0x02, 0x00, 0x00, 0x02, 0x01, 0xFF];
+        let mut reader = ByteReader::new(&data);
+
+        assert_eq!(reader.read_u32_le().unwrap(), 0x0201);
+        assert_eq!(reader.read_u16_be().unwrap(), 0x0201);
+        assert_eq!(reader.read_u8().unwrap(), 0xFF);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn read_u64() {
+        let data = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        let mut reader = ByteReader::new(&data);
+
+        assert_eq!(reader.read_u64_le().unwrap(), 1);
+        assert_eq!(reader.read_u64_be().unwrap(), 1);
+    }
+
+    #[test]
+    fn seek_and_skip() {
+        let data = vec![0, 1, 2, 3, 4, 5];
+        let mut reader = ByteReader::new(&data);
+
+        reader.seek(4).unwrap();
+        assert_eq!(reader.read_u8().unwrap(), 4);
+        reader.seek(0).unwrap();
+        reader.skip(2).unwrap();
+        assert_eq!(reader.read_bytes(2).unwrap(), &[2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn read_past_end() {
+        let data = vec![0, 1, 2];
+        ByteReader::new(&data).read_u32_le().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn seek_past_end() {
+        let data = vec![0, 1, 2];
+        ByteReader::new(&data).seek(4).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+/*
+ * Statistics over the counts of all 256 byte values in some data.
+ */
+
+use crate::frequency_analysis::shannon_entropy_vec;
+use crate::os_interactions::file_byte_cnt;
+use std::error::Error;
+use std::path::Path;
+
+/// A histogram of byte values and the statistics drawn from it.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct ByteStats {
+    pub(crate) counts: Vec<usize>,
+    pub(crate) total: usize,
+}
+
+impl ByteStats {
+    pub(crate) fn new() -> Self {
+        return ByteStats {
+            counts: vec![0; 256],
+            total: 0,
+        };
+    }
+
+    pub(crate) fn from_bytes(data: &[u8]) -> Self {
+        let mut stats = ByteStats::new();
+        stats.add(data);
+        return stats;
+    }
+
+    /// Count the bytes of a file, or of an even sample of at most
+    /// `sample_size` bytes from across it.
+    pub(crate) fn from_file(file: &Path, sample_size: Option<u64>) -> Result<Self, Box<dyn Error>> {
+        let counts = file_byte_cnt(file, sample_size)?;
+        return Ok(ByteStats {
+            total: counts.iter().sum(),
+            counts,
+        });
+    }
+
+    /// Count more data, so a stream can be counted a piece at a time.
+    pub(crate) fn add(&mut self, data: &[u8]) {
+        for byte in data {
+            self.counts[*byte as usize] += 1;
+        }
+        self.total += data.len();
+    }
+
+    /// Shannon entropy in bits a byte.
+    pub(crate) fn entropy(&self) -> f64 {
+        return shannon_entropy_vec(&self.counts);
+    }
+
+    fn ratio(&self, count: usize) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        return count as f64 / self.total as f64;
+    }
+
+    /// The fraction of bytes that are printable ASCII or whitespace.
+    pub(crate) fn printable_ratio(&self) -> f64 {
+        let printable = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(byte, _)| (0x20..0x7F).contains(byte) || [0x09, 0x0A, 0x0D].contains(byte))
+            .map(|(_, count)| count)
+            .sum();
+        return self.ratio(printable);
+    }
+
+    /// The fraction of bytes that are zero.
+    pub(crate) fn null_ratio(&self) -> f64 {
+        return self.ratio(self.counts[0]);
+    }
+
+    /// The most common byte values with their counts, most common first and
+    /// ties in byte order. Bytes never seen are left out.
+    pub(crate) fn top_bytes(&self, num_bytes: usize) -> Vec<(u8, usize)> {
+        let mut seen: Vec<(u8, usize)> = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(byte, count)| (byte as u8, *count))
+            .collect();
+        seen.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        seen.truncate(num_bytes);
+        return seen;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_from_bytes() {
+        let stats = ByteStats::from_bytes(b"aab\0\xff\n");
+        assert_eq!(stats.total, 6);
+        assert_eq!(stats.counts[b'a' as usize], 2);
+        assert_eq!(stats.null_ratio(), 1.0 / 6.0);
+        assert_eq!(stats.printable_ratio(), 4.0 / 6.0);
+        assert_eq!(stats.top_bytes(3), vec![(b'a', 2), (0x00, 1), (b'\n', 1)]);
+    }
+
+    #[test]
+    fn streamed_counts_match() {
+        let mut stats = ByteStats::new();
+        stats.add(b"hello ");
+        stats.add(b"world");
+        assert_eq!(stats, ByteStats::from_bytes(b"hello world"));
+    }
+
+    #[test]
+    fn uniform_entropy() {
+        let data: Vec<u8> = (0..=255).collect();
+        let stats = ByteStats::from_bytes(&data);
+        assert_eq!(stats.entropy(), 8.0);
+        assert_eq!(stats.top_bytes(1000).len(), 256);
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats = ByteStats::new();
+        assert_eq!(stats.entropy(), 0.0);
+        assert_eq!(stats.printable_ratio(), 0.0);
+        assert_eq!(stats.null_ratio(), 0.0);
+        assert!(stats.top_bytes(5).is_empty());
+    }
+
+    #[test]
+    fn stats_from_file() {
+        let file = Path::new("./tests/testing_files/entropy/firmware.bin");
+        assert_eq!(
+            ByteStats::from_file(file, None).unwrap(),
+            ByteStats::from_bytes(&std::fs::read(file).unwrap())
+        );
+    }
+
+    #[test]
+    fn sampled_file() {
+        let stats = ByteStats::from_file(
+            Path::new("./tests/testing_files/entropy/backup.hc"),
+            Some(8192),
+        )
+        .unwrap();
+        assert_eq!(stats.total, 8192);
+        assert!(stats.entropy() > 7.9);
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * The hashes and ciphers needed to check whether a credential store can be
+ * opened without a password, as thin wrappers over the RustCrypto crates so
+ * no cryptography is implemented here.
+ */
+
+use aes::cipher::{BlockDecrypt, KeyInit};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+pub(crate) fn sha1(data: &[u8]) -> [u8; 20] {
+    return Sha1::digest(data).into();
+}
+
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    return Sha256::digest(data).into();
+}
+
+pub(crate) fn sha512(data: &[u8]) -> [u8; 64] {
+    return Sha512::digest(data).into();
+}
+
+/// PBKDF2 from RFC 8018 using HMAC-SHA-512.
+pub(crate) fn pbkdf2_sha512(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    length: usize,
+) -> Vec<u8> {
+    let mut derived = vec![0u8; length];
+    pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, iterations, &mut derived);
+    return derived;
+}
+
+/// An expanded AES-256 key that can decrypt single blocks.
+pub(crate) struct Aes256 {
+    cipher: aes::Aes256,
+}
+
+impl Aes256 {
+    pub(crate) fn new(key: &[u8; 32]) -> Self {
+        return Aes256 {
+            cipher: aes::Aes256::new(key.into()),
+        };
+    }
+
+    pub(crate) fn decrypt_block(&self, block: &[u8; 16]) -> [u8; 16] {
+        let mut decrypted = (*block).into();
+        self.cipher.decrypt_block(&mut decrypted);
+        return decrypted.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        return bytes.iter().map(|x| format!("{x:02x}")).collect();
+    }
+
+    #[test]
+    fn sha1_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    #[test]
+    fn sha256_vectors() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn sha512_vectors() {
+        assert_eq!(
+            hex(&sha512(b"abc")),
+            concat!(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a",
+                "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+        );
+        assert_eq!(hex(&sha512(&[b'a'; 200]))[..16], *"4b11459c33f52a22");
+    }
+
+    #[test]
+    fn pbkdf2_sha512_vector() {
+        assert_eq!(
+            hex(&pbkdf2_sha512(b"password", b"salt", 2, 32)),
+            "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53c"
+        );
+    }
+
+    #[test]
+    fn aes256_vector() {
+        /* FIPS-197 appendix C.3. */
+        let key: [u8; 32] = core::array::from_fn(|x| x as u8);
+        let ciphertext = [
+            0x8E, 0xA2, 0xB7, 0xCA, 0x51, 0x67, 0x45, 0xBF, 0xEA, 0xFC, 0x49, 0x90, 0x4B, 0x49,
+            0x60, 0x89,
+        ];
+        assert_eq!(
+            hex(&Aes256::new(&key).decrypt_block(&ciphertext)),
+            "00112233445566778899aabbccddeeff"
+        );
+    }
+}
@@ -0,0 +1,743 @@
+/*
+ * Find the passwords left in Windows deployment and web server files:
+ * unattended setup answer files, sysprep.inf, Group Policy preference XML
+ * and ASP.NET or IIS configuration. Files are recognised by name and then
+ * confirmed by their structure before any field is reported.
+ */
+
+use crate::byte_reader::invalid_data;
+use crate::crypto::Aes256;
+use crate::encoding::base64_decode;
+use crate::findings::{Finding, Severity};
+use crate::xml::XmlElement;
+use std::error::Error;
+use std::path::Path;
+
+/// The AES-256 key every Group Policy preference password is encrypted
+/// with, published by Microsoft in the protocol documentation (MS-GPPREF).
+const GPP_KEY: [u8; 32] = [
+    0x4e, 0x99, 0x06, 0xe8, 0xfc, 0xb6, 0x6c, 0xc9, 0xfa, 0xf4, 0x93, 0x10, 0x62, 0x0f, 0xfe, 0xe8,
+    0xf4, 0x96, 0xe8, 0x06, 0xcc, 0x05, 0x79, 0x90, 0x20, 0x9b, 0x09, 0xa4, 0x33, 0xb6, 0x6c, 0x1b,
+];
+
+/// Windows setup overwrites the passwords it has used with this text.
+const SCRUBBED_PASSWORD: &str = "*SENSITIVE*DATA*DELETED*";
+
+/// Attributes that name the account a Group Policy preference runs as.
+const GPP_USER_ATTRIBUTES: [&str; 4] = ["userName", "accountName", "runAs", "username"];
+
+/// Application setting names that suggest the value is a secret.
+const SECRET_SETTING_NAMES: [&str; 6] = ["password", "passwd", "pwd", "secret", "apikey", "token"];
+
+/// A password stored in the ini style answer files used before Windows
+/// Vista, such as sysprep.inf and winnt.sif.
+struct IniCredential {
+    name: &'static str,
+    section: &'static str,
+    key: &'static str,
+    /// Keys in the same section worth reporting alongside the password.
+    context_keys: &'static [&'static str],
+}
+
+const INI_CREDENTIALS: [IniCredential; 2] = [
+    IniCredential {
+        name: "Windows setup administrator password",
+        section: "GuiUnattended",
+        key: "AdminPassword",
+        context_keys: &["EncryptedAdminPassword", "AutoLogon"],
+    },
+    IniCredential {
+        name: "Domain join password",
+        section: "Identification",
+        key: "DomainAdminPassword",
+        context_keys: &["JoinDomain", "DomainAdmin"],
+    },
+];
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum DeploymentFile {
+    /// unattend.xml and autounattend.xml answer files.
+    Unattend,
+    /// sysprep.inf and the other ini style answer files.
+    Sysprep,
+    /// Groups.xml, Services.xml, ScheduledTasks.xml and the other Group
+    /// Policy preference files that can carry a `cpassword`.
+    GroupPolicyPreference,
+    /// An ASP.NET application's web.config.
+    WebConfig,
+    /// The IIS server wide applicationHost.config.
+    ApplicationHost,
+}
+
+impl DeploymentFile {
+    /// Recognise a deployment file from its name alone.
+    pub(crate) fn from_file_name(file: &Path) -> Option<Self> {
+        let name = file.file_name()?.to_str()?.to_lowercase();
+
+        return match name.as_str() {
+            "unattend.xml" | "autounattend.xml" | "unattended.xml" | "sysprep.xml" => {
+                Some(DeploymentFile::Unattend)
+            }
+            "sysprep.inf" | "winnt.sif" | "unattend.txt" => Some(DeploymentFile::Sysprep),
+            "groups.xml" | "services.xml" | "scheduledtasks.xml" | "datasources.xml"
+            | "drives.xml" | "printers.xml" => Some(DeploymentFile::GroupPolicyPreference),
+            "web.config" => Some(DeploymentFile::WebConfig),
+            "applicationhost.config" => Some(DeploymentFile::ApplicationHost),
+            _ => None,
+        };
+    }
+
+    /// The root elements a file of this kind can have.
+    fn root_elements(&self) -> &'static [&'static str] {
+        return match self {
+            DeploymentFile::Unattend => &["unattend"],
+            DeploymentFile::Sysprep => &[],
+            DeploymentFile::GroupPolicyPreference => &[
+                "Groups",
+                "NTServices",
+                "ScheduledTasks",
+                "DataSources",
+                "Drives",
+                "Printers",
+            ],
+            DeploymentFile::WebConfig | DeploymentFile::ApplicationHost => &["configuration"],
+        };
+    }
+
+    fn description(&self) -> &'static str {
+        return match self {
+            DeploymentFile::Unattend => "Windows unattended setup file",
+            DeploymentFile::Sysprep => "Windows sysprep answer file",
+            DeploymentFile::GroupPolicyPreference => "Group Policy preference file",
+            DeploymentFile::WebConfig => "ASP.NET web.config",
+            DeploymentFile::ApplicationHost => "IIS applicationHost.config",
+        };
+    }
+}
+
+/// Decrypt a Group Policy preference `cpassword`, which is AES-256-CBC with
+/// a zero IV over the UTF-16 password, base64 encoded without padding.
+pub(crate) fn decrypt_cpassword(cpassword: &str) -> Result<String, Box<dyn Error>> {
+    let data = base64_decode(cpassword)?;
+    if data.is_empty() || data.len() % 16 != 0 {
+        return Err(Box::new(invalid_data(
+            "cpassword is not a whole number of AES blocks",
+        )));
+    }
+
+    let cipher = Aes256::new(&GPP_KEY);
+    let mut previous = [0u8; 16];
+    let mut plain = Vec::with_capacity(data.len());
+    for block in data.chunks_exact(16) {
+        let block: [u8; 16] = block.try_into()?;
+        let decrypted = cipher.decrypt_block(&block);
+        plain.extend(decrypted.iter().zip(previous.iter()).map(|(x, y)| x ^ y));
+        previous = block;
+    }
+
+    /* PKCS#7 padding, checked in full so a wrong key is noticed. */
+    let padding = plain[plain.len() - 1] as usize;
+    if padding == 0
+        || padding > 16
+        || !plain[plain.len() - padding..]
+            .iter()
+            .all(|x| *x as usize == padding)
+    {
+        return Err(Box::new(invalid_data("cpassword has invalid padding")));
+    }
+    plain.truncate(plain.len() - padding);
+
+    let units: Vec<u16> = plain
+        .chunks_exact(2)
+        .map(|x| u16::from_le_bytes([x[0], x[1]]))
+        .collect();
+    return Ok(String::from_utf16(&units)?);
+}
+
+/// Start a finding for one credential field in a deployment file.
+fn field_finding(
+    file: &Path,
+    kind: DeploymentFile,
+    severity: Severity,
+    description: &str,
+    field: &str,
+) -> Finding {
+    let mut finding = Finding::new(file, severity, description);
+    finding.add_detail("file type", String::from(kind.description()));
+    finding.add_detail("field", String::from(field));
+    return finding;
+}
+
+/// Passwords in an unattend.xml are either plain text or the base64 of the
+/// UTF-16 password with the element name appended, which hides nothing.
+fn unattend_findings(file: &Path, root: &XmlElement) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (parent, element) in root.descendants() {
+        if !["AdministratorPassword", "Password"]
+            .iter()
+            .any(|x| element.name.eq_ignore_ascii_case(x))
+        {
+            continue;
+        }
+        let value = element.child_text("Value").unwrap_or(element.text.trim());
+        if value.is_empty() || value == SCRUBBED_PASSWORD {
+            continue;
+        }
+        let plain_text = element
+            .child_text("PlainText")
+            .is_none_or(|x| x.eq_ignore_ascii_case("true"));
+
+        let mut finding = field_finding(
+            file,
+            DeploymentFile::Unattend,
+            Severity::Critical,
+            "Windows unattended setup password",
+            &format!("{}/{}", parent.name, element.name),
+        );
+        for (detail, name) in [("user", "Username"), ("user", "Name"), ("domain", "Domain")] {
+            if let Some(text) = parent.child_text(name).filter(|x| !x.is_empty()) {
+                finding.add_detail(detail, String::from(text));
+            }
+        }
+        finding.add_detail(
+            "encoding",
+            String::from(if plain_text { "plain text" } else { "base64" }),
+        );
+
+        let blank = if plain_text {
+            false
+        } else {
+            let suffix: Vec<u8> = element
+                .name
+                .encode_utf16()
+                .flat_map(|x| x.to_le_bytes())
+                .collect();
+            base64_decode(value).is_ok_and(|x| x == suffix)
+        };
+        if blank {
+            finding.add_detail("weakness", String::from("Blank password"));
+        }
+        findings.push(finding);
+    }
+    return findings;
+}
+
+/// Split an ini file into its sections' keys and values. Section and key
+/// names keep their case, quotes around values are removed.
+pub(crate) fn ini_values(text: &str) -> Vec<(String, String, String)> {
+    let mut values = Vec::new();
+    let mut section = String::new();
+
+    for line in text.lines().map(|x| x.trim()) {
+        if line.starts_with(';') || line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            section = String::from(name.trim());
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            values.push((
+                section.clone(),
+                String::from(key.trim()),
+                String::from(value.trim().trim_matches('"')),
+            ));
+        }
+    }
+    return values;
+}
+
+fn sysprep_findings(file: &Path, text: &str) -> Vec<Finding> {
+    let values = ini_values(text);
+    let lookup = |section: &str, key: &str| {
+        values
+            .iter()
+            .find(|(x, y, _)| x.eq_ignore_ascii_case(section) && y.eq_ignore_ascii_case(key))
+            .map(|(_, _, value)| value.as_str())
+    };
+    let mut findings = Vec::new();
+
+    for credential in INI_CREDENTIALS.iter() {
+        let Some(password) = lookup(credential.section, credential.key) else {
+            continue;
+        };
+        if password.is_empty() {
+            continue;
+        }
+        /* With EncryptedAdminPassword set the value is a hash, not the password. */
+        let hashed = lookup(credential.section, "EncryptedAdminPassword")
+            .is_some_and(|x| x.eq_ignore_ascii_case("yes"))
+            && credential.key == "AdminPassword";
+
+        let mut finding = field_finding(
+            file,
+            DeploymentFile::Sysprep,
+            if hashed {
+                Severity::High
+            } else {
+                Severity::Critical
+            },
+            credential.name,
+            &format!("{}/{}", credential.section, credential.key),
+        );
+        for key in credential.context_keys {
+            if let Some(value) = lookup(credential.section, key) {
+                finding.add_detail(key, String::from(value));
+            }
+        }
+        finding.add_detail(
+            "encoding",
+            String::from(if hashed {
+                "password hash"
+            } else {
+                "plain text"
+            }),
+        );
+        if password == "*" {
+            finding.add_detail("weakness", String::from("Blank password"));
+        }
+        findings.push(finding);
+    }
+    return findings;
+}
+
+/// Every `cpassword` in a Group Policy preference file can be decrypted by
+/// any domain user, as the key is public. Decrypting is optional and only
+/// confirms the value; the password itself is never reported.
+fn gpp_findings(file: &Path, root: &XmlElement, decrypt: bool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (parent, element) in root.descendants() {
+        let Some(cpassword) = element.attribute("cpassword").filter(|x| !x.is_empty()) else {
+            continue;
+        };
+
+        let decrypted = if decrypt {
+            Some(decrypt_cpassword(cpassword))
+        } else {
+            None
+        };
+        let mut finding = field_finding(
+            file,
+            DeploymentFile::GroupPolicyPreference,
+            if decrypted.as_ref().is_some_and(|x| x.is_ok()) {
+                Severity::Critical
+            } else {
+                Severity::High
+            },
+            "Group Policy preference password",
+            &format!("{}/{}", parent.name, element.name),
+        );
+        if let Some(name) = parent.attribute("name") {
+            finding.add_detail("name", String::from(name));
+        }
+        if let Some(user) = GPP_USER_ATTRIBUTES
+            .iter()
+            .find_map(|x| element.attribute(x).filter(|x| !x.is_empty()))
+        {
+            finding.add_detail("user", String::from(user));
+        }
+        if let Some(changed) = parent.attribute("changed") {
+            finding.add_detail("changed", String::from(changed));
+        }
+        finding.add_detail("encoding", String::from("AES-256 with the published key"));
+
+        match decrypted {
+            Some(Ok(password)) => {
+                finding.add_detail("decrypted", String::from("yes"));
+                finding.add_detail("password length", password.chars().count().to_string());
+                if password.is_empty() {
+                    finding.add_detail("weakness", String::from("Blank password"));
+                }
+            }
+            Some(Err(_)) => finding.add_detail("decrypted", String::from("failed")),
+            None => {}
+        }
+        findings.push(finding);
+    }
+    return findings;
+}
+
+/// Split a connection string into its keywords and values.
+fn connection_string_values(text: &str) -> Vec<(String, String)> {
+    return text
+        .split(';')
+        .filter_map(|x| x.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), String::from(value.trim())))
+        .collect();
+}
+
+/// Report connection strings, application settings and account passwords
+/// in an ASP.NET or IIS configuration file. IIS encrypts the passwords it
+/// writes itself, but with a machine key stored on the same server.
+fn config_findings(file: &Path, kind: DeploymentFile, root: &XmlElement) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (parent, element) in root.descendants() {
+        /* Database connection strings. */
+        if let Some(connection) = element.attribute("connectionString") {
+            let values = connection_string_values(connection);
+            let lookup = |keys: &[&str]| {
+                values
+                    .iter()
+                    .find(|(x, value)| keys.contains(&x.as_str()) && !value.is_empty())
+                    .map(|(_, value)| value.clone())
+            };
+            if lookup(&["password", "pwd"]).is_some() {
+                let mut finding = field_finding(
+                    file,
+                    kind,
+                    Severity::High,
+                    "Database connection string password",
+                    &format!("{}/{}", parent.name, element.name),
+                );
+                if let Some(name) = element.attribute("name") {
+                    finding.add_detail("name", String::from(name));
+                }
+                if let Some(server) = lookup(&["data source", "server", "address", "host"]) {
+                    finding.add_detail("server", server);
+                }
+                if let Some(user) = lookup(&["user id", "uid", "user", "username"]) {
+                    finding.add_detail("user", user);
+                }
+                findings.push(finding);
+            }
+        }
+
+        /* Secrets kept as application settings. */
+        if parent.name == "appSettings"
+            && let (Some(key), Some(value)) = (element.attribute("key"), element.attribute("value"))
+        {
+            let lower_key = key.to_lowercase();
+            if !value.is_empty() && SECRET_SETTING_NAMES.iter().any(|x| lower_key.contains(x)) {
+                let mut finding = field_finding(
+                    file,
+                    kind,
+                    Severity::Medium,
+                    "Secret in application settings",
+                    &format!("{}/{}", parent.name, element.name),
+                );
+                finding.add_detail("name", String::from(key));
+                findings.push(finding);
+            }
+        }
+
+        /* Account passwords for impersonation, pools and virtual directories. */
+        if let Some(password) = element.attribute("password").filter(|x| !x.is_empty()) {
+            let provider = password
+                .strip_prefix("[enc:")
+                .and_then(|x| x.split(':').next());
+            let mut finding = field_finding(
+                file,
+                kind,
+                if provider.is_some() {
+                    Severity::Medium
+                } else {
+                    Severity::High
+                },
+                if provider.is_some() {
+                    "Encrypted IIS account password"
+                } else {
+                    "Web server account password"
+                },
+                &format!("{}/{}", parent.name, element.name),
+            );
+            if let Some(name) = element
+                .attribute("name")
+                .or_else(|| element.attribute("path"))
+                .or_else(|| parent.attribute("name"))
+                .or_else(|| parent.attribute("path"))
+            {
+                finding.add_detail("name", String::from(name));
+            }
+            if let Some(user) = element.attribute("userName") {
+                finding.add_detail("user", String::from(user));
+            }
+            finding.add_detail(
+                "encoding",
+                match provider {
+                    Some(provider) => format!("encrypted with {provider}"),
+                    None => String::from("plain text"),
+                },
+            );
+            findings.push(finding);
+        }
+    }
+    return findings;
+}
+
+/// Report the credentials in a deployment file. Files that are not named
+/// like one, or are but have a different structure, give no findings. Set
+/// `decrypt_gpp` to confirm Group Policy preference passwords by decrypting
+/// them with the published key.
+pub(crate) fn deployment_file_findings(
+    file: &Path,
+    decrypt_gpp: bool,
+) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let Some(kind) = DeploymentFile::from_file_name(file) else {
+        return Ok(Vec::new());
+    };
+    let data = std::fs::read(file)?;
+
+    if kind == DeploymentFile::Sysprep {
+        let text = String::from_utf8_lossy(&data);
+        return Ok(sysprep_findings(file, &text));
+    }
+
+    let root = XmlElement::parse_bytes(&data)?;
+    if !kind
+        .root_elements()
+        .iter()
+        .any(|x| root.name.eq_ignore_ascii_case(x))
+    {
+        return Ok(Vec::new());
+    }
+    /* Both IIS files are <configuration>, the server one has its own section. */
+    if kind == DeploymentFile::ApplicationHost && root.child("system.applicationHost").is_none() {
+        return Ok(Vec::new());
+    }
+
+    return Ok(match kind {
+        DeploymentFile::Unattend => unattend_findings(file, &root),
+        DeploymentFile::GroupPolicyPreference => gpp_findings(file, &root, decrypt_gpp),
+        _ => config_findings(file, kind, &root),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPLOYMENT: &str = "./tests/testing_files/deployment";
+    const PREFERENCES: &str = "Policies/{31B2F340-016D-11D2-945F-00C04FB984F9}/Machine/Preferences";
+
+    fn findings(file: &str, decrypt: bool) -> Vec<Finding> {
+        return deployment_file_findings(&Path::new(DEPLOYMENT).join(file), decrypt).unwrap();
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(
+            DeploymentFile::from_file_name(Path::new("C:/Windows/Panther/Unattend.xml")),
+            Some(DeploymentFile::Unattend)
+        );
+        assert_eq!(
+            DeploymentFile::from_file_name(Path::new("sysprep.inf")),
+            Some(DeploymentFile::Sysprep)
+        );
+        assert_eq!(
+            DeploymentFile::from_file_name(Path::new("Machine/Preferences/Drives/Drives.xml")),
+            Some(DeploymentFile::GroupPolicyPreference)
+        );
+        assert_eq!(
+            DeploymentFile::from_file_name(Path::new("applicationHost.config")),
+            Some(DeploymentFile::ApplicationHost)
+        );
+        assert_eq!(
+            DeploymentFile::from_file_name(Path::new("app.config")),
+            None
+        );
+    }
+
+    #[test]
+    fn decrypt_published_cpassword() {
+        /* The example from Microsoft's own Group Policy documentation. */
+        assert_eq!(
+            decrypt_cpassword("j1Uyj3Vx8TY9LtLZil2uAuZkFQA/4latT76ZwgdHdhw").unwrap(),
+            "Local*P4ssword!"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn decrypt_invalid_cpassword() {
+        decrypt_cpassword("AAAAAAAAAAAAAAAAAAAAAA").unwrap();
+    }
+
+    #[test]
+    fn unattend_passwords() {
+        let found = findings("Panther/unattend.xml", false);
+        assert_eq!(found.len(), 4);
+        assert!(found.iter().all(|x| x.severity == Severity::Critical));
+
+        assert_eq!(
+            found[0].detail("field"),
+            Some(&String::from("Credentials/Password"))
+        );
+        assert_eq!(found[0].detail("user"), Some(&String::from("svc_join")));
+        assert_eq!(found[0].detail("domain"), Some(&String::from("CORP")));
+        assert_eq!(
+            found[0].detail("encoding"),
+            Some(&String::from("plain text"))
+        );
+
+        assert_eq!(
+            found[1].detail("field"),
+            Some(&String::from("AutoLogon/Password"))
+        );
+        assert_eq!(found[1].detail("encoding"), Some(&String::from("base64")));
+        assert_eq!(found[1].detail("weakness"), None);
+
+        assert_eq!(
+            found[2].detail("field"),
+            Some(&String::from("UserAccounts/AdministratorPassword"))
+        );
+        assert_eq!(
+            found[2].detail("weakness"),
+            Some(&String::from("Blank password"))
+        );
+        assert_eq!(found[3].detail("user"), Some(&String::from("deploy")));
+
+        /* Secrets never make it into the findings. */
+        let details: Vec<&String> = found
+            .iter()
+            .flat_map(|x| x.details.iter().map(|(_, y)| y))
+            .collect();
+        assert!(
+            !details
+                .iter()
+                .any(|x| x.contains("Summer2024") || x.contains("LocalP@ss"))
+        );
+    }
+
+    #[test]
+    fn scrubbed_utf16_unattend() {
+        assert!(findings("Panther/autounattend.xml", false).is_empty());
+    }
+
+    #[test]
+    fn sysprep_passwords() {
+        let found = findings("sysprep.inf", false);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].severity, Severity::Critical);
+        assert_eq!(found[0].description, "Windows setup administrator password");
+        assert_eq!(
+            found[0].detail("EncryptedAdminPassword"),
+            Some(&String::from("NO"))
+        );
+        assert_eq!(
+            found[1].detail("DomainAdmin"),
+            Some(&String::from("CORP\\svc_join"))
+        );
+        assert_eq!(
+            found[1].detail("weakness"),
+            Some(&String::from("Blank password"))
+        );
+    }
+
+    #[test]
+    fn group_policy_passwords() {
+        let found = findings(&format!("{PREFERENCES}/Groups/Groups.xml"), false);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(found[0].detail("user"), Some(&String::from("LocalAdmin")));
+        assert_eq!(found[0].detail("decrypted"), None);
+
+        let found = findings(&format!("{PREFERENCES}/Groups/Groups.xml"), true);
+        assert_eq!(found[0].severity, Severity::Critical);
+        assert_eq!(found[0].detail("decrypted"), Some(&String::from("yes")));
+        assert_eq!(
+            found[0].detail("password length"),
+            Some(&String::from("15"))
+        );
+
+        /* Blank cpassword attributes are what patched clients write. */
+        let found = findings(&format!("{PREFERENCES}/Services/Services.xml"), true);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].detail("user"),
+            Some(&String::from("CORP\\svc_backup"))
+        );
+        assert_eq!(found[0].detail("name"), Some(&String::from("BackupSvc")));
+
+        let found = findings(
+            &format!("{PREFERENCES}/ScheduledTasks/ScheduledTasks.xml"),
+            true,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].detail("field"),
+            Some(&String::from("Task/Properties"))
+        );
+        assert_eq!(
+            found[0].detail("user"),
+            Some(&String::from("CORP\\svc_sync"))
+        );
+        assert_eq!(
+            found[0].detail("password length"),
+            Some(&String::from("12"))
+        );
+    }
+
+    #[test]
+    fn web_config_secrets() {
+        let found = findings("inetpub/wwwroot/web.config", false);
+        let summary: Vec<(&str, Severity)> = found
+            .iter()
+            .map(|x| (x.description.as_str(), x.severity))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Secret in application settings", Severity::Medium),
+                ("Database connection string password", Severity::High),
+                ("Web server account password", Severity::High),
+            ]
+        );
+        assert_eq!(found[0].detail("name"), Some(&String::from("SmtpPassword")));
+        assert_eq!(
+            found[1].detail("server"),
+            Some(&String::from("sql01.corp.example.com"))
+        );
+        assert_eq!(found[1].detail("user"), Some(&String::from("orders_app")));
+        assert_eq!(
+            found[2].detail("user"),
+            Some(&String::from("CORP\\web_user"))
+        );
+    }
+
+    #[test]
+    fn application_host_passwords() {
+        let found = findings("inetsrv/config/applicationHost.config", false);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(found[0].detail("name"), Some(&String::from("OrdersPool")));
+        assert_eq!(
+            found[0].detail("encoding"),
+            Some(&String::from("encrypted with IISWASOnlyAesProvider"))
+        );
+
+        assert_eq!(found[1].severity, Severity::High);
+        assert_eq!(
+            found[1].detail("field"),
+            Some(&String::from("application/virtualDirectory"))
+        );
+        assert_eq!(
+            found[1].detail("user"),
+            Some(&String::from("CORP\\share_reader"))
+        );
+    }
+
+    #[test]
+    fn unrelated_files() {
+        assert!(findings("other/web.config", true).is_empty());
+        assert!(findings("other/Groups.xml", true).is_empty());
+        assert!(
+            deployment_file_findings(Path::new("./tests/testing_files/x509/server.crt"), true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn malformed_unattend() {
+        deployment_file_findings(
+            Path::new("./tests/testing_files/deployment/broken/unattend.xml"),
+            false,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,291 @@
+/*
+ * A minimal ASN.1 DER reader for the structures found in keys and
+ * certificates.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use std::error::Error;
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+
+/// A single tag-length-value element.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) struct DerElement<'a> {
+    pub(crate) tag: u8,
+    pub(crate) contents: &'a [u8],
+}
+
+impl<'a> DerElement<'a> {
+    /// Read the first element from some data, returning it along with the
+    /// bytes that follow it.
+    pub(crate) fn parse(data: &'a [u8]) -> Result<(Self, &'a [u8]), Box<dyn Error>> {
+        let mut reader = ByteReader::new(data);
+        let tag = reader.read_u8()?;
+
+        /* Multi-byte tag numbers never appear in the structures we read. */
+        if tag & 0x1F == 0x1F {
+            return Err(Box::new(invalid_data("Unsupported long form DER tag")));
+        }
+
+        let first_len = reader.read_u8()?;
+        let length = if first_len & 0x80 == 0 {
+            first_len as usize
+        } else {
+            let num_bytes = (first_len & 0x7F) as usize;
+            if num_bytes == 0 || num_bytes > 4 {
+                return Err(Box::new(invalid_data("Unsupported DER length")));
+            }
+            reader
+                .read_bytes(num_bytes)?
+                .iter()
+                .fold(0, |acc, x| (acc << 8) | *x as usize)
+        };
+
+        let contents = reader.read_bytes(length)?;
+        return Ok((DerElement { tag, contents }, &data[reader.position..]));
+    }
+
+    /// Parse an element and insist that it has a specific tag.
+    pub(crate) fn parse_tagged(
+        data: &'a [u8],
+        tag: u8,
+    ) -> Result<(Self, &'a [u8]), Box<dyn Error>> {
+        let (element, rest) = DerElement::parse(data)?;
+        if element.tag != tag {
+            return Err(Box::new(invalid_data(&format!(
+                "Expected DER tag {tag:#04x} but found {:#04x}",
+                element.tag
+            ))));
+        }
+        return Ok((element, rest));
+    }
+
+    /// Split the contents of a constructed element into its children.
+    pub(crate) fn children(&self) -> Result<Vec<DerElement<'a>>, Box<dyn Error>> {
+        let mut children = Vec::new();
+        let mut remaining = self.contents;
+
+        while !remaining.is_empty() {
+            let (child, rest) = DerElement::parse(remaining)?;
+            children.push(child);
+            remaining = rest;
+        }
+        return Ok(children);
+    }
+
+    /// Decode an object identifier into dotted decimal form.
+    pub(crate) fn as_oid(&self) -> Result<String, Box<dyn Error>> {
+        if self.tag != TAG_OID || self.contents.is_empty() {
+            return Err(Box::new(invalid_data("Not an object identifier")));
+        }
+
+        let mut arcs: Vec<u64> = Vec::new();
+        let mut value: u64 = 0;
+
+        for byte in self.contents {
+            value = (value << 7) | (byte & 0x7F) as u64;
+            if byte & 0x80 == 0 {
+                /* The first value packs the first two arcs together. */
+                if arcs.is_empty() {
+                    let first = std::cmp::min(value / 40, 2);
+                    arcs.push(first);
+                    arcs.push(value - first * 40);
+                } else {
+                    arcs.push(value);
+                }
+                value = 0;
+            }
+        }
+        return Ok(arcs
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join("."));
+    }
+
+    /// The number of significant bits in an unsigned integer, such as a key
+    /// modulus.
+    pub(crate) fn integer_bits(&self) -> u32 {
+        return unsigned_bits(self.contents);
+    }
+
+    /// The bytes of an unsigned integer without any leading zero padding.
+    pub(crate) fn unsigned_value(&self) -> &'a [u8] {
+        let mut bytes = self.contents;
+        while bytes.len() > 1 && bytes[0] == 0 {
+            bytes = &bytes[1..];
+        }
+        return bytes;
+    }
+
+    /// Read a small non-negative integer.
+    pub(crate) fn as_u64(&self) -> Result<u64, Box<dyn Error>> {
+        if self.tag != TAG_INTEGER || self.integer_bits() > 64 {
+            return Err(Box::new(invalid_data("Not a small integer")));
+        }
+        return Ok(self
+            .contents
+            .iter()
+            .fold(0, |acc, x| (acc << 8) | *x as u64));
+    }
+}
+
+/// The number of significant bits in a big-endian unsigned integer, ignoring
+/// any leading zero bytes.
+pub(crate) fn unsigned_bits(bytes: &[u8]) -> u32 {
+    let mut bytes = bytes;
+
+    while bytes.len() > 1 && bytes[0] == 0 {
+        bytes = &bytes[1..];
+    }
+    if bytes.is_empty() || bytes[0] == 0 {
+        return 0;
+    }
+    return (bytes.len() as u32 - 1) * 8 + (8 - bytes[0].leading_zeros());
+}
+
+/// Give a friendly name to the object identifiers used for key types, curves
+/// and encryption schemes.
+pub(crate) fn oid_name(oid: &str) -> Option<&'static str> {
+    return match oid {
+        "1.2.840.113549.1.1.1" => Some("RSA"),
+        "1.2.840.10040.4.1" => Some("DSA"),
+        "1.2.840.10045.2.1" => Some("EC"),
+        "1.3.101.110" => Some("X25519"),
+        "1.3.101.111" => Some("X448"),
+        "1.3.101.112" => Some("Ed25519"),
+        "1.3.101.113" => Some("Ed448"),
+        "1.2.840.10045.3.1.7" => Some("P-256"),
+        "1.3.132.0.10" => Some("secp256k1"),
+        "1.3.132.0.34" => Some("P-384"),
+        "1.3.132.0.35" => Some("P-521"),
+        "1.2.840.113549.1.5.13" => Some("PBES2"),
+        "1.2.840.113549.1.5.12" => Some("PBKDF2"),
+        "1.3.6.1.4.1.11591.4.11" => Some("scrypt"),
+        "2.16.840.1.101.3.4.1.2" => Some("AES-128-CBC"),
+        "2.16.840.1.101.3.4.1.22" => Some("AES-192-CBC"),
+        "2.16.840.1.101.3.4.1.42" => Some("AES-256-CBC"),
+        "1.2.840.113549.3.7" => Some("DES-EDE3-CBC"),
+        "1.2.840.113549.1.12.1.3" => Some("PBE-SHA1-3DES"),
+        "1.2.840.113549.1.12.1.6" => Some("PBE-SHA1-RC2-40"),
+        _ => None,
+    };
+}
+
+/// The size in bits of the named elliptic curves.
+pub(crate) fn curve_bits(curve: &str) -> Option<u32> {
+    return match curve {
+        "P-256" | "secp256k1" | "X25519" | "Ed25519" => Some(256),
+        "P-384" => Some(384),
+        "P-521" => Some(521),
+        "X448" | "Ed448" => Some(448),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_short_length() {
+        let data = vec![0x04, 0x02, 0xAA, 0xBB, 0xFF];
+        let (element, rest) = DerElement::parse(&data).unwrap();
+
+        assert_eq!(element.tag, TAG_OCTET_STRING);
+        assert_eq!(element.contents, &[0xAA, 0xBB]);
+        assert_eq!(rest, &[0xFF]);
+    }
+
+    #[test]
+    fn parse_long_length() {
+        let mut data = vec![0x04, 0x81, 0x80];
+        data.extend(vec![0x11; 0x80]);
+        let (element, rest) = DerElement::parse(&data).unwrap();
+
+        assert_eq!(element.contents.len(), 0x80);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_truncated() {
+        DerElement::parse(&vec![0x30, 0x05, 0x02, 0x01]).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_wrong_tag() {
+        DerElement::parse_tagged(&vec![0x02, 0x01, 0x00], TAG_SEQUENCE).unwrap();
+    }
+
+    #[test]
+    fn sequence_children() {
+        let data = vec![0x30, 0x05, 0x02, 0x01, 0x05, 0x05, 0x00, 0x01];
+        let (element, _) = DerElement::parse(&data).unwrap();
+        let children = element.children().unwrap();
+
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].as_u64().unwrap(), 5);
+        assert_eq!(children[1].tag, 0x05);
+    }
+
+    #[test]
+    fn decode_oid() {
+        let data = vec![
+            0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01,
+        ];
+        let (element, _) = DerElement::parse(&data).unwrap();
+
+        assert_eq!(element.as_oid().unwrap(), "1.2.840.113549.1.1.1");
+        assert_eq!(oid_name(&element.as_oid().unwrap()), Some("RSA"));
+    }
+
+    #[test]
+    fn decode_oid_joint_iso() {
+        let data = vec![0x06, 0x03, 0x55, 0x04, 0x03];
+        let (element, _) = DerElement::parse(&data).unwrap();
+
+        assert_eq!(element.as_oid().unwrap(), "2.5.4.3");
+    }
+
+    #[test]
+    fn integer_bit_length() {
+        assert_eq!(
+            DerElement {
+                tag: TAG_INTEGER,
+                contents: &[0x00, 0x80, 0x00]
+            }
+            .integer_bits(),
+            16
+        );
+        assert_eq!(
+            DerElement {
+                tag: TAG_INTEGER,
+                contents: &[0x00, 0x80, 0x00]
+            }
+            .unsigned_value(),
+            &[0x80, 0x00]
+        );
+        assert_eq!(
+            DerElement {
+                tag: TAG_INTEGER,
+                contents: &[0x01]
+            }
+            .integer_bits(),
+            1
+        );
+        assert_eq!(
+            DerElement {
+                tag: TAG_INTEGER,
+                contents: &[0x00]
+            }
+            .integer_bits(),
+            0
+        );
+    }
+}
@@ -0,0 +1,492 @@
+/*
+ * Read the structure of Windows DPAPI artefacts: protected blobs, the
+ * credential and vault files that wrap them, and the master key files that
+ * unlock them. Nothing is decrypted; the findings say which master key a
+ * secret needs and whether that key was found in the same user profile.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::file_signatures::FileSigniture;
+use crate::findings::{Finding, Severity};
+use crate::os_interactions::read_file_header;
+use std::error::Error;
+use std::path::Path;
+
+/// The provider GUID every DPAPI blob starts with, as stored on disk.
+const DPAPI_PROVIDER: [u8; 16] = [
+    0xD0, 0x8C, 0x9D, 0xDF, 0x01, 0x15, 0xD1, 0x11, 0x8C, 0x7A, 0x00, 0xC0, 0x4F, 0xC2, 0x97, 0xEB,
+];
+
+/// The size of the fixed header at the start of a master key file.
+const MASTER_KEY_HEADER_LEN: usize = 128;
+
+/// Windows XP era master keys use fewer PBKDF2 rounds than this.
+const MIN_MASTER_KEY_ROUNDS: u32 = 8000;
+
+/// Name a CryptoAPI algorithm identifier.
+fn algorithm_name(id: u32) -> String {
+    return String::from(match id {
+        0x6601 => "DES",
+        0x6603 => "3DES",
+        0x6609 => "3DES-112",
+        0x660E => "AES-128",
+        0x660F => "AES-192",
+        0x6610 => "AES-256",
+        0x6801 => "RC4",
+        0x8003 => "MD5",
+        0x8004 => "SHA-1",
+        0x8009 => "HMAC",
+        0x800C => "SHA-256",
+        0x800D => "SHA-384",
+        0x800E => "SHA-512",
+        _ => return format!("0x{id:04X}"),
+    });
+}
+
+/// Format a GUID stored in the mixed endian Windows layout.
+fn format_guid(bytes: &[u8]) -> Result<String, Box<dyn Error>> {
+    let mut reader = ByteReader::new(bytes);
+    let data1 = reader.read_u32_le()?;
+    let data2 = reader.read_u16_le()?;
+    let data3 = reader.read_u16_le()?;
+    let data4 = reader.read_bytes(8)?;
+
+    return Ok(format!(
+        "{data1:08x}-{data2:04x}-{data3:04x}-{}-{}",
+        data4[..2]
+            .iter()
+            .map(|x| format!("{x:02x}"))
+            .collect::<String>(),
+        data4[2..]
+            .iter()
+            .map(|x| format!("{x:02x}"))
+            .collect::<String>()
+    ));
+}
+
+/// Decode a null terminated UTF-16LE string.
+fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|x| u16::from_le_bytes([x[0], x[1]]))
+        .take_while(|x| *x != 0)
+        .collect();
+    return String::from_utf16_lossy(&units);
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct DpapiBlob {
+    pub(crate) master_key_guid: String,
+    pub(crate) flags: u32,
+    pub(crate) description: String,
+    pub(crate) cipher_algorithm: u32,
+    pub(crate) cipher_key_bits: u32,
+    pub(crate) hash_algorithm: u32,
+    pub(crate) hash_bits: u32,
+    pub(crate) data_len: usize,
+}
+
+impl DpapiBlob {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = ByteReader::new(data);
+        if reader.read_u32_le()? != 1 || reader.read_bytes(16)? != DPAPI_PROVIDER {
+            return Err(Box::new(invalid_data("Not a DPAPI blob")));
+        }
+        if reader.read_u32_le()? != 1 {
+            return Err(Box::new(invalid_data("Unknown DPAPI master key version")));
+        }
+        let master_key_guid = format_guid(reader.read_bytes(16)?)?;
+        let flags = reader.read_u32_le()?;
+        let description_len = reader.read_u32_le()? as usize;
+        let description = utf16_string(reader.read_bytes(description_len)?);
+
+        let cipher_algorithm = reader.read_u32_le()?;
+        let cipher_key_bits = reader.read_u32_le()?;
+        let salt_len = reader.read_u32_le()? as usize;
+        reader.skip(salt_len)?;
+        let hmac_key_len = reader.read_u32_le()? as usize;
+        reader.skip(hmac_key_len)?;
+
+        let hash_algorithm = reader.read_u32_le()?;
+        let hash_bits = reader.read_u32_le()?;
+        let hmac2_key_len = reader.read_u32_le()? as usize;
+        reader.skip(hmac2_key_len)?;
+        let data_len = reader.read_u32_le()? as usize;
+        reader.skip(data_len)?;
+        let sign_len = reader.read_u32_le()? as usize;
+        reader.skip(sign_len)?;
+
+        return Ok(DpapiBlob {
+            master_key_guid,
+            flags,
+            description,
+            cipher_algorithm,
+            cipher_key_bits,
+            hash_algorithm,
+            hash_bits,
+            data_len,
+        });
+    }
+
+    /// Find and read the first blob embedded in a larger structure.
+    pub(crate) fn find_in(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let Some(start) = data
+            .windows(20)
+            .position(|x| x[..4] == [1, 0, 0, 0] && x[4..] == DPAPI_PROVIDER)
+        else {
+            return Err(Box::new(invalid_data("No DPAPI blob found")));
+        };
+        return DpapiBlob::from_bytes(&data[start..]);
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct MasterKeyFile {
+    pub(crate) guid: String,
+    pub(crate) policy: u32,
+    pub(crate) rounds: u32,
+    pub(crate) hash_algorithm: u32,
+    pub(crate) cipher_algorithm: u32,
+    pub(crate) has_backup_key: bool,
+    pub(crate) has_domain_key: bool,
+}
+
+impl MasterKeyFile {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = ByteReader::new(data);
+        if reader.read_u32_le()? != 2 {
+            return Err(Box::new(invalid_data("Not a DPAPI master key file")));
+        }
+        reader.skip(8)?;
+        let guid = utf16_string(reader.read_bytes(72)?).to_lowercase();
+        reader.skip(8)?;
+        let policy = reader.read_u32_le()?;
+
+        let master_key_len = reader.read_u64_le()?;
+        let backup_key_len = reader.read_u64_le()?;
+        let credential_history_len = reader.read_u64_le()?;
+        let domain_key_len = reader.read_u64_le()?;
+        let sections = [
+            master_key_len,
+            backup_key_len,
+            credential_history_len,
+            domain_key_len,
+        ];
+        if sections
+            .iter()
+            .try_fold(MASTER_KEY_HEADER_LEN as u64, |acc, x| acc.checked_add(*x))
+            != Some(data.len() as u64)
+        {
+            return Err(Box::new(invalid_data(
+                "Master key sections do not fill the file",
+            )));
+        }
+
+        /* The master key section opens with its version and a 16 byte salt. */
+        reader.skip(4 + 16)?;
+        let rounds = reader.read_u32_le()?;
+        let hash_algorithm = reader.read_u32_le()?;
+        let cipher_algorithm = reader.read_u32_le()?;
+
+        return Ok(MasterKeyFile {
+            guid,
+            policy,
+            rounds,
+            hash_algorithm,
+            cipher_algorithm,
+            has_backup_key: backup_key_len > 0,
+            has_domain_key: domain_key_len > 0,
+        });
+    }
+
+    pub(crate) fn weaknesses(&self) -> Vec<String> {
+        let mut weaknesses = Vec::new();
+        if self.rounds < MIN_MASTER_KEY_ROUNDS {
+            weaknesses.push(format!("Only {} PBKDF2 rounds", self.rounds));
+        }
+        if self.cipher_algorithm == 0x6603 {
+            weaknesses.push(String::from("Master key encrypted with 3DES"));
+        }
+        return weaknesses;
+    }
+}
+
+/// Work out the SID and user name a file belongs to from its path. Master
+/// keys sit in a directory named after the SID, and everything else sits
+/// below the user's profile directory.
+fn path_owner(file: &Path) -> (Option<String>, Option<String>) {
+    let components: Vec<String> = file
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    let sid = components
+        .iter()
+        .rev()
+        .find(|x| x.starts_with("S-1-"))
+        .cloned();
+    let user = components
+        .windows(2)
+        .find(|x| {
+            x[0].eq_ignore_ascii_case("Users")
+                || x[0].eq_ignore_ascii_case("Documents and Settings")
+        })
+        .map(|x| x[1].clone());
+    return (sid, user);
+}
+
+/// Look for the master key a blob needs in the profile the blob came from,
+/// returning the SID directory it was found in.
+fn find_master_key(file: &Path, guid: &str) -> Option<String> {
+    for ancestor in file.ancestors().skip(1) {
+        let protect = ancestor.join("AppData/Roaming/Microsoft/Protect");
+        let Ok(entries) = std::fs::read_dir(&protect) else {
+            continue;
+        };
+
+        for entry in entries.filter_map(|x| x.ok()) {
+            let key_file = entry.path().join(guid);
+            if key_file.is_file()
+                && std::fs::read(&key_file).is_ok_and(|x| MasterKeyFile::from_bytes(&x).is_ok())
+            {
+                return Some(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        return None;
+    }
+    return None;
+}
+
+/// Report a DPAPI blob, credential file, vault policy or master key file.
+pub(crate) fn dpapi_finding(file: &Path) -> Result<Finding, Box<dyn Error>> {
+    let data = std::fs::read(file)?;
+    let (mut sid, user) = path_owner(file);
+    let is_vault_policy = file
+        .extension()
+        .is_some_and(|x| x.eq_ignore_ascii_case("vpol"));
+
+    let (description, blob) = match FileSigniture::from_bytes(&read_file_header(file)?) {
+        FileSigniture::DPAPIMasterKey => {
+            let master_key = MasterKeyFile::from_bytes(&data)?;
+            let weaknesses = master_key.weaknesses();
+
+            let mut finding = Finding::new(file, Severity::High, "DPAPI master key");
+            finding.add_detail("guid", master_key.guid.clone());
+            if let Some(sid) = sid {
+                finding.add_detail("sid", sid);
+            }
+            if let Some(user) = user {
+                finding.add_detail("user", user);
+            }
+            finding.add_detail("rounds", master_key.rounds.to_string());
+            finding.add_detail("hash", algorithm_name(master_key.hash_algorithm));
+            finding.add_detail("cipher", algorithm_name(master_key.cipher_algorithm));
+            /* A domain backup key lets any domain admin decrypt the master key. */
+            finding.add_detail(
+                "domain backup key",
+                String::from(if master_key.has_domain_key {
+                    "present"
+                } else {
+                    "absent"
+                }),
+            );
+            for weakness in weaknesses {
+                finding.add_detail("weakness", weakness);
+            }
+            return Ok(finding);
+        }
+        FileSigniture::DPAPIBlob => ("DPAPI blob", DpapiBlob::from_bytes(&data)?),
+        FileSigniture::DPAPICredentialFile => (
+            "DPAPI protected Windows credential",
+            DpapiBlob::from_bytes(&data[12..])?,
+        ),
+        _ if is_vault_policy => ("DPAPI protected Windows vault", DpapiBlob::find_in(&data)?),
+        _ => return Err(Box::new(invalid_data("Not a DPAPI file"))),
+    };
+
+    /* Secrets whose master key is in the same image can be opened offline. */
+    let master_key_sid = find_master_key(file, &blob.master_key_guid);
+    let severity = if master_key_sid.is_some() {
+        Severity::High
+    } else {
+        Severity::High.downgrade()
+    };
+    sid = sid.or(master_key_sid.clone());
+
+    let mut finding = Finding::new(file, severity, description);
+    finding.add_detail("master key guid", blob.master_key_guid.clone());
+    finding.add_detail(
+        "master key",
+        String::from(if master_key_sid.is_some() {
+            "found"
+        } else {
+            "not found"
+        }),
+    );
+    if let Some(sid) = sid {
+        finding.add_detail("sid", sid);
+    }
+    if let Some(user) = user {
+        finding.add_detail("user", user);
+    }
+    if !blob.description.is_empty() {
+        finding.add_detail("description", blob.description.trim_end().to_string());
+    }
+    finding.add_detail(
+        "cipher",
+        format!(
+            "{} ({} bit key)",
+            algorithm_name(blob.cipher_algorithm),
+            blob.cipher_key_bits
+        ),
+    );
+    finding.add_detail("hash", algorithm_name(blob.hash_algorithm));
+    finding.add_detail("protected bytes", blob.data_len.to_string());
+
+    if is_vault_policy {
+        let credentials = std::fs::read_dir(file.parent().unwrap_or(Path::new(".")))?
+            .filter_map(|x| x.ok())
+            .filter(|x| {
+                x.path()
+                    .extension()
+                    .is_some_and(|x| x.eq_ignore_ascii_case("vcrd"))
+            })
+            .count();
+        finding.add_detail("vault credentials", credentials.to_string());
+    }
+    return Ok(finding);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = "./tests/testing_files/dpapi/Users/alice/AppData";
+    const SID: &str = "S-1-5-21-3623811015-3361044348-30300820-1013";
+
+    #[test]
+    fn master_key_file() {
+        let file = format!(
+            "{PROFILE}/Roaming/Microsoft/Protect/{SID}/3e4a1f2b-6c5d-4e7f-8a9b-0c1d2e3f4a5b"
+        );
+        let master_key = MasterKeyFile::from_bytes(&std::fs::read(&file).unwrap()).unwrap();
+        assert_eq!(master_key.guid, "3e4a1f2b-6c5d-4e7f-8a9b-0c1d2e3f4a5b");
+        assert_eq!(master_key.rounds, 8000);
+        assert!(master_key.has_backup_key);
+        assert!(master_key.weaknesses().is_empty());
+
+        let finding = dpapi_finding(Path::new(&file)).unwrap();
+        assert_eq!(finding.severity, Severity::High);
+        assert_eq!(finding.detail("sid"), Some(&String::from(SID)));
+        assert_eq!(finding.detail("user"), Some(&String::from("alice")));
+        assert_eq!(finding.detail("cipher"), Some(&String::from("AES-256")));
+        assert_eq!(
+            finding.detail("domain backup key"),
+            Some(&String::from("present"))
+        );
+    }
+
+    #[test]
+    fn legacy_master_key_file() {
+        let finding = dpapi_finding(Path::new(
+            "./tests/testing_files/dpapi/Windows/System32/Microsoft/Protect/S-1-5-18/User/9a8b7c6d-5e4f-4a3b-9c2d-1e0f9a8b7c6d",
+        ))
+        .unwrap();
+        assert_eq!(finding.detail("sid"), Some(&String::from("S-1-5-18")));
+        assert_eq!(finding.detail("user"), None);
+        assert_eq!(
+            finding
+                .details
+                .iter()
+                .filter(|(name, _)| name == "weakness")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn credential_with_master_key() {
+        let finding = dpapi_finding(Path::new(&format!(
+            "{PROFILE}/Local/Microsoft/Credentials/DFBE70A7E5CC19A398EBF1B96859CE5D"
+        )))
+        .unwrap();
+        assert_eq!(finding.description, "DPAPI protected Windows credential");
+        assert_eq!(finding.severity, Severity::High);
+        assert_eq!(finding.detail("master key"), Some(&String::from("found")));
+        assert_eq!(finding.detail("sid"), Some(&String::from(SID)));
+        assert_eq!(
+            finding.detail("description"),
+            Some(&String::from("Local Credential Data"))
+        );
+        assert_eq!(
+            finding.detail("cipher"),
+            Some(&String::from("AES-256 (256 bit key)"))
+        );
+    }
+
+    #[test]
+    fn credential_without_master_key() {
+        let finding = dpapi_finding(Path::new(&format!(
+            "{PROFILE}/Roaming/Microsoft/Credentials/0F2A7C9E4B1D3A5C6E8F0A2B4C6D8E0F"
+        )))
+        .unwrap();
+        assert_eq!(finding.severity, Severity::Medium);
+        assert_eq!(
+            finding.detail("master key guid"),
+            Some(&String::from("9a8b7c6d-5e4f-4a3b-9c2d-1e0f9a8b7c6d"))
+        );
+        assert_eq!(finding.detail("sid"), None);
+    }
+
+    #[test]
+    fn vault_policy() {
+        let finding = dpapi_finding(Path::new(&format!(
+            "{PROFILE}/Local/Microsoft/Vault/4BF4C442-9B8A-41A0-B380-DD4A704DDB28/Policy.vpol"
+        )))
+        .unwrap();
+        assert_eq!(finding.description, "DPAPI protected Windows vault");
+        assert_eq!(
+            finding.detail("vault credentials"),
+            Some(&String::from("2"))
+        );
+        assert_eq!(finding.detail("master key"), Some(&String::from("found")));
+    }
+
+    #[test]
+    fn standalone_blob() {
+        let blob = DpapiBlob::from_bytes(
+            &std::fs::read("./tests/testing_files/dpapi/exported/secret.bin").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(blob.description, "Saved password");
+        assert_eq!(algorithm_name(blob.cipher_algorithm), "3DES");
+        assert_eq!(algorithm_name(blob.hash_algorithm), "SHA-1");
+        assert_eq!(blob.data_len, 48);
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_blob() {
+        dpapi_finding(Path::new(
+            "./tests/testing_files/dpapi/exported/truncated.bin",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_dpapi() {
+        dpapi_finding(Path::new(&format!(
+            "{PROFILE}/Roaming/Microsoft/Protect/CREDHIST"
+        )))
+        .unwrap();
+    }
+
+    #[test]
+    fn guid_layout() {
+        assert_eq!(
+            format_guid(&DPAPI_PROVIDER).unwrap(),
+            "df9d8cd0-1501-11d1-8c7a-00c04fc297eb"
+        );
+    }
+}
@@ -0,0 +1,471 @@
+/*
+ * Decode base64, hex, URL and quoted-printable payloads hidden in text and
+ * scan what they hold.
+ */
+
+use crate::byte_stats::ByteStats;
+use crate::encoding::{base64_decode, hex_decode, quoted_printable_decode, url_decode};
+use crate::file_signatures::{FileSigniture, SignitureMatch};
+use crate::findings::{Finding, Severity};
+use crate::frequency_analysis::Alphabet;
+use crate::secret_classifier::{
+    Candidate, FileKind, extract_candidates, probability_severity, secret_probability,
+};
+use crate::suppression::{public_pem_lines, suppression};
+use std::error::Error;
+use std::path::Path;
+
+/// Base64 and hex tokens shorter than this are too short to hide anything.
+const MIN_ENCODED_LENGTH: usize = 16;
+
+/// Payloads are decoded at most this many times over, so text that decodes
+/// to more encoded text cannot be chased forever.
+const MAX_DEPTH: usize = 3;
+
+/// Decoded bytes are only treated as text when almost all printable.
+const MIN_PRINTABLE_RATIO: f64 = 0.9;
+
+/// A decoded value too short for the classifier to tokenise is still scored
+/// on its own if it is at least this long.
+const MIN_DECODED_SECRET_LENGTH: usize = 8;
+
+/// Signiture matches in decoded data less certain than this are ignored.
+const MIN_CONFIDENCE: f64 = 0.25;
+
+/// Characters that separate encoded tokens. Equals signs are kept, as base64
+/// padding and quoted-printable escapes use them.
+const TOKEN_SEPARATORS: &str = "\"'`,;:()[]{}<>";
+
+/// The encodings that are undone to reach a payload.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum Encoding {
+    Base64,
+    Hex,
+    Url,
+    QuotedPrintable,
+}
+
+impl Encoding {
+    pub(crate) fn description(&self) -> &'static str {
+        return match self {
+            Encoding::Base64 => "base64",
+            Encoding::Hex => "hex",
+            Encoding::Url => "url",
+            Encoding::QuotedPrintable => "quoted-printable",
+        };
+    }
+
+    /// Does a token look like it was written in this encoding.
+    fn applies(&self, token: &str) -> bool {
+        let escapes = |marker: char| {
+            token
+                .match_indices(marker)
+                .filter(|(index, _)| {
+                    token
+                        .get(index + 1..index + 3)
+                        .is_some_and(|x| x.chars().all(|y| y.is_ascii_hexdigit()))
+                })
+                .count()
+        };
+        return match self {
+            Encoding::Base64 => {
+                token.len() >= MIN_ENCODED_LENGTH
+                    && !token.trim_end_matches('=').contains('=')
+                    && matches!(Alphabet::detect(token), Alphabet::Base32 | Alphabet::Base64)
+            }
+            Encoding::Hex => {
+                token.len() >= MIN_ENCODED_LENGTH && Alphabet::detect(token) == Alphabet::Hex
+            }
+            Encoding::Url => escapes('%') > 0,
+            /* A single =XX could be an ordinary setting, so ask for two or
+            an escaped equals sign. */
+            Encoding::QuotedPrintable => escapes('=') > 1 || token.contains("=3D"),
+        };
+    }
+
+    fn decode(&self, token: &str) -> Option<Vec<u8>> {
+        let decoded = match self {
+            Encoding::Base64 => base64_decode(token),
+            Encoding::Hex => hex_decode(token),
+            Encoding::Url => url_decode(token),
+            Encoding::QuotedPrintable => quoted_printable_decode(token.trim_end_matches('=')),
+        };
+        return decoded.ok();
+    }
+}
+
+/// Encodings tried on each token, most specific first.
+const TOKEN_ENCODINGS: [Encoding; 3] = [Encoding::Url, Encoding::Hex, Encoding::Base64];
+
+/// One step taken to turn encoded text back into a payload.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum DecodingStep {
+    Decoded(Encoding),
+    /// The decoded bytes were text stored as UTF-16, as PowerShell encoded
+    /// commands are, and were read back into UTF-8.
+    Utf16Le,
+}
+
+impl DecodingStep {
+    pub(crate) fn description(&self) -> &'static str {
+        return match self {
+            DecodingStep::Decoded(encoding) => encoding.description(),
+            DecodingStep::Utf16Le => "utf-16le",
+        };
+    }
+}
+
+/// Bytes decoded from some text, with the chain of steps taken to reach
+/// them, outermost first.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct DecodedPayload {
+    pub(crate) line: usize,
+    pub(crate) chain: Vec<DecodingStep>,
+    pub(crate) data: Vec<u8>,
+    /// The rest of the line the payload was found on.
+    pub(crate) context: String,
+}
+
+impl DecodedPayload {
+    pub(crate) fn chain_description(&self) -> String {
+        return self
+            .chain
+            .iter()
+            .map(|x| x.description())
+            .collect::<Vec<_>>()
+            .join(" > ");
+    }
+
+    /// The payload as text, if it is text.
+    pub(crate) fn text(&self) -> Option<String> {
+        return decoded_text(&self.data).map(|(text, _)| text);
+    }
+
+    /// A file type recognised from the payload's first bytes.
+    pub(crate) fn signiture(&self) -> Option<FileSigniture> {
+        return signiture_of(&self.data);
+    }
+}
+
+fn signiture_of(data: &[u8]) -> Option<FileSigniture> {
+    let found = SignitureMatch::from_bytes(&data.to_vec(), data.len() as u64);
+    if found.signiture == FileSigniture::Unknown || found.confidence < MIN_CONFIDENCE {
+        return None;
+    }
+    return Some(found.signiture);
+}
+
+/// Read decoded bytes as text, noting whether they had to be read as UTF-16.
+fn decoded_text(data: &[u8]) -> Option<(String, bool)> {
+    /* UTF-16 ASCII text has every other byte zero. */
+    let odd_zeros = data.iter().skip(1).step_by(2).filter(|x| **x == 0).count();
+    if data.len() >= 4 && data.len().is_multiple_of(2) && odd_zeros * 10 >= data.len() / 2 * 9 {
+        let units: Vec<u16> = data
+            .chunks(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+            .collect();
+        return String::from_utf16(&units).ok().map(|x| (x, true));
+    }
+
+    let text = String::from_utf8(data.to_vec()).ok()?;
+    if ByteStats::from_bytes(data).printable_ratio() < MIN_PRINTABLE_RATIO {
+        return None;
+    }
+    return Some((text, false));
+}
+
+/// Decode one piece of text, keeping it only if it decodes to text or to
+/// data with a known signiture.
+fn try_decode(encoding: Encoding, token: &str) -> Option<(Vec<DecodingStep>, Vec<u8>)> {
+    if !encoding.applies(token) {
+        return None;
+    }
+    let data = encoding.decode(token)?;
+    if data.is_empty() || data == token.as_bytes() {
+        return None;
+    }
+    let step = DecodingStep::Decoded(encoding);
+    return match decoded_text(&data) {
+        Some((text, true)) => Some((vec![step, DecodingStep::Utf16Le], text.into_bytes())),
+        Some((_, false)) => Some((vec![step], data)),
+        None => signiture_of(&data).map(|_| (vec![step], data)),
+    };
+}
+
+/// Decode a token, or failing that the value of a key=value token, giving
+/// the piece that was decoded.
+fn decode_token(token: &str) -> Option<(&str, Vec<DecodingStep>, Vec<u8>)> {
+    let value = token.split_once('=').map(|(_, x)| x);
+    return [Some(token), value]
+        .into_iter()
+        .flatten()
+        .filter(|x| !x.is_empty())
+        .find_map(|piece| {
+            let (chain, data) = TOKEN_ENCODINGS.iter().find_map(|x| try_decode(*x, piece))?;
+            Some((piece, chain, data))
+        });
+}
+
+/// Find and decode every payload in some text, and the payloads inside
+/// those, down to the depth limit.
+pub(crate) fn decode_payloads(text: &str) -> Vec<DecodedPayload> {
+    return decode_payloads_at_depth(text, 1);
+}
+
+fn decode_payloads_at_depth(text: &str, depth: usize) -> Vec<DecodedPayload> {
+    let mut payloads = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        /* Quoted-printable encodes whole lines of text rather than tokens. */
+        let mut found: Vec<(&str, Vec<DecodingStep>, Vec<u8>)> = Vec::new();
+        match try_decode(Encoding::QuotedPrintable, line) {
+            Some((chain, data)) => found.push((line, chain, data)),
+            None => found.extend(
+                line.split(|x: char| x.is_whitespace() || TOKEN_SEPARATORS.contains(x))
+                    .filter_map(decode_token),
+            ),
+        }
+
+        for (token, chain, data) in found {
+            let payload = DecodedPayload {
+                line: index + 1,
+                chain,
+                data,
+                context: line.replacen(token, " ", 1),
+            };
+
+            /* Payloads inside this one are reported against the outer line. */
+            if depth < MAX_DEPTH
+                && let Some(inner_text) = payload.text()
+            {
+                for inner in decode_payloads_at_depth(&inner_text, depth + 1) {
+                    payloads.push(DecodedPayload {
+                        line: payload.line,
+                        chain: [payload.chain.clone(), inner.chain].concat(),
+                        data: inner.data,
+                        context: payload.context.clone(),
+                    });
+                }
+            }
+            payloads.push(payload);
+        }
+    }
+    return payloads;
+}
+
+/// Run the secret detectors over a payload that decoded to text.
+fn payload_secret_findings(file: &Path, payload: &DecodedPayload, text: &str) -> Vec<Finding> {
+    let mut candidates = extract_candidates(text);
+
+    /* A decoded value is often the secret itself, too short to tokenise, with
+    the key naming it outside the encoding. */
+    let value = text.trim();
+    if candidates.is_empty()
+        && value.chars().count() >= MIN_DECODED_SECRET_LENGTH
+        && !value.contains(char::is_whitespace)
+    {
+        candidates.push(Candidate {
+            token: String::from(value),
+            context: payload.context.clone(),
+            line: 1,
+        });
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let public_lines = public_pem_lines(text);
+    let kind = FileKind::from_path(file);
+    return candidates
+        .iter()
+        .filter(|x| suppression(x, file, &lines, &public_lines).is_none())
+        .filter_map(|candidate| {
+            let probability = secret_probability(candidate, kind);
+            let severity = probability_severity(probability)?;
+            let mut finding = Finding::new(file, severity, "Possible secret in encoded data");
+            finding.add_detail("line", payload.line.to_string());
+            finding.add_detail("decoding chain", payload.chain_description());
+            finding.add_detail("length", candidate.token.chars().count().to_string());
+            finding.add_detail("probability", format!("{:.2}", probability));
+            Some(finding)
+        })
+        .collect();
+}
+
+/// Decode the encoded payloads in a text file and report those that are
+/// recognisable files, such as private keys, or that hold secrets. Files
+/// that are not UTF-8 text are skipped.
+pub(crate) fn encoded_payload_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let text = match String::from_utf8(std::fs::read(file)?) {
+        Ok(text) => text,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut findings = Vec::new();
+    for payload in decode_payloads(&text) {
+        if let Some(signiture) = payload.signiture() {
+            let severity = match signiture.is_credential_store() {
+                true => Severity::High,
+                false => Severity::Medium,
+            };
+            let mut finding = Finding::new(file, severity, "Encoded file in text");
+            finding.add_detail("line", payload.line.to_string());
+            finding.add_detail("decoding chain", payload.chain_description());
+            finding.add_detail("signiture", format!("{:?}", signiture));
+            finding.add_detail("length", payload.data.len().to_string());
+            findings.push(finding);
+            continue;
+        }
+        if let Some(decoded) = payload.text() {
+            findings.extend(payload_secret_findings(file, &payload, &decoded));
+        }
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODED_PAYLOADS: &str = "./tests/testing_files/encoded_payloads";
+
+    fn findings(name: &str) -> Vec<Finding> {
+        return encoded_payload_findings(&Path::new(ENCODED_PAYLOADS).join(name)).unwrap();
+    }
+
+    #[test]
+    fn encodings_applied() {
+        assert!(Encoding::Base64.applies("SHc0clQ5eFEydkxtN0twWg=="));
+        assert!(!Encoding::Base64.applies("YmlsbGluZw=="));
+        assert!(!Encoding::Base64.applies("key=SHc0clQ5eFEydkxtN0twWg"));
+        assert!(Encoding::Hex.applies("676878375471324c"));
+        assert!(!Encoding::Hex.applies("67687837"));
+        assert!(Encoding::Url.applies("a%3Db"));
+        assert!(!Encoding::Url.applies("100%"));
+        assert!(Encoding::QuotedPrintable.applies("a=3Db"));
+        assert!(!Encoding::QuotedPrintable.applies("level=AB"));
+    }
+
+    #[test]
+    fn key_value_tokens() {
+        let (piece, chain, data) = decode_token("secret=SHc0clQ5eFEydkxtN0twWg==").unwrap();
+        assert_eq!(piece, "SHc0clQ5eFEydkxtN0twWg==");
+        assert_eq!(chain, vec![DecodingStep::Decoded(Encoding::Base64)]);
+        assert_eq!(data, b"Hw4rT9xQ2vLm7KpZ");
+        assert_eq!(decode_token("timeout=30"), None);
+    }
+
+    #[test]
+    fn utf16_payload() {
+        let payloads = decode_payloads("-enc JABhACAAPQAgADEAMgAzADQANQA2ADcAOAA=");
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].chain_description(), "base64 > utf-16le");
+        assert_eq!(payloads[0].text(), Some(String::from("$a = 12345678")));
+    }
+
+    #[test]
+    fn nested_payloads() {
+        let payloads = decode_payloads("token: NjE2MjYzNjQ2NTY2Njc2ODY5NmE2YjZjNmQ2ZTZmNzA=");
+        let chains: Vec<String> = payloads.iter().map(|x| x.chain_description()).collect();
+        assert_eq!(chains, vec!["base64 > hex", "base64"]);
+        assert_eq!(payloads[0].text(), Some(String::from("abcdefghijklmnop")));
+        assert_eq!(payloads[0].context, "token:  ");
+    }
+
+    #[test]
+    fn depth_limit() {
+        /* Zq8vN3kR7tY2pL9mXc4W base64 encoded four times. */
+        let text = "VmpJMVJrNUhVbkpPU0hCb1RWVnJlbHBGV25KbFYwNUdaSHBXYVZadGFIRlVhMXBxVUZFOVBRPT0=";
+        let deepest = decode_payloads(text)
+            .iter()
+            .map(|x| x.chain.len())
+            .max()
+            .unwrap();
+        assert_eq!(deepest, MAX_DEPTH);
+    }
+
+    #[test]
+    fn plain_text() {
+        assert!(decode_payloads("Settings are stored in config.ini, 100% of the time.").is_empty());
+        assert!(findings("readme.txt").is_empty());
+    }
+
+    #[test]
+    fn powershell_encoded_command() {
+        let found = findings("deploy.ps1");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].description, "Possible secret in encoded data");
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(
+            found[0].detail("decoding chain"),
+            Some(&String::from("base64 > utf-16le"))
+        );
+    }
+
+    #[test]
+    fn kubernetes_secret() {
+        let found = findings("secret.yaml");
+        let chains: Vec<(&String, &String)> = found
+            .iter()
+            .map(|x| {
+                (
+                    x.detail("line").unwrap(),
+                    x.detail("decoding chain").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            chains,
+            vec![
+                (&String::from("8"), &String::from("base64")),
+                (&String::from("9"), &String::from("base64 > hex")),
+            ]
+        );
+    }
+
+    #[test]
+    fn encoded_private_key() {
+        let found = findings("key.env");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].description, "Encoded file in text");
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(
+            found[0].detail("signiture"),
+            Some(&String::from("PEMPrivateKey"))
+        );
+    }
+
+    #[test]
+    fn url_encoded_secret() {
+        let found = findings("webhook.txt");
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].detail("decoding chain"),
+            Some(&String::from("url"))
+        );
+    }
+
+    #[test]
+    fn quoted_printable_secret() {
+        let found = findings("welcome.eml");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].detail("line"), Some(&String::from("5")));
+        assert_eq!(
+            found[0].detail("decoding chain"),
+            Some(&String::from("quoted-printable"))
+        );
+    }
+
+    #[test]
+    fn binary_file_skipped() {
+        assert!(
+            encoded_payload_findings(Path::new("./tests/testing_files/entropy/backup.hc"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_file() {
+        findings("missing.txt");
+    }
+}
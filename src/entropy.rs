@@ -0,0 +1,330 @@
+/*
+ * Profile the entropy of a file's bytes to find encrypted or packed data.
+ */
+
+use crate::byte_reader::invalid_data;
+use crate::byte_stats::ByteStats;
+use crate::file_signatures::{FileSigniture, SignitureMatch};
+use crate::findings::{Finding, Severity};
+use crate::os_interactions::read_file_header;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Bytes in each window of the profile. Random data only comes close to 8
+/// bits a byte once a window has several times 256 bytes in it.
+pub(crate) const WINDOW_SIZE: usize = 1024;
+
+/// Windows at or above this many bits a byte look random. A window of random
+/// bytes averages about 7.82.
+const HIGH_ENTROPY: f64 = 7.5;
+
+/// A file is low entropy when its median window is below this, as text,
+/// code and most uncompressed formats are.
+const LOW_ENTROPY: f64 = 6.0;
+
+/// Whole file entropy above which a file without a signiture is taken to be
+/// encrypted throughout.
+const CONTAINER_ENTROPY: f64 = 7.99;
+
+/// Encrypted volumes are much bigger than this, and smaller random files
+/// are more likely to be keys or seeds than containers.
+const MIN_CONTAINER_SIZE: u64 = 64 * 1024;
+
+/// Volumes are made of whole disk sectors.
+const SECTOR_SIZE: u64 = 512;
+
+/// Magic numbers of compressed and media formats, which are as random as
+/// encrypted data but say what they are.
+const COMPRESSED_MAGIC: [&[u8]; 12] = [
+    b"PK\x03\x04",
+    b"\x1f\x8b",
+    b"7z\xbc\xaf\x27\x1c",
+    b"\xfd7zXZ\x00",
+    b"BZh",
+    b"\x28\xb5\x2f\xfd",
+    b"Rar!\x1a\x07",
+    b"\x89PNG",
+    b"\xff\xd8\xff",
+    b"%PDF",
+    b"OggS",
+    b"fLaC",
+];
+
+/// Regions reported for any one file.
+const MAX_REGIONS: usize = 16;
+
+/// The entropy of a file in bits a byte, over the whole file and over each
+/// fixed size window of it.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct EntropyProfile {
+    pub(crate) size: u64,
+    pub(crate) entropy: f64,
+    pub(crate) window_size: usize,
+    pub(crate) windows: Vec<f64>,
+}
+
+/// A run of neighbouring high entropy windows.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct EntropyRegion {
+    pub(crate) offset: u64,
+    pub(crate) length: u64,
+    pub(crate) entropy: f64,
+}
+
+/// A profile needs windows of at least one byte.
+fn check_window_size(window_size: usize) -> Result<(), Box<dyn Error>> {
+    if window_size == 0 {
+        return Err(Box::new(invalid_data(
+            "Entropy window size must not be zero",
+        )));
+    }
+    return Ok(());
+}
+
+impl EntropyProfile {
+    pub(crate) fn from_bytes(data: &[u8], window_size: usize) -> Result<Self, Box<dyn Error>> {
+        check_window_size(window_size)?;
+        return Ok(EntropyProfile {
+            size: data.len() as u64,
+            entropy: ByteStats::from_bytes(data).entropy(),
+            window_size,
+            windows: data
+                .chunks(window_size)
+                .map(|x| ByteStats::from_bytes(x).entropy())
+                .collect(),
+        });
+    }
+
+    /// Profile a file a window at a time so large files are never held in
+    /// memory.
+    pub(crate) fn from_file(file: &Path, window_size: usize) -> Result<Self, Box<dyn Error>> {
+        check_window_size(window_size)?;
+        let mut reader = BufReader::new(File::open(file)?);
+        let mut totals = ByteStats::new();
+        let mut windows = Vec::new();
+        let mut buffer = vec![0; window_size];
+
+        loop {
+            /* Fill the whole window unless the file runs out. */
+            let mut filled = 0;
+            while filled < window_size {
+                let read = reader.read(&mut buffer[filled..])?;
+                if read == 0 {
+                    break;
+                }
+                filled += read;
+            }
+            if filled == 0 {
+                break;
+            }
+
+            windows.push(ByteStats::from_bytes(&buffer[..filled]).entropy());
+            totals.add(&buffer[..filled]);
+        }
+
+        return Ok(EntropyProfile {
+            size: totals.total as u64,
+            entropy: totals.entropy(),
+            window_size,
+            windows,
+        });
+    }
+
+    /// The entropy of the middle window, which unlike the whole file
+    /// entropy is not pulled up by a few random regions.
+    pub(crate) fn median(&self) -> f64 {
+        let mut sorted = self.windows.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        return match sorted.len() {
+            0 => 0.0,
+            len => sorted[len / 2],
+        };
+    }
+
+    /// Merge neighbouring high entropy windows into regions, with the
+    /// average entropy of their windows.
+    pub(crate) fn high_entropy_regions(&self) -> Vec<EntropyRegion> {
+        let mut regions: Vec<EntropyRegion> = Vec::new();
+        let mut run: Vec<(usize, f64)> = Vec::new();
+
+        for (index, entropy) in self
+            .windows
+            .iter()
+            .enumerate()
+            .chain([(self.windows.len(), &0.0)])
+        {
+            if *entropy >= HIGH_ENTROPY {
+                run.push((index, *entropy));
+                continue;
+            }
+            if let (Some(first), Some(last)) = (run.first(), run.last()) {
+                let offset = (first.0 * self.window_size) as u64;
+                let end = std::cmp::min(((last.0 + 1) * self.window_size) as u64, self.size);
+                regions.push(EntropyRegion {
+                    offset,
+                    length: end - offset,
+                    entropy: run.iter().map(|x| x.1).sum::<f64>() / run.len() as f64,
+                });
+            }
+            run.clear();
+        }
+        return regions;
+    }
+
+    /// Is the whole file as random as encrypted data, and sized like a
+    /// volume made of disk sectors.
+    pub(crate) fn looks_encrypted(&self) -> bool {
+        return self.entropy >= CONTAINER_ENTROPY
+            && self.size >= MIN_CONTAINER_SIZE
+            && self.size.is_multiple_of(SECTOR_SIZE);
+    }
+}
+
+/// Report files that are random throughout with no signiture, as encrypted
+/// containers like VeraCrypt volumes are, and high entropy regions hidden in
+/// otherwise low entropy files.
+pub(crate) fn entropy_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let profile = EntropyProfile::from_file(file, WINDOW_SIZE)?;
+    let mut findings = Vec::new();
+
+    if profile.looks_encrypted() {
+        /* Formats with a signiture are left to the checks that know them. */
+        let header = read_file_header(file)?;
+        if SignitureMatch::from_file(file)?.signiture != FileSigniture::Unknown
+            || COMPRESSED_MAGIC.iter().any(|x| header.starts_with(x))
+        {
+            return Ok(findings);
+        }
+        let mut finding = Finding::new(file, Severity::High, "Possible encrypted container");
+        finding.add_detail("size", profile.size.to_string());
+        finding.add_detail("entropy", format!("{:.4}", profile.entropy));
+        findings.push(finding);
+        return Ok(findings);
+    }
+
+    if profile.median() >= LOW_ENTROPY {
+        return Ok(findings);
+    }
+    for region in profile.high_entropy_regions().iter().take(MAX_REGIONS) {
+        let mut finding = Finding::new(
+            file,
+            Severity::Medium,
+            "High entropy region in a low entropy file",
+        );
+        finding.add_detail("offset", region.offset.to_string());
+        finding.add_detail("length", region.length.to_string());
+        finding.add_detail("entropy", format!("{:.2}", region.entropy));
+        finding.add_detail("file entropy", format!("{:.2}", profile.entropy));
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn findings(file: &str) -> Vec<Finding> {
+        return entropy_findings(&Path::new("./tests/testing_files/entropy").join(file)).unwrap();
+    }
+
+    #[test]
+    fn profile_windows() {
+        let data: Vec<u8> = (0..=255).cycle().take(2500).collect();
+        let profile = EntropyProfile::from_bytes(&data, WINDOW_SIZE).unwrap();
+        assert_eq!(profile.size, 2500);
+        assert_eq!(profile.windows.len(), 3);
+        assert_eq!(profile.windows[0], 8.0);
+        assert!(profile.entropy > 7.99);
+    }
+
+    #[test]
+    fn profile_file_matches_bytes() {
+        let file = Path::new("./tests/testing_files/entropy/firmware.bin");
+        assert_eq!(
+            EntropyProfile::from_file(file, WINDOW_SIZE).unwrap(),
+            EntropyProfile::from_bytes(&std::fs::read(file).unwrap(), WINDOW_SIZE).unwrap()
+        );
+    }
+
+    #[test]
+    fn merged_regions() {
+        let profile = EntropyProfile {
+            size: 4500,
+            entropy: 5.0,
+            window_size: WINDOW_SIZE,
+            windows: vec![2.0, 7.5, 8.0, 3.0, 7.9],
+        };
+        assert_eq!(
+            profile.high_entropy_regions(),
+            vec![
+                EntropyRegion {
+                    offset: 1024,
+                    length: 2048,
+                    entropy: 7.75
+                },
+                EntropyRegion {
+                    offset: 4096,
+                    length: 404,
+                    entropy: 7.9
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_profile() {
+        let profile = EntropyProfile::from_bytes(&[], WINDOW_SIZE).unwrap();
+        assert_eq!(profile.entropy, 0.0);
+        assert_eq!(profile.median(), 0.0);
+        assert!(profile.high_entropy_regions().is_empty());
+    }
+
+    #[test]
+    fn embedded_key() {
+        let found = findings("firmware.bin");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(found[0].detail("offset"), Some(&String::from("8192")));
+        assert_eq!(found[0].detail("length"), Some(&String::from("2048")));
+    }
+
+    #[test]
+    fn encrypted_volume() {
+        let found = findings("backup.hc");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(found[0].description, "Possible encrypted container");
+    }
+
+    #[test]
+    fn compressed_archive() {
+        assert!(findings("photos.zip").is_empty());
+    }
+
+    #[test]
+    fn plain_text() {
+        assert!(findings("readme.txt").is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_window_size() {
+        EntropyProfile::from_bytes(b"some bytes", 0).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_window_size_file() {
+        EntropyProfile::from_file(Path::new("./tests/testing_files/entropy/readme.txt"), 0)
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_file() {
+        findings("missing.bin");
+    }
+}
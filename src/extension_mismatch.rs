@@ -0,0 +1,190 @@
+/*
+ * Find files whose contents do not match the type their extension claims.
+ */
+
+use crate::file_signatures::{FileSigniture, SignitureMatch};
+use crate::findings::{Finding, Severity};
+use crate::os_interactions::all_files;
+use std::error::Error;
+use std::path::Path;
+
+/// Signiture matches less certain than this are too likely to be false
+/// positives to report.
+const MIN_CONFIDENCE: f64 = 0.25;
+
+/// Compare the signiture of a file with its extension and return a finding if
+/// the two disagree. Files with an unrecognised or doubtful signiture are never
+/// flagged.
+pub(crate) fn check_extension_mismatch(file: &Path) -> Result<Option<Finding>, Box<dyn Error>> {
+    let found = SignitureMatch::from_file(file)?;
+    let signiture = found.signiture;
+
+    if signiture == FileSigniture::Unknown || found.confidence < MIN_CONFIDENCE {
+        return Ok(None);
+    }
+
+    /* Extensions are compared without regard to case. */
+    let extension = match file.extension() {
+        Some(exten) => exten.to_string_lossy().to_lowercase(),
+        None => String::new(),
+    };
+
+    if signiture
+        .expected_extensions()
+        .contains(&extension.as_str())
+    {
+        return Ok(None);
+    }
+
+    /* A disguised credential store is more serious than a misnamed file. */
+    let mut severity = Severity::Medium;
+    if signiture.is_credential_store() {
+        severity = severity.escalate();
+    }
+
+    let mut finding = Finding::new(file, severity, "File contents do not match its extension");
+    finding.add_detail("extension", extension);
+    finding.add_detail("signiture", format!("{:?}", signiture));
+    finding.add_detail("confidence", format!("{:.2}", found.confidence));
+    finding.add_detail(
+        "expected extensions",
+        signiture.expected_extensions().join(", "),
+    );
+    return Ok(Some(finding));
+}
+
+/// Check every file recursively in a directory for extension mismatches,
+/// skipping any file that cannot be read.
+pub(crate) fn extension_mismatch_search(directory: &Path) -> Result<Vec<Finding>, std::io::Error> {
+    return Ok(all_files(directory)?
+        .iter()
+        .filter_map(|x| check_extension_mismatch(x).ok().flatten())
+        .collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disguised_kdbx() {
+        let finding = check_extension_mismatch(Path::new(
+            "./tests/testing_files/extension_mismatch/holiday.jpg",
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(finding.severity, Severity::High);
+        assert_eq!(finding.detail("extension"), Some(&String::from("jpg")));
+        assert_eq!(finding.detail("signiture"), Some(&String::from("KDBX")));
+    }
+
+    #[test]
+    fn correct_kdbx() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/vault.kdbx"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn uppercase_extension() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/keys.KDBX"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn disguised_certificate() {
+        let finding = check_extension_mismatch(Path::new(
+            "./tests/testing_files/extension_mismatch/cert.txt",
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(finding.severity, Severity::Medium);
+        assert_eq!(
+            finding.detail("signiture"),
+            Some(&String::from("PEMCertificate"))
+        );
+    }
+
+    #[test]
+    fn doubtful_signiture() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/regf_noise.jpg"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_signiture() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/notes.txt"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn empty_file() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/empty.jpg"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn no_extension_allowed() {
+        assert_eq!(
+            check_extension_mismatch(Path::new(
+                "./tests/testing_files/extension_mismatch/Login Data"
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn file_does_not_exist() {
+        check_extension_mismatch(Path::new(
+            "./tests/testing_files/extension_mismatch/NO_FILE.txt",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn search_directory() {
+        let mut found =
+            extension_mismatch_search(Path::new("./tests/testing_files/extension_mismatch/"))
+                .unwrap()
+                .into_iter()
+                .map(|x| x.path)
+                .collect::<Vec<_>>();
+        found.sort();
+
+        assert_eq!(
+            found,
+            vec![
+                Path::new("./tests/testing_files/extension_mismatch/cert.txt").to_path_buf(),
+                Path::new("./tests/testing_files/extension_mismatch/holiday.jpg").to_path_buf(),
+            ]
+        );
+    }
+}
@@ -2,8 +2,12 @@
  * Functions associated with file signitures or magic numbers.
  */
 
-#[derive(PartialEq, Debug)]
-enum FileSigniture {
+use crate::os_interactions::read_file_bytes;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum FileSigniture {
     Unknown,
     MultiBitBitcoinWallet,
     ArmoredPGPPublicKey,
@@ -21,11 +25,16 @@ enum FileSigniture {
     OpenSSHPrivateKey,
     WindowsRegistry,
     KDBX,
+    JCEKSJavaKeyStore,
+    DPAPIBlob,
+    DPAPICredentialFile,
+    DPAPIMasterKey,
 }
 
 impl FileSigniture {
-    fn from_bytes(inital_file_bytes: &Vec<u8>) -> Self {
-        let file_first_bytes = vec![
+    /// The magic numbers of every known signiture, in the order of their index.
+    fn signiture_patterns() -> Vec<Vec<u8>> {
+        return vec![
             vec![
                 0x0A, 0x16, 0x6F, 0x72, 0x67, 0x2E, 0x62, 0x69, 0x74, 0x63, 0x6F, 0x69, 0x6E, 0x2E,
                 0x70, 0x72,
@@ -80,7 +89,46 @@ impl FileSigniture {
             ],
             vec![0x72, 0x65, 0x67, 0x66],
             vec![0x03, 0xD9, 0xA2, 0x9A, 0x67, 0xFB, 0x4B, 0xB5],
+            vec![0xCE, 0xCE, 0xCE, 0xCE],
+            vec![
+                0x01, 0x00, 0x00, 0x00, 0xD0, 0x8C, 0x9D, 0xDF, 0x01, 0x15, 0xD1, 0x11, 0x8C, 0x7A,
+                0x00, 0xC0, 0x4F, 0xC2, 0x97, 0xEB,
+            ],
+            vec![
+                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+                0x00, 0x00, 0xD0, 0x8C, 0x9D, 0xDF, 0x01, 0x15, 0xD1, 0x11, 0x8C, 0x7A, 0x00, 0xC0,
+                0x4F, 0xC2, 0x97, 0xEB,
+            ],
+            vec![
+                0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x2D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2D, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x2D, 0x00,
+            ],
         ];
+    }
+
+    /// The positions within the magic number of this signiture that vary
+    /// between files and match any byte, such as lengths and GUID digits.
+    fn variable_bytes(&self) -> Vec<usize> {
+        return match self {
+            /* The size of the blob that follows the credential file header. */
+            FileSigniture::DPAPICredentialFile => (4..8).collect(),
+            /* The low bytes of the UTF-16 master key GUID, other than its dashes. */
+            FileSigniture::DPAPIMasterKey => (0..24)
+                .filter(|x| ![8, 13, 18, 23].contains(x))
+                .map(|x| 12 + x * 2)
+                .collect(),
+            _ => Vec::new(),
+        };
+    }
+
+    pub(crate) fn from_bytes(inital_file_bytes: &Vec<u8>) -> Self {
+        let file_first_bytes = FileSigniture::signiture_patterns();
+        let variable_bytes: Vec<Vec<usize>> = (0..file_first_bytes.len())
+            .map(|idx| FileSigniture::from_index(idx).variable_bytes())
+            .collect();
         let mut matching_patterns = vec![true; file_first_bytes.len()];
         let mut curr_byte_check_idx = 0;
 
@@ -88,6 +136,11 @@ impl FileSigniture {
         loop {
             let mut match_happened = false;
 
+            /* The sample ran out before any pattern was fully matched. */
+            if curr_byte_check_idx >= inital_file_bytes.len() {
+                return FileSigniture::Unknown;
+            }
+
             for pattr_idx in 0..file_first_bytes.len() {
                 if !matching_patterns[pattr_idx] {
                     continue;
@@ -96,6 +149,7 @@ impl FileSigniture {
                 /* Check that this byte of the sample matches this pattern byte. */
                 if file_first_bytes[pattr_idx][curr_byte_check_idx]
                     != inital_file_bytes[curr_byte_check_idx]
+                    && !variable_bytes[pattr_idx].contains(&curr_byte_check_idx)
                 {
                     matching_patterns[pattr_idx] = false;
                     continue;
@@ -133,9 +187,302 @@ impl FileSigniture {
             13 => FileSigniture::OpenSSHPrivateKey,
             14 => FileSigniture::WindowsRegistry,
             15 => FileSigniture::KDBX,
+            16 => FileSigniture::JCEKSJavaKeyStore,
+            17 => FileSigniture::DPAPIBlob,
+            18 => FileSigniture::DPAPICredentialFile,
+            19 => FileSigniture::DPAPIMasterKey,
             _ => panic!("Index {idx} has no matching signiture!"),
         };
     }
+
+    /// The lowercase file extensions a file with this signiture would normally
+    /// have. An empty string means the file is expected to have no extension.
+    pub(crate) fn expected_extensions(&self) -> Vec<&'static str> {
+        return match self {
+            FileSigniture::Unknown => Vec::new(),
+            FileSigniture::MultiBitBitcoinWallet => vec!["wallet", "key", ""],
+            FileSigniture::ArmoredPGPPublicKey => vec!["asc", "gpg", "pgp", "pub", "key", "txt"],
+            FileSigniture::SQLiteDatabase => {
+                vec![
+                    "sqlite", "sqlite3", "db", "db3", "s3db", "sl3", "sqlitedb", "",
+                ]
+            }
+            FileSigniture::TelegramDesktopFile => vec![""],
+            FileSigniture::TelegramDesktopEncryptedFile => vec![""],
+            FileSigniture::JKSJavaKeyStore => vec!["jks", "keystore", "ks", "truststore", ""],
+            FileSigniture::PEMCertificate => vec!["pem", "crt", "cer", "cert", "ca-bundle"],
+            FileSigniture::PEMCertificateRequest => vec!["csr", "pem", "req"],
+            FileSigniture::PEMPrivateKey => vec!["pem", "key", "p8", "pk8", ""],
+            FileSigniture::PEMDSAPrivateKey => vec!["pem", "key", ""],
+            FileSigniture::PEMRSAPrivateKey => vec!["pem", "key", ""],
+            FileSigniture::PuTTYPrivateKeyV2 => vec!["ppk"],
+            FileSigniture::PuTTYPrivateKeyV3 => vec!["ppk"],
+            FileSigniture::OpenSSHPrivateKey => vec!["pem", "key", ""],
+            FileSigniture::WindowsRegistry => {
+                vec!["hiv", "hive", "dat", "sav", "log1", "log2", "bak", ""]
+            }
+            FileSigniture::KDBX => vec!["kdbx"],
+            FileSigniture::JCEKSJavaKeyStore => vec!["jceks", "keystore", "ks", ""],
+            FileSigniture::DPAPIBlob => vec!["bin", "blob", "dat", ""],
+            FileSigniture::DPAPICredentialFile => vec![""],
+            FileSigniture::DPAPIMasterKey => vec![""],
+        };
+    }
+
+    /// Does a file with this signiture directly hold credentials or keys.
+    pub(crate) fn is_credential_store(&self) -> bool {
+        return matches!(
+            self,
+            FileSigniture::MultiBitBitcoinWallet
+                | FileSigniture::JKSJavaKeyStore
+                | FileSigniture::PEMPrivateKey
+                | FileSigniture::PEMDSAPrivateKey
+                | FileSigniture::PEMRSAPrivateKey
+                | FileSigniture::PuTTYPrivateKeyV2
+                | FileSigniture::PuTTYPrivateKeyV3
+                | FileSigniture::OpenSSHPrivateKey
+                | FileSigniture::KDBX
+                | FileSigniture::JCEKSJavaKeyStore
+                | FileSigniture::DPAPIBlob
+                | FileSigniture::DPAPICredentialFile
+                | FileSigniture::DPAPIMasterKey
+        );
+    }
+
+    /// The number of bytes in the magic number of this signiture.
+    fn pattern_len(&self) -> usize {
+        let patterns = FileSigniture::signiture_patterns();
+        return (0..patterns.len())
+            .find(|idx| FileSigniture::from_index(*idx) == *self)
+            .map_or(0, |idx| patterns[idx].len());
+    }
+
+    /// The smallest and largest size in bytes a real file of this type is
+    /// likely to have.
+    fn plausible_size_range(&self) -> (u64, u64) {
+        return match self {
+            FileSigniture::Unknown => (0, u64::MAX),
+            FileSigniture::MultiBitBitcoinWallet => (64, 64 * 1024 * 1024),
+            FileSigniture::ArmoredPGPPublicKey => (256, 16 * 1024 * 1024),
+            FileSigniture::SQLiteDatabase => (512, u64::MAX),
+            FileSigniture::TelegramDesktopFile => (24, 256 * 1024 * 1024),
+            FileSigniture::TelegramDesktopEncryptedFile => (24, 256 * 1024 * 1024),
+            FileSigniture::JKSJavaKeyStore => (32, 64 * 1024 * 1024),
+            FileSigniture::PEMCertificate => (256, 16 * 1024 * 1024),
+            FileSigniture::PEMCertificateRequest => (256, 1024 * 1024),
+            FileSigniture::PEMPrivateKey => (128, 1024 * 1024),
+            FileSigniture::PEMDSAPrivateKey => (256, 1024 * 1024),
+            FileSigniture::PEMRSAPrivateKey => (256, 1024 * 1024),
+            FileSigniture::PuTTYPrivateKeyV2 => (128, 1024 * 1024),
+            FileSigniture::PuTTYPrivateKeyV3 => (128, 1024 * 1024),
+            FileSigniture::OpenSSHPrivateKey => (128, 1024 * 1024),
+            FileSigniture::WindowsRegistry => (4096, u64::MAX),
+            FileSigniture::KDBX => (128, u64::MAX),
+            FileSigniture::JCEKSJavaKeyStore => (32, 64 * 1024 * 1024),
+            FileSigniture::DPAPIBlob => (64, 1024 * 1024),
+            FileSigniture::DPAPICredentialFile => (96, 1024 * 1024),
+            FileSigniture::DPAPIMasterKey => (128, 64 * 1024),
+        };
+    }
+
+    /// Check fields beyond the magic number that a genuine file of this type
+    /// must contain. Returns `None` when no check exists or the sample is too
+    /// short to perform it.
+    fn validate_structure(&self, inital_file_bytes: &Vec<u8>, file_size: u64) -> Option<bool> {
+        let bytes = inital_file_bytes;
+
+        return match self {
+            FileSigniture::SQLiteDatabase => {
+                if bytes.len() < 24 {
+                    return None;
+                }
+
+                /* A page size of one is used to represent 65536. */
+                let page_size = match u16::from_be_bytes([bytes[16], bytes[17]]) as u64 {
+                    1 => 65536,
+                    size => size,
+                };
+
+                Some(
+                    page_size.is_power_of_two()
+                        && page_size >= 512
+                        && (bytes[18] == 1 || bytes[18] == 2)
+                        && (bytes[19] == 1 || bytes[19] == 2)
+                        && bytes[21] == 64
+                        && bytes[22] == 32
+                        && bytes[23] == 32
+                        && file_size.is_multiple_of(page_size),
+                )
+            }
+            FileSigniture::WindowsRegistry => {
+                if bytes.len() < 512 {
+                    return None;
+                }
+
+                /* The base block checksum is the XOR of its first 127 dwords. */
+                let mut checksum = (0..508)
+                    .step_by(4)
+                    .map(|x| {
+                        u32::from_le_bytes([bytes[x], bytes[x + 1], bytes[x + 2], bytes[x + 3]])
+                    })
+                    .fold(0, |acc, x| acc ^ x);
+
+                if checksum == u32::MAX {
+                    checksum = u32::MAX - 1;
+                } else if checksum == 0 {
+                    checksum = 1;
+                }
+                Some(
+                    checksum
+                        == u32::from_le_bytes([bytes[508], bytes[509], bytes[510], bytes[511]]),
+                )
+            }
+            FileSigniture::KDBX => {
+                if bytes.len() < 12 {
+                    return None;
+                }
+                let major_version = u16::from_le_bytes([bytes[10], bytes[11]]);
+                Some((2..=4).contains(&major_version))
+            }
+            FileSigniture::JKSJavaKeyStore | FileSigniture::JCEKSJavaKeyStore => {
+                if bytes.len() < 12 {
+                    return None;
+                }
+                let version = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+                let entries = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+                Some((version == 1 || version == 2) && (entries as u64) * 32 <= file_size)
+            }
+            FileSigniture::TelegramDesktopFile | FileSigniture::TelegramDesktopEncryptedFile => {
+                if bytes.len() < 8 {
+                    return None;
+                }
+                let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+                Some(version > 0 && version < 100_000_000)
+            }
+            FileSigniture::PuTTYPrivateKeyV2 | FileSigniture::PuTTYPrivateKeyV3 => {
+                let header = String::from_utf8_lossy(&bytes[self.pattern_len()..]);
+                let algorithm = header.lines().next()?;
+                Some(
+                    [
+                        "ssh-rsa",
+                        "ssh-dss",
+                        "ssh-ed25519",
+                        "ssh-ed448",
+                        "ecdsa-sha2-nistp256",
+                        "ecdsa-sha2-nistp384",
+                        "ecdsa-sha2-nistp521",
+                    ]
+                    .contains(&algorithm.trim()),
+                )
+            }
+            FileSigniture::ArmoredPGPPublicKey
+            | FileSigniture::PEMCertificate
+            | FileSigniture::PEMCertificateRequest
+            | FileSigniture::PEMPrivateKey
+            | FileSigniture::PEMDSAPrivateKey
+            | FileSigniture::PEMRSAPrivateKey
+            | FileSigniture::OpenSSHPrivateKey => {
+                /* Armored banners are always followed by a line break. */
+                let pattr_len = self.pattern_len();
+                if bytes.len() <= pattr_len {
+                    return None;
+                }
+                Some(bytes[pattr_len] == b'\n' || bytes[pattr_len] == b'\r')
+            }
+            FileSigniture::DPAPIBlob | FileSigniture::DPAPICredentialFile => {
+                /* Credential files wrap a blob in a 12 byte header. */
+                let start = if *self == FileSigniture::DPAPICredentialFile {
+                    12
+                } else {
+                    0
+                };
+                if bytes.len() < start + 48 {
+                    return None;
+                }
+                let read_u32 = |x: usize| {
+                    u32::from_le_bytes([bytes[x], bytes[x + 1], bytes[x + 2], bytes[x + 3]])
+                };
+
+                let block_fits =
+                    *self == FileSigniture::DPAPIBlob || read_u32(4) as u64 + 12 <= file_size;
+                let description_len = read_u32(start + 44);
+                Some(
+                    block_fits
+                        && read_u32(start + 20) == 1
+                        && description_len.is_multiple_of(2)
+                        && (description_len as u64) < file_size,
+                )
+            }
+            FileSigniture::DPAPIMasterKey => {
+                if bytes.len() < 128 {
+                    return None;
+                }
+                let read_u64 =
+                    |x: usize| u64::from_le_bytes(bytes[x..x + 8].try_into().unwrap_or([0; 8]));
+
+                /* The four key sections follow the 128 byte header exactly. */
+                let sections = (96..128)
+                    .step_by(8)
+                    .map(read_u64)
+                    .try_fold(0u64, |acc, x| acc.checked_add(x));
+                Some(read_u64(96) > 0 && sections.is_some_and(|x| x + 128 == file_size))
+            }
+            FileSigniture::Unknown | FileSigniture::MultiBitBitcoinWallet => None,
+        };
+    }
+}
+
+/// A signiture detected in a file along with how likely it is, between zero and
+/// one, that the file genuinely is of that type.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) struct SignitureMatch {
+    pub(crate) signiture: FileSigniture,
+    pub(crate) confidence: f64,
+}
+
+impl SignitureMatch {
+    /// Score a signiture match using the length of the magic number, any
+    /// structural checks that are possible on the sample and whether the size
+    /// of the file is plausible for that type.
+    pub(crate) fn from_bytes(inital_file_bytes: &Vec<u8>, file_size: u64) -> Self {
+        let signiture = FileSigniture::from_bytes(inital_file_bytes);
+
+        if signiture == FileSigniture::Unknown {
+            return SignitureMatch {
+                signiture,
+                confidence: 0.0,
+            };
+        }
+
+        /* Every four fixed bytes of magic number halves the chance of a false match. */
+        let fixed_len = signiture.pattern_len() - signiture.variable_bytes().len();
+        let mut confidence = 1.0 - 0.5_f64.powf(fixed_len as f64 / 4.0);
+
+        match signiture.validate_structure(inital_file_bytes, file_size) {
+            Some(true) => confidence += (1.0 - confidence) * 0.8,
+            Some(false) => confidence *= 0.2,
+            None => {}
+        }
+
+        let (min_size, max_size) = signiture.plausible_size_range();
+        if file_size < min_size || file_size > max_size {
+            confidence *= 0.5;
+        }
+
+        return SignitureMatch {
+            signiture,
+            confidence,
+        };
+    }
+
+    /// Read the start of a file and score the signiture found in it.
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        let file_size = std::fs::metadata(file)?.len();
+        return Ok(SignitureMatch::from_bytes(
+            &read_file_bytes(file, 512)?,
+            file_size,
+        ));
+    }
 }
 
 #[cfg(test)]
@@ -308,6 +655,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detect_jceks_java_key_store() {
+        assert_eq!(
+            FileSigniture::from_bytes(&vec![0xCE, 0xCE, 0xCE, 0xCE]),
+            FileSigniture::JCEKSJavaKeyStore
+        );
+    }
+
+    #[test]
+    fn detect_dpapi_blob() {
+        assert_eq!(
+            FileSigniture::from_bytes(&vec![
+                0x01, 0x00, 0x00, 0x00, 0xD0, 0x8C, 0x9D, 0xDF, 0x01, 0x15, 0xD1, 0x11, 0x8C, 0x7A,
+                0x00, 0xC0, 0x4F, 0xC2, 0x97, 0xEB,
+            ]),
+            FileSigniture::DPAPIBlob
+        );
+    }
+
+    #[test]
+    fn detect_dpapi_credential_file() {
+        /* The block size in bytes four to eight can be anything. */
+        assert_eq!(
+            FileSigniture::from_bytes(&vec![
+                0x01, 0x00, 0x00, 0x00, 0x3A, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+                0x00, 0x00, 0xD0, 0x8C, 0x9D, 0xDF, 0x01, 0x15, 0xD1, 0x11, 0x8C, 0x7A, 0x00, 0xC0,
+                0x4F, 0xC2, 0x97, 0xEB,
+            ]),
+            FileSigniture::DPAPICredentialFile
+        );
+    }
+
+    #[test]
+    fn detect_dpapi_master_key() {
+        let mut header = vec![
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        for character in "3e4a1f2b-6c5d-4e7f-8a9b-0c1d2e3f4a5b".encode_utf16() {
+            header.extend(character.to_le_bytes());
+        }
+        assert_eq!(
+            FileSigniture::from_bytes(&header),
+            FileSigniture::DPAPIMasterKey
+        );
+    }
+
+    #[test]
+    fn detect_dpapi_master_key_without_dashes() {
+        let mut header = vec![
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        for character in "3e4a1f2b06c5d04e7f08a9b00c1d2e3f4a5b".encode_utf16() {
+            header.extend(character.to_le_bytes());
+        }
+        assert_eq!(FileSigniture::from_bytes(&header), FileSigniture::Unknown);
+    }
+
     #[test]
     #[should_panic]
     fn invalid_file_idx_0() {
@@ -332,6 +736,49 @@ mod tests {
         FileSigniture::from_index(99);
     }
 
+    #[test]
+    fn detect_empty_sample() {
+        assert_eq!(
+            FileSigniture::from_bytes(&Vec::new()),
+            FileSigniture::Unknown
+        );
+    }
+
+    #[test]
+    fn detect_truncated_sample() {
+        assert_eq!(
+            FileSigniture::from_bytes(&vec![0x03, 0xD9, 0xA2, 0x9A]),
+            FileSigniture::Unknown
+        );
+    }
+
+    #[test]
+    fn detect_unknown_sample() {
+        assert_eq!(
+            FileSigniture::from_bytes(&vec![0x4E, 0x61, 0x6D, 0x65, 0x20, 0x2C, 0x41, 0x67]),
+            FileSigniture::Unknown
+        );
+    }
+
+    #[test]
+    fn credential_store_signitures() {
+        assert!(FileSigniture::KDBX.is_credential_store());
+        assert!(FileSigniture::OpenSSHPrivateKey.is_credential_store());
+        assert!(FileSigniture::DPAPIMasterKey.is_credential_store());
+        assert!(!FileSigniture::PEMCertificate.is_credential_store());
+        assert!(!FileSigniture::Unknown.is_credential_store());
+    }
+
+    #[test]
+    fn expected_extensions_kdbx() {
+        assert_eq!(FileSigniture::KDBX.expected_extensions(), vec!["kdbx"]);
+    }
+
+    #[test]
+    fn expected_extensions_unknown() {
+        assert!(FileSigniture::Unknown.expected_extensions().is_empty());
+    }
+
     #[test]
     fn detect_multibit_bitcoin_wallet_extended() {
         assert_eq!(
@@ -513,4 +960,147 @@ mod tests {
             FileSigniture::KDBX
         );
     }
+
+    #[test]
+    fn confidence_unknown() {
+        assert_eq!(
+            SignitureMatch::from_bytes(&vec![0x4E, 0x61, 0x6D, 0x65], 4),
+            SignitureMatch {
+                signiture: FileSigniture::Unknown,
+                confidence: 0.0
+            }
+        );
+    }
+
+    #[test]
+    fn confidence_valid_registry() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/valid_hive.dat",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::WindowsRegistry);
+        assert!(0.899 < found.confidence && found.confidence < 0.901);
+    }
+
+    #[test]
+    fn confidence_bad_checksum_registry() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/bad_checksum_hive.dat",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::WindowsRegistry);
+        assert!(0.099 < found.confidence && found.confidence < 0.101);
+    }
+
+    #[test]
+    fn confidence_valid_sqlite() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/valid.sqlite",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::SQLiteDatabase);
+        assert!(0.9874 < found.confidence && found.confidence < 0.9876);
+    }
+
+    #[test]
+    fn confidence_bad_page_size_sqlite() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/bad_page_size.sqlite",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::SQLiteDatabase);
+        assert!(0.1874 < found.confidence && found.confidence < 0.1876);
+    }
+
+    #[test]
+    fn confidence_valid_kdbx() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/valid.kdbx",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::KDBX);
+        assert!(0.949 < found.confidence && found.confidence < 0.951);
+    }
+
+    #[test]
+    fn confidence_bad_version_kdbx() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/bad_version.kdbx",
+        ))
+        .unwrap();
+        assert!(0.149 < found.confidence && found.confidence < 0.151);
+    }
+
+    #[test]
+    fn confidence_implausible_size_kdbx() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/tiny.kdbx",
+        ))
+        .unwrap();
+        assert!(0.474 < found.confidence && found.confidence < 0.476);
+    }
+
+    #[test]
+    fn confidence_telegram_desktop_file() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/signiture_match/tdata_file",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::TelegramDesktopFile);
+        assert!(0.899 < found.confidence && found.confidence < 0.901);
+    }
+
+    #[test]
+    fn confidence_valid_dpapi_master_key() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/dpapi/Users/alice/AppData/Roaming/Microsoft/Protect/S-1-5-21-3623811015-3361044348-30300820-1013/3e4a1f2b-6c5d-4e7f-8a9b-0c1d2e3f4a5b",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::DPAPIMasterKey);
+        assert!(found.confidence > 0.99);
+    }
+
+    #[test]
+    fn confidence_valid_dpapi_credential_file() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/dpapi/Users/alice/AppData/Local/Microsoft/Credentials/DFBE70A7E5CC19A398EBF1B96859CE5D",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::DPAPICredentialFile);
+        assert!(found.confidence > 0.99);
+    }
+
+    #[test]
+    fn confidence_truncated_dpapi_blob() {
+        let found = SignitureMatch::from_file(&Path::new(
+            "./tests/testing_files/dpapi/exported/truncated.bin",
+        ))
+        .unwrap();
+        assert_eq!(found.signiture, FileSigniture::DPAPIBlob);
+        assert!(0.496 < found.confidence && found.confidence < 0.498);
+    }
+
+    #[test]
+    fn confidence_long_pattern_beats_short() {
+        let mut pem = b"-----BEGIN CERTIFICATE-----\nMIIB".to_vec();
+        pem.resize(64, b'A');
+        let mut jks = vec![0xFE, 0xED, 0xFE, 0xED];
+        jks.resize(64, 0xFF);
+
+        assert!(
+            SignitureMatch::from_bytes(&pem, 1024).confidence
+                > SignitureMatch::from_bytes(&jks, 1024).confidence
+        );
+    }
+
+    #[test]
+    fn confidence_putty_algorithm() {
+        let good = b"PuTTY-User-Key-File-3: ssh-ed25519\nEncryption: none\n".to_vec();
+        let bad = b"PuTTY-User-Key-File-3: not-a-key\nEncryption: none\n".to_vec();
+
+        assert!(
+            SignitureMatch::from_bytes(&good, 512).confidence
+                > SignitureMatch::from_bytes(&bad, 512).confidence
+        );
+    }
 }
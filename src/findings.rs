@@ -0,0 +1,110 @@
+/*
+ * The results reported by the detectors and how serious they are.
+ */
+
+use std::path::{Path, PathBuf};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub(crate) enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Move the severity one level higher, stopping at critical.
+    pub(crate) fn escalate(&self) -> Self {
+        return match self {
+            Severity::Info => Severity::Low,
+            Severity::Low => Severity::Medium,
+            Severity::Medium => Severity::High,
+            Severity::High => Severity::Critical,
+            Severity::Critical => Severity::Critical,
+        };
+    }
+
+    /// Move the severity one level lower, stopping at info.
+    pub(crate) fn downgrade(&self) -> Self {
+        return match self {
+            Severity::Info => Severity::Info,
+            Severity::Low => Severity::Info,
+            Severity::Medium => Severity::Low,
+            Severity::High => Severity::Medium,
+            Severity::Critical => Severity::High,
+        };
+    }
+}
+
+/// Something of interest found on the system, with any extra details the
+/// detector was able to extract stored as name and value pairs.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct Finding {
+    pub(crate) path: PathBuf,
+    pub(crate) severity: Severity,
+    pub(crate) description: String,
+    pub(crate) details: Vec<(String, String)>,
+}
+
+impl Finding {
+    pub(crate) fn new(path: &Path, severity: Severity, description: &str) -> Self {
+        return Finding {
+            path: path.to_path_buf(),
+            severity,
+            description: String::from(description),
+            details: Vec::new(),
+        };
+    }
+
+    /// Record an extra piece of information about the finding.
+    pub(crate) fn add_detail(&mut self, name: &str, value: String) {
+        self.details.push((String::from(name), value));
+    }
+
+    /// Return the value of the first detail with a specific name.
+    pub(crate) fn detail(&self, name: &str) -> Option<&String> {
+        return self
+            .details
+            .iter()
+            .find(|(det_name, _)| det_name == name)
+            .map(|(_, value)| value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_ordering() {
+        assert!(Severity::Info < Severity::Low);
+        assert!(Severity::Low < Severity::Medium);
+        assert!(Severity::Medium < Severity::High);
+        assert!(Severity::High < Severity::Critical);
+    }
+
+    #[test]
+    fn severity_escalate() {
+        assert_eq!(Severity::Medium.escalate(), Severity::High);
+        assert_eq!(Severity::Critical.escalate(), Severity::Critical);
+    }
+
+    #[test]
+    fn severity_downgrade() {
+        assert_eq!(Severity::Medium.downgrade(), Severity::Low);
+        assert_eq!(Severity::Info.downgrade(), Severity::Info);
+    }
+
+    #[test]
+    fn finding_details() {
+        let mut finding = Finding::new(Path::new("a.txt"), Severity::Low, "Test");
+        finding.add_detail("first", String::from("1"));
+        finding.add_detail("second", String::from("2"));
+        finding.add_detail("first", String::from("3"));
+
+        assert_eq!(finding.detail("first"), Some(&String::from("1")));
+        assert_eq!(finding.detail("second"), Some(&String::from("2")));
+        assert_eq!(finding.detail("third"), None);
+    }
+}
@@ -0,0 +1,561 @@
+/*
+ * Enumerate the entries of Java JKS and JCEKS keystores to show which hold
+ * private or secret keys. Protected key material is never decrypted.
+ * PKCS12 keystores are a different, ASN.1 based format and are not handled
+ * here.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::findings::{Finding, Severity};
+use crate::x509::{Certificate, format_timestamp, unix_now};
+use std::error::Error;
+use std::path::Path;
+
+/// Objects nested deeper than this in a serialised secret key are treated as
+/// malformed rather than risking the stack on hostile input.
+const MAX_DEPTH: usize = 128;
+
+const JKS_MAGIC: u32 = 0xFEEDFEED;
+const JCEKS_MAGIC: u32 = 0xCECECECE;
+
+const TAG_PRIVATE_KEY: u32 = 1;
+const TAG_TRUSTED_CERTIFICATE: u32 = 2;
+const TAG_SECRET_KEY: u32 = 3;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum KeystoreFormat {
+    Jks,
+    Jceks,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum KeystoreEntryKind {
+    PrivateKey,
+    TrustedCertificate,
+    SecretKey,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct KeystoreEntry {
+    pub(crate) alias: String,
+    pub(crate) kind: KeystoreEntryKind,
+    /// When the entry was added, in seconds since the Unix epoch.
+    pub(crate) created: i64,
+    /// The certificate chain of a private key, or the single trusted
+    /// certificate. Certificates of types other than X.509 are left out.
+    pub(crate) certificates: Vec<Certificate>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct JavaKeystore {
+    pub(crate) format: KeystoreFormat,
+    pub(crate) version: u32,
+    pub(crate) entries: Vec<KeystoreEntry>,
+}
+
+impl JavaKeystore {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = ByteReader::new(data);
+
+        let format = match reader.read_u32_be()? {
+            JKS_MAGIC => KeystoreFormat::Jks,
+            JCEKS_MAGIC => KeystoreFormat::Jceks,
+            _ => return Err(Box::new(invalid_data("Not a Java keystore"))),
+        };
+        let version = reader.read_u32_be()?;
+        if version != 1 && version != 2 {
+            return Err(Box::new(invalid_data(&format!(
+                "Unsupported keystore version {version}"
+            ))));
+        }
+
+        let entry_count = reader.read_u32_be()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entry_count {
+            let tag = reader.read_u32_be()?;
+            let alias = read_java_utf(&mut reader)?;
+            let created = reader.read_u64_be()? as i64 / 1000;
+
+            let (kind, certificates) = match tag {
+                TAG_PRIVATE_KEY => {
+                    let key_len = reader.read_u32_be()? as usize;
+                    reader.skip(key_len)?;
+
+                    let mut certificates = Vec::new();
+                    for _ in 0..reader.read_u32_be()? {
+                        certificates.extend(read_certificate(&mut reader, version)?);
+                    }
+                    (KeystoreEntryKind::PrivateKey, certificates)
+                }
+                TAG_TRUSTED_CERTIFICATE => (
+                    KeystoreEntryKind::TrustedCertificate,
+                    read_certificate(&mut reader, version)?
+                        .into_iter()
+                        .collect(),
+                ),
+                TAG_SECRET_KEY if format == KeystoreFormat::Jceks => {
+                    /* Secret keys are stored as a serialised Java SealedObject. */
+                    JavaStreamSkipper::new(&mut reader).skip_stream()?;
+                    (KeystoreEntryKind::SecretKey, Vec::new())
+                }
+                _ => {
+                    return Err(Box::new(invalid_data(&format!(
+                        "Unknown keystore entry tag {tag}"
+                    ))));
+                }
+            };
+
+            entries.push(KeystoreEntry {
+                alias,
+                kind,
+                created,
+                certificates,
+            });
+        }
+
+        return Ok(JavaKeystore {
+            format,
+            version,
+            entries,
+        });
+    }
+
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return JavaKeystore::from_bytes(&std::fs::read(file)?);
+    }
+
+    /// Does the keystore hold anything other than trusted certificates.
+    pub(crate) fn holds_keys(&self) -> bool {
+        return self
+            .entries
+            .iter()
+            .any(|x| x.kind != KeystoreEntryKind::TrustedCertificate);
+    }
+}
+
+/// Read a string written by Java's `DataOutput.writeUTF`.
+fn read_java_utf(reader: &mut ByteReader) -> Result<String, Box<dyn Error>> {
+    let length = reader.read_u16_be()? as usize;
+    return Ok(String::from_utf8_lossy(reader.read_bytes(length)?).to_string());
+}
+
+/// Read one certificate, decoding it when it is an X.509 certificate.
+fn read_certificate(
+    reader: &mut ByteReader,
+    version: u32,
+) -> Result<Option<Certificate>, Box<dyn Error>> {
+    /* Version 1 keystores only ever held X.509 certificates. */
+    let certificate_type = if version == 2 {
+        read_java_utf(reader)?
+    } else {
+        String::from("X.509")
+    };
+    let length = reader.read_u32_be()? as usize;
+    let data = reader.read_bytes(length)?;
+
+    if certificate_type != "X.509" {
+        return Ok(None);
+    }
+    return Ok(Some(Certificate::from_der(data)?));
+}
+
+/// Walks over a Java object serialisation stream without building the
+/// objects, remembering only enough about each class to find its end.
+struct JavaStreamSkipper<'a, 'b> {
+    reader: &'b mut ByteReader<'a>,
+    /// The field type codes of every class description, in handle order.
+    /// Handles that are not class descriptions hold `None`.
+    handles: Vec<Option<JavaClass>>,
+    /// How many content elements are being skipped inside one another.
+    depth: usize,
+}
+
+#[derive(Clone)]
+struct JavaClass {
+    name: String,
+    flags: u8,
+    fields: Vec<u8>,
+    super_class: Option<usize>,
+}
+
+const STREAM_MAGIC: u16 = 0xACED;
+const TC_NULL: u8 = 0x70;
+const TC_REFERENCE: u8 = 0x71;
+const TC_CLASSDESC: u8 = 0x72;
+const TC_OBJECT: u8 = 0x73;
+const TC_STRING: u8 = 0x74;
+const TC_ARRAY: u8 = 0x75;
+const TC_BLOCKDATA: u8 = 0x77;
+const TC_ENDBLOCKDATA: u8 = 0x78;
+const TC_BLOCKDATALONG: u8 = 0x7A;
+const TC_LONGSTRING: u8 = 0x7C;
+const BASE_HANDLE: u32 = 0x7E0000;
+const SC_WRITE_METHOD: u8 = 0x01;
+
+impl<'a, 'b> JavaStreamSkipper<'a, 'b> {
+    fn new(reader: &'b mut ByteReader<'a>) -> Self {
+        return JavaStreamSkipper {
+            reader,
+            handles: Vec::new(),
+            depth: 0,
+        };
+    }
+
+    /// Skip the stream header and the single object that follows it.
+    fn skip_stream(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.reader.read_u16_be()? != STREAM_MAGIC {
+            return Err(Box::new(invalid_data("Not a Java serialisation stream")));
+        }
+        self.reader.skip(2)?;
+        self.skip_content()?;
+        return Ok(());
+    }
+
+    /// Skip one content element, returning the handle of the class
+    /// description when the element was one.
+    fn skip_content(&mut self) -> Result<Option<usize>, Box<dyn Error>> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Box::new(invalid_data(
+                "Serialised object nested too deeply",
+            )));
+        }
+        self.depth += 1;
+        let skipped = self.skip_element();
+        self.depth -= 1;
+        return skipped;
+    }
+
+    fn skip_element(&mut self) -> Result<Option<usize>, Box<dyn Error>> {
+        let tag = self.reader.read_u8()?;
+
+        match tag {
+            TC_NULL => {}
+            TC_REFERENCE => {
+                let handle = self.reader.read_u32_be()?.wrapping_sub(BASE_HANDLE) as usize;
+                if handle >= self.handles.len() {
+                    return Err(Box::new(invalid_data("Dangling serialisation handle")));
+                }
+                return Ok(self.handles[handle].as_ref().map(|_| handle));
+            }
+            TC_STRING => {
+                let length = self.reader.read_u16_be()? as usize;
+                self.reader.skip(length)?;
+                self.handles.push(None);
+            }
+            TC_LONGSTRING => {
+                let length = self.reader.read_u64_be()? as usize;
+                self.reader.skip(length)?;
+                self.handles.push(None);
+            }
+            TC_BLOCKDATA => {
+                let length = self.reader.read_u8()? as usize;
+                self.reader.skip(length)?;
+            }
+            TC_BLOCKDATALONG => {
+                let length = self.reader.read_u32_be()? as usize;
+                self.reader.skip(length)?;
+            }
+            TC_CLASSDESC => return Ok(Some(self.read_class_description()?)),
+            TC_OBJECT => {
+                let class = self.expect_class()?;
+                self.handles.push(None);
+                self.skip_object_data(class)?;
+            }
+            TC_ARRAY => {
+                let class = self.expect_class()?;
+                self.handles.push(None);
+
+                let element_type = self.class(class)?.name.as_bytes().get(1).copied();
+                let length = self.reader.read_u32_be()? as usize;
+                match element_type.and_then(primitive_size) {
+                    Some(size) => self.reader.skip(length * size)?,
+                    None => {
+                        for _ in 0..length {
+                            self.skip_content()?;
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(Box::new(invalid_data(&format!(
+                    "Unsupported serialisation tag {tag:#04x}"
+                ))));
+            }
+        }
+        return Ok(None);
+    }
+
+    fn class(&self, handle: usize) -> Result<&JavaClass, Box<dyn Error>> {
+        return self.handles[handle]
+            .as_ref()
+            .ok_or_else(|| Box::new(invalid_data("Handle is not a class")) as Box<dyn Error>);
+    }
+
+    /// Read an element that must be a class description or a reference to one.
+    fn expect_class(&mut self) -> Result<usize, Box<dyn Error>> {
+        return self.skip_content()?.ok_or_else(|| {
+            Box::new(invalid_data("Expected a class description")) as Box<dyn Error>
+        });
+    }
+
+    fn read_class_description(&mut self) -> Result<usize, Box<dyn Error>> {
+        let name = read_java_utf(self.reader)?;
+        self.reader.skip(8)?;
+        let flags = self.reader.read_u8()?;
+
+        /* The handle is assigned before the field names are read. */
+        let handle = self.handles.len();
+        self.handles.push(Some(JavaClass {
+            name,
+            flags,
+            fields: Vec::new(),
+            super_class: None,
+        }));
+
+        let mut fields = Vec::new();
+        for _ in 0..self.reader.read_u16_be()? {
+            let type_code = self.reader.read_u8()?;
+            read_java_utf(self.reader)?;
+            if type_code == b'[' || type_code == b'L' {
+                self.skip_content()?;
+            }
+            fields.push(type_code);
+        }
+        self.skip_annotations()?;
+        let super_class = self.skip_content()?;
+
+        if let Some(class) = self.handles[handle].as_mut() {
+            class.fields = fields;
+            class.super_class = super_class;
+        }
+        return Ok(handle);
+    }
+
+    /// Skip the field values of an object, from its outermost super class
+    /// down to the class itself.
+    fn skip_object_data(&mut self, class: usize) -> Result<(), Box<dyn Error>> {
+        let mut hierarchy = vec![class];
+        while let Some(super_class) = self.class(*hierarchy.last().unwrap())?.super_class {
+            if hierarchy.contains(&super_class) {
+                return Err(Box::new(invalid_data("Cyclic class hierarchy")));
+            }
+            hierarchy.push(super_class);
+        }
+
+        for class in hierarchy.into_iter().rev() {
+            let JavaClass { flags, fields, .. } = self.class(class)?.clone();
+            for type_code in fields {
+                match primitive_size(type_code) {
+                    Some(size) => self.reader.skip(size)?,
+                    None => {
+                        self.skip_content()?;
+                    }
+                }
+            }
+            if flags & SC_WRITE_METHOD != 0 {
+                self.skip_annotations()?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Skip content elements up to and including the end of block marker.
+    fn skip_annotations(&mut self) -> Result<(), Box<dyn Error>> {
+        loop {
+            if self.reader.read_bytes(1)?[0] == TC_ENDBLOCKDATA {
+                return Ok(());
+            }
+            self.reader.seek(self.reader.position - 1)?;
+            self.skip_content()?;
+        }
+    }
+}
+
+/// The size in bytes of a primitive field type code.
+fn primitive_size(type_code: u8) -> Option<usize> {
+    return match type_code {
+        b'B' | b'Z' => Some(1),
+        b'C' | b'S' => Some(2),
+        b'I' | b'F' => Some(4),
+        b'J' | b'D' => Some(8),
+        _ => None,
+    };
+}
+
+/// Report a Java keystore, listing every entry so auditors can see whether it
+/// holds keys or only trusted certificates.
+pub(crate) fn java_keystore_finding(file: &Path) -> Result<Finding, Box<dyn Error>> {
+    let keystore = JavaKeystore::from_file(file)?;
+    let now = unix_now();
+
+    let mut finding = if keystore.holds_keys() {
+        Finding::new(file, Severity::High, "Java keystore holding keys")
+    } else {
+        Finding::new(file, Severity::Low, "Java trust store")
+    };
+
+    finding.add_detail("format", format!("{:?}", keystore.format).to_uppercase());
+    finding.add_detail("version", keystore.version.to_string());
+
+    for entry in &keystore.entries {
+        let kind = match entry.kind {
+            KeystoreEntryKind::PrivateKey => "private key",
+            KeystoreEntryKind::TrustedCertificate => "trusted certificate",
+            KeystoreEntryKind::SecretKey => "secret key",
+        };
+        let mut description = format!("{}: {kind}", entry.alias);
+
+        /* The first certificate of a chain belongs to the key itself. */
+        if let Some(certificate) = entry.certificates.first() {
+            let expiry = if certificate.is_expired(now) {
+                "expired"
+            } else {
+                "expires"
+            };
+            description.push_str(&format!(
+                ", {} ({expiry} {})",
+                certificate.subject,
+                format_timestamp(certificate.not_after)
+            ));
+        }
+        finding.add_detail("entry", description);
+    }
+
+    /* JKS protects keys with a single round of a SHA-1 based keystream. */
+    if keystore.format == KeystoreFormat::Jks
+        && keystore
+            .entries
+            .iter()
+            .any(|x| x.kind == KeystoreEntryKind::PrivateKey)
+    {
+        finding.add_detail(
+            "weakness",
+            String::from("Private keys are protected by the weak proprietary JKS algorithm"),
+        );
+    }
+    return Ok(finding);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_jks() {
+        let keystore =
+            JavaKeystore::from_file(Path::new("./tests/testing_files/java_keystore/mixed.jks"))
+                .unwrap();
+
+        assert_eq!(keystore.format, KeystoreFormat::Jks);
+        assert_eq!(keystore.version, 2);
+        assert_eq!(keystore.entries.len(), 2);
+
+        let server = keystore
+            .entries
+            .iter()
+            .find(|x| x.alias == "server")
+            .unwrap();
+        assert_eq!(server.kind, KeystoreEntryKind::PrivateKey);
+        assert_eq!(server.certificates.len(), 1);
+        assert_eq!(
+            server.certificates[0].subject,
+            "CN=server.example.com, O=Example Ltd, C=GB"
+        );
+
+        let ca = keystore
+            .entries
+            .iter()
+            .find(|x| x.alias == "oldca")
+            .unwrap();
+        assert_eq!(ca.kind, KeystoreEntryKind::TrustedCertificate);
+        assert_eq!(
+            format_timestamp(ca.certificates[0].not_after),
+            "2020-12-31 14:05:07 UTC"
+        );
+    }
+
+    #[test]
+    fn parse_jceks_with_secret_key() {
+        let keystore = JavaKeystore::from_file(Path::new(
+            "./tests/testing_files/java_keystore/secrets.jceks",
+        ))
+        .unwrap();
+
+        assert_eq!(keystore.format, KeystoreFormat::Jceks);
+        assert_eq!(
+            keystore
+                .entries
+                .iter()
+                .map(|x| (x.alias.as_str(), x.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("client", KeystoreEntryKind::PrivateKey),
+                ("dbkey", KeystoreEntryKind::SecretKey)
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_truncated() {
+        JavaKeystore::from_file(Path::new(
+            "./tests/testing_files/java_keystore/truncated.jks",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_not_a_keystore() {
+        JavaKeystore::from_file(Path::new("./tests/testing_files/java_keystore/server.cer"))
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn deeply_nested_secret_key() {
+        /* An Object[] holding an Object[] holding an Object[], and so on. */
+        let mut data = vec![0xAC, 0xED, 0x00, 0x05, TC_ARRAY, TC_CLASSDESC];
+        data.extend_from_slice(&19u16.to_be_bytes());
+        data.extend_from_slice(b"[Ljava.lang.Object;");
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0x02, 0x00, 0x00, TC_ENDBLOCKDATA, TC_NULL]);
+        data.extend_from_slice(&1u32.to_be_bytes());
+        for _ in 0..100_000 {
+            data.extend_from_slice(&[TC_ARRAY, TC_REFERENCE]);
+            data.extend_from_slice(&BASE_HANDLE.to_be_bytes());
+            data.extend_from_slice(&1u32.to_be_bytes());
+        }
+        data.push(TC_NULL);
+
+        JavaStreamSkipper::new(&mut ByteReader::new(&data))
+            .skip_stream()
+            .unwrap();
+    }
+
+    #[test]
+    fn key_store_finding() {
+        let finding =
+            java_keystore_finding(Path::new("./tests/testing_files/java_keystore/mixed.jks"))
+                .unwrap();
+
+        assert_eq!(finding.severity, Severity::High);
+        assert_eq!(finding.detail("format"), Some(&String::from("JKS")));
+        assert!(finding.detail("weakness").is_some());
+        assert!(finding.details.iter().any(|(name, value)| name == "entry"
+            && value.starts_with("oldca: trusted certificate, CN=Old Root CA")
+            && value.contains("expired")));
+    }
+
+    #[test]
+    fn trust_store_finding() {
+        let finding = java_keystore_finding(Path::new(
+            "./tests/testing_files/java_keystore/truststore.jks",
+        ))
+        .unwrap();
+
+        assert_eq!(finding.severity, Severity::Low);
+        assert_eq!(finding.detail("weakness"), None);
+    }
+}
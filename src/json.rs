@@ -0,0 +1,312 @@
+/*
+ * A small JSON parser for the configuration and keystore files that wallets
+ * and other applications write. Numbers are kept as f64, which is enough for
+ * the counts and cost parameters that are read from them.
+ */
+
+use crate::byte_reader::invalid_data;
+use std::error::Error;
+
+/// Nesting deeper than this is treated as malformed rather than risking the
+/// stack on hostile input.
+const MAX_DEPTH: usize = 128;
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Members are kept in file order, duplicates included.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub(crate) fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut parser = JsonParser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err(Box::new(invalid_data("Trailing data after JSON value")));
+        }
+        return Ok(value);
+    }
+
+    /// Look up the first member of an object with the given name.
+    pub(crate) fn get(&self, name: &str) -> Option<&JsonValue> {
+        let JsonValue::Object(members) = self else {
+            return None;
+        };
+        return members.iter().find(|(x, _)| x == name).map(|(_, x)| x);
+    }
+
+    /// Follow a path of member names through nested objects.
+    pub(crate) fn path(&self, names: &[&str]) -> Option<&JsonValue> {
+        return names.iter().try_fold(self, |value, name| value.get(name));
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        return match self {
+            JsonValue::String(x) => Some(x),
+            _ => None,
+        };
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        return match self {
+            JsonValue::Number(x) => Some(*x),
+            _ => None,
+        };
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        return match self {
+            JsonValue::Bool(x) => Some(*x),
+            _ => None,
+        };
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        return match self {
+            JsonValue::Array(x) => Some(x),
+            _ => None,
+        };
+    }
+}
+
+struct JsonParser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.position)
+            .is_some_and(|x| matches!(x, b' ' | b'\t' | b'\r' | b'\n'))
+        {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &[u8]) -> Result<(), Box<dyn Error>> {
+        if !self.text[self.position..].starts_with(literal) {
+            return Err(Box::new(invalid_data(&format!(
+                "Expected {} at offset {}",
+                String::from_utf8_lossy(literal),
+                self.position
+            ))));
+        }
+        self.position += literal.len();
+        return Ok(());
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue, Box<dyn Error>> {
+        if depth > MAX_DEPTH {
+            return Err(Box::new(invalid_data("JSON nested too deeply")));
+        }
+        self.skip_whitespace();
+
+        return match self.text.get(self.position) {
+            Some(b'{') => self.parse_object(depth),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some(b't') => self.expect(b"true").map(|_| JsonValue::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| JsonValue::Bool(false)),
+            Some(b'n') => self.expect(b"null").map(|_| JsonValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(Box::new(invalid_data(&format!(
+                "Unexpected JSON at offset {}",
+                self.position
+            )))),
+        };
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<JsonValue, Box<dyn Error>> {
+        let mut members = Vec::new();
+        self.expect(b"{")?;
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b":")?;
+            members.push((name, self.parse_value(depth + 1)?));
+
+            self.skip_whitespace();
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err(Box::new(invalid_data("Unterminated JSON object"))),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<JsonValue, Box<dyn Error>> {
+        let mut items = Vec::new();
+        self.expect(b"[")?;
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(Box::new(invalid_data("Unterminated JSON array"))),
+            }
+        }
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, Box<dyn Error>> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .ok_or_else(|| invalid_data("Truncated JSON escape"))?;
+        self.position += 4;
+        return Ok(u32::from_str_radix(std::str::from_utf8(digits)?, 16)?);
+    }
+
+    fn parse_string(&mut self) -> Result<String, Box<dyn Error>> {
+        self.expect(b"\"")?;
+        let mut bytes = Vec::new();
+
+        loop {
+            let Some(&byte) = self.text.get(self.position) else {
+                return Err(Box::new(invalid_data("Unterminated JSON string")));
+            };
+            self.position += 1;
+
+            match byte {
+                b'"' => return Ok(String::from_utf8(bytes)?),
+                b'\\' => {
+                    let Some(&escape) = self.text.get(self.position) else {
+                        return Err(Box::new(invalid_data("Unterminated JSON string")));
+                    };
+                    self.position += 1;
+
+                    let character = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.parse_hex_escape()?;
+                            /* Characters outside the BMP are written as a surrogate pair. */
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.text[self.position..].starts_with(b"\\u")
+                            {
+                                self.position += 2;
+                                let low = self.parse_hex_escape()?;
+                                code = 0x10000
+                                    + ((code - 0xD800) << 10)
+                                    + (low.wrapping_sub(0xDC00) & 0x3FF);
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(Box::new(invalid_data("Invalid JSON escape"))),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, Box<dyn Error>> {
+        let start = self.position;
+        while self
+            .text
+            .get(self.position)
+            .is_some_and(|x| matches!(x, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.position += 1;
+        }
+        let number = std::str::from_utf8(&self.text[start..self.position])?;
+        return Ok(JsonValue::Number(number.parse()?));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nested() {
+        let value = JsonValue::parse(
+            r#" {"version": 3, "crypto": {"kdf": "scrypt", "kdfparams": {"n": 262144}},
+                "tags": [true, false, null, -1.5e2], "empty": {}} "#,
+        )
+        .unwrap();
+
+        assert_eq!(value.get("version"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(
+            value
+                .path(&["crypto", "kdfparams", "n"])
+                .and_then(|x| x.as_f64()),
+            Some(262144.0)
+        );
+        assert_eq!(
+            value.get("tags"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Bool(true),
+                JsonValue::Bool(false),
+                JsonValue::Null,
+                JsonValue::Number(-150.0),
+            ]))
+        );
+        assert_eq!(value.get("empty"), Some(&JsonValue::Object(vec![])));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn parse_escapes() {
+        assert_eq!(
+            JsonValue::parse(r#""a\"b\\c\n\u00e9\ud83d\ude00""#).unwrap(),
+            JsonValue::String(String::from("a\"b\\c\né😀"))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_unterminated() {
+        JsonValue::parse(r#"{"seed_version": 18"#).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_trailing_data() {
+        JsonValue::parse("{} {}").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_too_deep() {
+        JsonValue::parse(&"[".repeat(1000)).unwrap();
+    }
+}
@@ -0,0 +1,390 @@
+/*
+ * Inspect the unencrypted outer header of KeePass KDBX databases to judge how
+ * well the vault is protected. Nothing is ever decrypted.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::findings::{Finding, Severity};
+use std::error::Error;
+use std::path::Path;
+
+/// AES-KDF vaults with fewer transform rounds than this are quick to attack.
+const MIN_AES_KDF_ROUNDS: u64 = 100_000;
+
+/// Argon2 vaults using less memory than this, in bytes, are quick to attack.
+const MIN_ARGON2_MEMORY: u64 = 64 * 1024 * 1024;
+
+const CIPHER_AES256: [u8; 16] = [
+    0x31, 0xC1, 0xF2, 0xE6, 0xBF, 0x71, 0x43, 0x50, 0xBE, 0x58, 0x05, 0x21, 0x6A, 0xFC, 0x5A, 0xFF,
+];
+const CIPHER_CHACHA20: [u8; 16] = [
+    0xD6, 0x03, 0x8A, 0x2B, 0x8B, 0x6F, 0x4C, 0xB5, 0xA5, 0x24, 0x33, 0x9A, 0x31, 0xDB, 0xB5, 0x9A,
+];
+const CIPHER_TWOFISH: [u8; 16] = [
+    0xAD, 0x68, 0xF2, 0x9F, 0x57, 0x6F, 0x4B, 0xB9, 0xA3, 0x6A, 0xD4, 0x7A, 0xF9, 0x65, 0x34, 0x6C,
+];
+const KDF_AES: [u8; 16] = [
+    0xC9, 0xD9, 0xF3, 0x9A, 0x62, 0x8A, 0x44, 0x60, 0xBF, 0x74, 0x0D, 0x08, 0xC1, 0x8A, 0x4F, 0xEA,
+];
+/* KDBX 4 writers use a second identifier for the same AES-KDF. */
+const KDF_AES_ALT: [u8; 16] = [
+    0x7C, 0x02, 0xBB, 0x82, 0x79, 0xA7, 0x4A, 0xC0, 0x92, 0x7D, 0x11, 0x4A, 0x00, 0x64, 0x82, 0x38,
+];
+const KDF_ARGON2D: [u8; 16] = [
+    0xEF, 0x63, 0x6D, 0xDF, 0x8C, 0x29, 0x44, 0x4B, 0x91, 0xF7, 0xA9, 0xA4, 0x03, 0xE3, 0x0A, 0x0C,
+];
+const KDF_ARGON2ID: [u8; 16] = [
+    0x9E, 0x29, 0x8B, 0x19, 0x56, 0xDB, 0x47, 0x73, 0xB2, 0x3D, 0xFC, 0x3E, 0xC6, 0xF0, 0xA1, 0xE6,
+];
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum KdbxCipher {
+    Aes256,
+    ChaCha20,
+    Twofish,
+    Unknown(String),
+}
+
+impl KdbxCipher {
+    fn from_uuid(uuid: &[u8]) -> Self {
+        return if uuid == CIPHER_AES256 {
+            KdbxCipher::Aes256
+        } else if uuid == CIPHER_CHACHA20 {
+            KdbxCipher::ChaCha20
+        } else if uuid == CIPHER_TWOFISH {
+            KdbxCipher::Twofish
+        } else {
+            KdbxCipher::Unknown(format_uuid(uuid))
+        };
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum KdbxKdf {
+    AesKdf {
+        rounds: u64,
+    },
+    Argon2d {
+        memory: u64,
+        iterations: u64,
+        parallelism: u32,
+    },
+    Argon2id {
+        memory: u64,
+        iterations: u64,
+        parallelism: u32,
+    },
+    Unknown(String),
+}
+
+/// The settings that control how a KDBX database is encrypted.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct KdbxHeader {
+    pub(crate) major_version: u16,
+    pub(crate) minor_version: u16,
+    pub(crate) cipher: KdbxCipher,
+    pub(crate) kdf: KdbxKdf,
+}
+
+impl KdbxHeader {
+    /// Parse the outer header of a KDBX 3.1 or 4.x database.
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = ByteReader::new(data);
+
+        if reader.read_u32_le()? != 0x9AA2D903 || reader.read_u32_le()? != 0xB54BFB67 {
+            return Err(Box::new(invalid_data("Not a KDBX database")));
+        }
+        let minor_version = reader.read_u16_le()?;
+        let major_version = reader.read_u16_le()?;
+
+        if !(3..=4).contains(&major_version) {
+            return Err(Box::new(invalid_data(&format!(
+                "Unsupported KDBX version {major_version}.{minor_version}"
+            ))));
+        }
+
+        let mut cipher = None;
+        let mut kdf = None;
+        let mut transform_rounds = None;
+
+        /* Read type-length-value fields until the end of header marker. */
+        loop {
+            let field_id = reader.read_u8()?;
+            let field_len = if major_version >= 4 {
+                reader.read_u32_le()? as usize
+            } else {
+                reader.read_u16_le()? as usize
+            };
+            let field_data = reader.read_bytes(field_len)?;
+
+            match field_id {
+                0 => break,
+                2 => cipher = Some(KdbxCipher::from_uuid(field_data)),
+                6 if field_len == 8 => {
+                    transform_rounds = Some(ByteReader::new(field_data).read_u64_le()?)
+                }
+                11 => kdf = Some(parse_kdf_parameters(field_data)?),
+                _ => {}
+            }
+        }
+
+        /* Version 3 databases always use AES-KDF with a separate rounds field. */
+        if major_version == 3 {
+            kdf = transform_rounds.map(|rounds| KdbxKdf::AesKdf { rounds });
+        }
+
+        return Ok(KdbxHeader {
+            major_version,
+            minor_version,
+            cipher: cipher.ok_or_else(|| invalid_data("KDBX header has no cipher"))?,
+            kdf: kdf.ok_or_else(|| invalid_data("KDBX header has no key derivation"))?,
+        });
+    }
+
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return KdbxHeader::from_bytes(&std::fs::read(file)?);
+    }
+
+    /// Describe every part of the configuration that makes the database easier
+    /// to brute force.
+    pub(crate) fn weaknesses(&self) -> Vec<String> {
+        let mut weaknesses = Vec::new();
+
+        match &self.kdf {
+            KdbxKdf::AesKdf { rounds } if *rounds < MIN_AES_KDF_ROUNDS => {
+                weaknesses.push(format!("Only {rounds} AES-KDF rounds"));
+            }
+            KdbxKdf::Argon2d { memory, .. } | KdbxKdf::Argon2id { memory, .. }
+                if *memory < MIN_ARGON2_MEMORY =>
+            {
+                weaknesses.push(format!("Only {} KiB of Argon2 memory", memory / 1024));
+            }
+            _ => {}
+        }
+        return weaknesses;
+    }
+}
+
+/// Decode the variant dictionary holding the KDF settings of a KDBX 4 file.
+fn parse_kdf_parameters(data: &[u8]) -> Result<KdbxKdf, Box<dyn Error>> {
+    let mut reader = ByteReader::new(data);
+    let mut uuid = Vec::new();
+    let mut rounds = 0;
+    let mut memory = 0;
+    let mut iterations = 0;
+    let mut parallelism = 0;
+
+    if reader.read_u16_le()? >> 8 != 1 {
+        return Err(Box::new(invalid_data("Unsupported KDF parameter version")));
+    }
+
+    loop {
+        let value_type = reader.read_u8()?;
+        if value_type == 0 {
+            break;
+        }
+        let name_len = reader.read_u32_le()? as usize;
+        let name = reader.read_bytes(name_len)?;
+        let value_len = reader.read_u32_le()? as usize;
+        let value = reader.read_bytes(value_len)?;
+        let mut value_reader = ByteReader::new(value);
+
+        match (name, value_type) {
+            (b"$UUID", 0x42) => uuid = value.to_vec(),
+            (b"R", 0x05) => rounds = value_reader.read_u64_le()?,
+            (b"M", 0x05) => memory = value_reader.read_u64_le()?,
+            (b"I", 0x05) => iterations = value_reader.read_u64_le()?,
+            (b"P", 0x04) => parallelism = value_reader.read_u32_le()?,
+            _ => {}
+        }
+    }
+
+    return Ok(if uuid == KDF_AES || uuid == KDF_AES_ALT {
+        KdbxKdf::AesKdf { rounds }
+    } else if uuid == KDF_ARGON2D {
+        KdbxKdf::Argon2d {
+            memory,
+            iterations,
+            parallelism,
+        }
+    } else if uuid == KDF_ARGON2ID {
+        KdbxKdf::Argon2id {
+            memory,
+            iterations,
+            parallelism,
+        }
+    } else {
+        KdbxKdf::Unknown(format_uuid(&uuid))
+    });
+}
+
+/// Write sixteen bytes in the usual hyphenated UUID form.
+fn format_uuid(uuid: &[u8]) -> String {
+    let hex: String = uuid.iter().map(|x| format!("{:02x}", x)).collect();
+
+    if hex.len() != 32 {
+        return hex;
+    }
+    return format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    );
+}
+
+/// Report a KeePass database, raising the severity when its key derivation
+/// settings are weak.
+pub(crate) fn kdbx_finding(file: &Path) -> Result<Finding, Box<dyn Error>> {
+    let header = KdbxHeader::from_file(file)?;
+    let weaknesses = header.weaknesses();
+
+    let mut finding = if weaknesses.is_empty() {
+        Finding::new(file, Severity::Medium, "KeePass database")
+    } else {
+        Finding::new(file, Severity::High, "Weakly protected KeePass database")
+    };
+
+    finding.add_detail(
+        "version",
+        format!("{}.{}", header.major_version, header.minor_version),
+    );
+    finding.add_detail("cipher", format!("{:?}", header.cipher));
+    finding.add_detail("kdf", format!("{:?}", header.kdf));
+    for weakness in weaknesses {
+        finding.add_detail("weakness", weakness);
+    }
+    return Ok(finding);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_kdbx_3_1() {
+        assert_eq!(
+            KdbxHeader::from_file(Path::new("./tests/testing_files/kdbx/aes_kdf_3_1.kdbx"))
+                .unwrap(),
+            KdbxHeader {
+                major_version: 3,
+                minor_version: 1,
+                cipher: KdbxCipher::Aes256,
+                kdf: KdbxKdf::AesKdf { rounds: 73170728 },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_kdbx_4_0() {
+        assert_eq!(
+            KdbxHeader::from_file(Path::new("./tests/testing_files/kdbx/argon2d_4_0.kdbx"))
+                .unwrap(),
+            KdbxHeader {
+                major_version: 4,
+                minor_version: 0,
+                cipher: KdbxCipher::Aes256,
+                kdf: KdbxKdf::Argon2d {
+                    memory: 64 * 1024 * 1024,
+                    iterations: 59,
+                    parallelism: 2
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_kdbx_4_weak_argon2id() {
+        assert_eq!(
+            KdbxHeader::from_file(Path::new(
+                "./tests/testing_files/kdbx/weak_argon2id_4_1.kdbx"
+            ))
+            .unwrap(),
+            KdbxHeader {
+                major_version: 4,
+                minor_version: 1,
+                cipher: KdbxCipher::ChaCha20,
+                kdf: KdbxKdf::Argon2id {
+                    memory: 1024 * 1024,
+                    iterations: 2,
+                    parallelism: 1
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn weak_aes_kdf_rounds() {
+        let header = KdbxHeader::from_file(Path::new(
+            "./tests/testing_files/kdbx/weak_aes_kdf_3_1.kdbx",
+        ))
+        .unwrap();
+        assert_eq!(
+            header.weaknesses(),
+            vec![String::from("Only 6000 AES-KDF rounds")]
+        );
+    }
+
+    #[test]
+    fn weak_argon2_memory() {
+        let header = KdbxHeader::from_file(Path::new(
+            "./tests/testing_files/kdbx/weak_argon2id_4_1.kdbx",
+        ))
+        .unwrap();
+        assert_eq!(
+            header.weaknesses(),
+            vec![String::from("Only 1024 KiB of Argon2 memory")]
+        );
+    }
+
+    #[test]
+    fn strong_argon2() {
+        let header =
+            KdbxHeader::from_file(Path::new("./tests/testing_files/kdbx/argon2d_4_0.kdbx"))
+                .unwrap();
+        assert!(header.weaknesses().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_truncated_header() {
+        KdbxHeader::from_file(Path::new("./tests/testing_files/kdbx/truncated.kdbx")).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_not_kdbx() {
+        KdbxHeader::from_file(Path::new(
+            "./tests/testing_files/read_file_header/text_file.bib",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn finding_severity() {
+        assert_eq!(
+            kdbx_finding(Path::new("./tests/testing_files/kdbx/argon2d_4_0.kdbx"))
+                .unwrap()
+                .severity,
+            Severity::Medium
+        );
+        assert_eq!(
+            kdbx_finding(Path::new(
+                "./tests/testing_files/kdbx/weak_aes_kdf_3_1.kdbx"
+            ))
+            .unwrap()
+            .severity,
+            Severity::High
+        );
+    }
+
+    #[test]
+    fn uuid_format() {
+        assert_eq!(
+            format_uuid(&CIPHER_CHACHA20),
+            "d6038a2b-8b6f-4cb5-a524-339a31dbb59a"
+        );
+    }
+}
@@ -1,3 +1,32 @@
+mod benford;
+mod bip39;
+mod browsers;
+mod byte_reader;
+mod byte_stats;
+mod crypto;
+mod deployment;
+mod der;
+mod dpapi;
+mod encoded_payloads;
+mod encoding;
+mod entropy;
+mod extension_mismatch;
 mod file_signatures;
+mod findings;
 mod frequency_analysis;
+mod java_keystore;
+mod json;
+mod kdbx;
 mod os_interactions;
+mod password_strength;
+mod private_keys;
+mod putty_key;
+mod registry;
+mod secret_classifier;
+mod sqlite;
+mod suppression;
+mod telegram;
+mod text_profile;
+mod wallets;
+mod x509;
+mod xml;
@@ -4,19 +4,22 @@
 
 use std::error::Error;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 /// Using the path to a file, extract the at least the first 64 bytes of its data
-fn read_file_header(file: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
-    let num_bytes = 64;
+pub(crate) fn read_file_header(file: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    return read_file_bytes(file, 64);
+}
 
+/// Read up to a specific number of bytes from the start of a file.
+pub(crate) fn read_file_bytes(file: &Path, num_bytes: usize) -> Result<Vec<u8>, Box<dyn Error>> {
     /* Open the file and move the pointer to the position to read from. */
     let mut f_pntr = std::fs::File::open(file)?;
 
     /* Determine if the file is big enough to fill the whole buffer. */
-    let file_size: usize = f_pntr.metadata().unwrap().len() as usize;
+    let file_size: usize = f_pntr.metadata()?.len() as usize;
     let buf_size: usize = std::cmp::min(file_size, num_bytes);
     let mut buffer = vec![0; buf_size];
 
@@ -84,9 +87,24 @@ fn file_search(
     return Ok(found_paths);
 }
 
+/// Return the paths of every file recursively in a specific directory.
+pub(crate) fn all_files(directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    /* Ensure the supplied path is valid and accessible. */
+    if !std::fs::metadata(directory)?.is_dir() {
+        return Err(std::io::ErrorKind::NotADirectory.into());
+    }
+
+    return Ok(WalkDir::new(directory)
+        .into_iter()
+        .filter_map(|x| x.ok())
+        .filter(|x| x.path().is_file())
+        .map(|x| x.path().to_path_buf())
+        .collect());
+}
+
 /// Determine the count of the ascii characters within a text file and
 /// return a vector with the counts of each character.
-fn file_char_cnt(text_file: &Path) -> Result<Vec<usize>, Box<dyn Error>> {
+pub(crate) fn file_char_cnt(text_file: &Path) -> Result<Vec<usize>, Box<dyn Error>> {
     let mut buffer = String::new();
     let mut seen_chars = vec![0; 128];
     // stream did not contain valid UTF-8
@@ -108,6 +126,62 @@ fn file_char_cnt(text_file: &Path) -> Result<Vec<usize>, Box<dyn Error>> {
     return Ok(seen_chars);
 }
 
+/// Size of the pieces a large file is sampled in.
+const SAMPLE_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Where each chunk of a sampled file starts and how long it is. The chunks
+/// are spread evenly and never overlap. A sample of more than half the file
+/// gains little over reading all of it, so the whole file is read instead.
+fn sample_chunks(file_size: u64, sample_size: Option<u64>) -> Vec<(u64, u64)> {
+    return match sample_size {
+        Some(sample) if sample.saturating_mul(2) <= file_size => {
+            let sample = sample.max(1);
+            let num_chunks = sample.div_ceil(SAMPLE_CHUNK_SIZE);
+            let chunk_size = sample.div_ceil(num_chunks);
+            let stride = file_size / num_chunks;
+            (0..num_chunks)
+                .map(|x| {
+                    (
+                        x * stride,
+                        std::cmp::min(chunk_size, sample.saturating_sub(x * chunk_size)),
+                    )
+                })
+                .filter(|(_, length)| *length > 0)
+                .collect()
+        }
+        _ => vec![(0, u64::MAX)],
+    };
+}
+
+/// Count the occurance of every byte value in a file in a single pass. When
+/// a sample size is given and the file is more than twice as large, only
+/// that many bytes are counted, read in chunks spread evenly from the start
+/// to the end.
+pub(crate) fn file_byte_cnt(
+    file: &Path,
+    sample_size: Option<u64>,
+) -> Result<Vec<usize>, Box<dyn Error>> {
+    let mut seen_bytes = vec![0; 256];
+    let mut f_pntr = File::open(file)?;
+    let file_size = f_pntr.metadata()?.len();
+
+    let mut buffer = vec![0; SAMPLE_CHUNK_SIZE as usize];
+    for (start, length) in sample_chunks(file_size, sample_size) {
+        f_pntr.seek(SeekFrom::Start(start))?;
+        let mut reader = BufReader::new(&f_pntr).take(length);
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            for byte in &buffer[..read] {
+                seen_bytes[*byte as usize] += 1;
+            }
+        }
+    }
+    return Ok(seen_bytes);
+}
+
 /// Extract all the whitespace seperated strings from a text file.
 fn file_str_extract(text_file: &Path) -> Result<Vec<String>, Box<dyn Error>> {
     return Ok(Vec::new());
@@ -786,6 +860,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn all_files_dir_5_val() {
+        assert_eq!(
+            HashSet::from_iter(
+                all_files(&Path::new("./tests/testing_files/file_searches/5"))
+                    .unwrap()
+                    .iter()
+                    .cloned()
+            ),
+            HashSet::from([
+                PathBuf::from("./tests/testing_files/file_searches/5/0.bin"),
+                PathBuf::from("./tests/testing_files/file_searches/5/1.bin"),
+                PathBuf::from("./tests/testing_files/file_searches/5/2.bin"),
+                PathBuf::from("./tests/testing_files/file_searches/5/3.bin"),
+                PathBuf::from("./tests/testing_files/file_searches/5/4.bin"),
+            ])
+        );
+    }
+
+    #[test]
+    fn all_files_cnt() {
+        assert_eq!(
+            all_files(&Path::new("./tests/testing_files/file_searches/"))
+                .unwrap()
+                .len(),
+            40
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn all_files_path_is_file() {
+        all_files(&Path::new("./tests/testing_files/file_searches/5/0.bin")).unwrap();
+    }
+
     #[test]
     #[should_panic]
     fn char_cnt_file_not_exist() {
@@ -822,6 +931,77 @@ mod tests {
         let _ = file_char_cnt(&Path::new(r"C:\Windows\System32\Config\SAM")).unwrap();
     }
 
+    #[test]
+    fn byte_cnt_binary_file() {
+        let counts =
+            file_byte_cnt(&Path::new("./tests/testing_files/entropy/photos.zip"), None).unwrap();
+        assert_eq!(counts.len(), 256);
+        assert_eq!(counts.iter().sum::<usize>(), 65536);
+    }
+
+    #[test]
+    fn byte_cnt_sampled() {
+        let counts = file_byte_cnt(
+            &Path::new("./tests/testing_files/entropy/backup.hc"),
+            Some(100_000),
+        )
+        .unwrap();
+        assert_eq!(counts.iter().sum::<usize>(), 65536);
+
+        let counts = file_byte_cnt(
+            &Path::new("./tests/testing_files/entropy/backup.hc"),
+            Some(1000),
+        )
+        .unwrap();
+        assert_eq!(counts.iter().sum::<usize>(), 1000);
+    }
+
+    #[test]
+    fn sample_chunks_do_not_overlap() {
+        const KIB: u64 = 1024;
+        for (file_size, sample) in [
+            (130 * KIB, 65 * KIB),
+            (130 * KIB, 64 * KIB),
+            (1000 * KIB, 129 * KIB),
+            (1000 * KIB + 7, 300 * KIB + 3),
+            (10_000 * KIB, 1),
+        ] {
+            let chunks = sample_chunks(file_size, Some(sample));
+            assert_eq!(chunks.iter().map(|x| x.1).sum::<u64>(), sample);
+            assert!(chunks.iter().all(|x| x.1 <= SAMPLE_CHUNK_SIZE));
+            assert!(chunks.windows(2).all(|x| x[0].0 + x[0].1 <= x[1].0));
+            assert!(chunks.iter().all(|x| x.0 + x.1 <= file_size));
+        }
+
+        /* Most of the file would be read anyway, so all of it is, once. */
+        assert_eq!(
+            sample_chunks(130 * KIB, Some(129 * KIB)),
+            vec![(0, u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn byte_cnt_empty_file() {
+        assert_eq!(
+            file_byte_cnt(
+                &Path::new("./tests/testing_files/file_char_freq/empty_file.txt"),
+                Some(10)
+            )
+            .unwrap(),
+            vec![0; 256]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn byte_cnt_file_not_exist() {
+        let _ = file_byte_cnt(
+            &Path::new("./tests/testing_files/file_char_freq/DOES_NOT_EXIST"),
+            None,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn char_cnt_empty_file() {
         assert_eq!(
@@ -0,0 +1,975 @@
+/*
+ * Estimate how many guesses it would take to find a password, in the style
+ * of zxcvbn, by splitting it into dictionary words, keyboard walks,
+ * sequences, repeats and dates. Passwords people chose stand out from random
+ * ones and from placeholders, and the weakest can be dealt with first.
+ */
+
+use crate::findings::{Finding, Severity};
+use crate::suppression::is_placeholder;
+use crate::x509::{civil_from_days, unix_now};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Commonly used passwords, most common first.
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+/// Common English words, most frequent in general text first. The order is
+/// approximate, so a word's rank is only a rough guess count.
+const ENGLISH_WORDS: &str = include_str!("english_words.txt");
+
+/// Setting names that hold passwords.
+const PASSWORD_KEYWORDS: [&str; 4] = ["password", "passwd", "passphrase", "pwd"];
+
+/// Words in a setting name that say it is about a password rather than
+/// holding one.
+const NOT_PASSWORD_WORDS: [&str; 8] = [
+    "hash", "file", "path", "length", "policy", "prompt", "expire", "reset",
+];
+
+/// Longer values are keys or hashes rather than passwords.
+const MAX_PASSWORD_LENGTH: usize = 64;
+
+/// Words, walks, sequences and repeats shorter than this are left to brute
+/// force.
+const MIN_MATCH_LENGTH: usize = 3;
+
+/// No word in the dictionaries is longer than this.
+const MAX_WORD_LENGTH: usize = 16;
+
+/// Spellings of a word tried when undoing l33t substitutions.
+const MAX_L33T_VARIANTS: usize = 256;
+
+/// Guesses for each character no pattern explains.
+const BRUTE_FORCE_CARDINALITY: f64 = 10.0;
+
+/// Even a year close to the reference year takes this many guesses.
+const MIN_YEAR_SPACE: i32 = 20;
+
+/// Characters used between the parts of a date.
+const DATE_SEPARATORS: &str = "/-._ ";
+
+/// A value with at least this fraction of its characters explained by
+/// patterns was chosen by a person.
+const MIN_HUMAN_COVERAGE: f64 = 0.5;
+
+/// Characters people swap for letters, with the letters they stand for.
+const L33T_TABLE: [(char, &str); 10] = [
+    ('4', "a"),
+    ('@', "a"),
+    ('3', "e"),
+    ('1', "il"),
+    ('!', "i"),
+    ('0', "o"),
+    ('$', "s"),
+    ('5', "s"),
+    ('7', "t"),
+    ('+', "t"),
+];
+
+/// The rows of a US QWERTY keyboard, each with how far it is shifted to the
+/// right of the top row, in keys.
+const KEYBOARD_ROWS: [(&str, f64); 4] = [
+    ("1234567890-=", 0.0),
+    ("qwertyuiop[]\\", 0.5),
+    ("asdfghjkl;'", 0.75),
+    ("zxcvbnm,./", 1.25),
+];
+
+/// Keys a walk can start from, counting shifted keys, and the average number
+/// of neighbours a key has, as zxcvbn counts them for QWERTY.
+const KEYBOARD_STARTS: f64 = 94.0;
+const KEYBOARD_DEGREE: f64 = 4.6;
+
+/// The kinds of pattern a password is split into.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum Pattern {
+    /// A common password or English word, perhaps with letters swapped for
+    /// digits and symbols.
+    Dictionary {
+        l33t: bool,
+    },
+    /// Keys next to each other on the keyboard, like qwerty or 1qaz.
+    KeyboardWalk,
+    /// Characters counting up or down, like abc or 987.
+    Sequence,
+    /// One character over and over.
+    Repeat,
+    Year,
+    Date,
+    /// Characters no pattern explains.
+    BruteForce,
+}
+
+impl Pattern {
+    pub(crate) fn description(&self) -> &'static str {
+        return match self {
+            Pattern::Dictionary { l33t: false } => "dictionary word",
+            Pattern::Dictionary { l33t: true } => "l33t dictionary word",
+            Pattern::KeyboardWalk => "keyboard walk",
+            Pattern::Sequence => "sequence",
+            Pattern::Repeat => "repeat",
+            Pattern::Year => "year",
+            Pattern::Date => "date",
+            Pattern::BruteForce => "brute force",
+        };
+    }
+}
+
+/// A pattern covering the characters from `start` up to `end` of a password,
+/// with the log10 of the guesses needed to find that part.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PatternMatch {
+    pub(crate) pattern: Pattern,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) log_guesses: f64,
+}
+
+impl PatternMatch {
+    fn new(pattern: Pattern, start: usize, end: usize, guesses: f64) -> Self {
+        return PatternMatch {
+            pattern,
+            start,
+            end,
+            log_guesses: guesses.max(1.0).log10(),
+        };
+    }
+}
+
+fn ranks(list: &'static str) -> HashMap<&'static str, usize> {
+    return list
+        .split_whitespace()
+        .enumerate()
+        .map(|(rank, word)| (word, rank + 1))
+        .collect();
+}
+
+fn password_ranks() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    return RANKS.get_or_init(|| ranks(COMMON_PASSWORDS));
+}
+
+fn english_ranks() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    return RANKS.get_or_init(|| ranks(ENGLISH_WORDS));
+}
+
+/// How many guesses into a dictionary attack a lower case word is found.
+fn dictionary_rank(word: &str) -> Option<f64> {
+    if let Some(rank) = password_ranks().get(word) {
+        return Some(*rank as f64);
+    }
+    return english_ranks().get(word).map(|rank| *rank as f64);
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    return (1..=k).fold(1.0, |total, x| total * (n + 1 - x) as f64 / x as f64);
+}
+
+/// The ways the letters of a word could have been capitalised, counting
+/// only a capital first or last letter or all capitals as one more guess.
+fn uppercase_variations(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|x| x.is_uppercase()).count();
+    let lower = word.iter().filter(|x| x.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    let edge_upper = upper == 1 && (word[0].is_uppercase() || word[word.len() - 1].is_uppercase());
+    if lower == 0 || edge_upper {
+        return 2.0;
+    }
+    return (1..=upper.min(lower))
+        .map(|x| binomial(upper + lower, x))
+        .sum();
+}
+
+/// The lower case spellings a word could have before l33t substitution,
+/// with how many characters each substitutes. The spelling as written
+/// comes first.
+fn l33t_variants(word: &[char]) -> Vec<(String, usize)> {
+    let mut variants = vec![(String::new(), 0)];
+    for chr_t in word {
+        let lower = chr_t.to_lowercase().next().unwrap_or(*chr_t);
+        let letters = L33T_TABLE
+            .iter()
+            .find(|(x, _)| x == chr_t)
+            .map_or("", |(_, y)| y);
+        let mut next = Vec::new();
+        for (spelling, subs) in &variants {
+            next.push((format!("{spelling}{lower}"), *subs));
+            for letter in letters.chars() {
+                next.push((format!("{spelling}{letter}"), subs + 1));
+            }
+        }
+        next.truncate(MAX_L33T_VARIANTS);
+        variants = next;
+    }
+    return variants;
+}
+
+fn dictionary_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        let longest = (start + MAX_WORD_LENGTH).min(chars.len());
+        for end in start + MIN_MATCH_LENGTH..=longest {
+            let word = &chars[start..end];
+            let best = l33t_variants(word)
+                .iter()
+                .filter_map(|(spelling, subs)| {
+                    let rank = dictionary_rank(spelling)?;
+                    Some((rank * 2.0_f64.powi(*subs as i32), *subs > 0))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((guesses, l33t)) = best {
+                matches.push(PatternMatch::new(
+                    Pattern::Dictionary { l33t },
+                    start,
+                    end,
+                    guesses * uppercase_variations(word),
+                ));
+            }
+        }
+    }
+    return matches;
+}
+
+/// The row and horizontal position of a key.
+fn key_position(key: char) -> Option<(i32, f64)> {
+    return KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, (keys, offset))| {
+            let column = keys.chars().position(|x| x == key)?;
+            Some((row as i32, offset + column as f64))
+        });
+}
+
+/// The direction from one key to a neighbouring key, or None when they are
+/// not next to each other.
+fn key_direction(from: char, to: char) -> Option<(i32, i32)> {
+    let (from_row, from_x) = key_position(from)?;
+    let (to_row, to_x) = key_position(to)?;
+    let rows = to_row - from_row;
+    let across = to_x - from_x;
+    let neighbours = match rows {
+        0 => (across.abs() - 1.0).abs() < 1e-9,
+        -1 | 1 => across.abs() <= 1.0,
+        _ => false,
+    };
+    if !neighbours {
+        return None;
+    }
+    return Some((rows, across.partial_cmp(&0.0).map_or(0, |x| x as i32)));
+}
+
+/// The guesses to find a walk of some length and number of turns, summing
+/// over the shorter walks and fewer turns tried first.
+fn walk_guesses(length: usize, turns: usize) -> f64 {
+    let mut guesses = 0.0;
+    for i in 2..=length {
+        for j in 1..=turns.min(i - 1) {
+            guesses += binomial(i - 1, j - 1) * KEYBOARD_STARTS * KEYBOARD_DEGREE.powi(j as i32);
+        }
+    }
+    return guesses;
+}
+
+fn keyboard_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let lower: Vec<char> = chars.iter().map(|x| x.to_ascii_lowercase()).collect();
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start < lower.len() {
+        let mut end = start + 1;
+        let mut turns = 0;
+        let mut heading = None;
+        while end < lower.len() {
+            let Some(direction) = key_direction(lower[end - 1], lower[end]) else {
+                break;
+            };
+            if heading != Some(direction) {
+                turns += 1;
+                heading = Some(direction);
+            }
+            end += 1;
+        }
+        if end - start >= MIN_MATCH_LENGTH {
+            matches.push(PatternMatch::new(
+                Pattern::KeyboardWalk,
+                start,
+                end,
+                walk_guesses(end - start, turns) * uppercase_variations(&chars[start..end]),
+            ));
+        }
+        start = end;
+    }
+    return matches;
+}
+
+/// The guesses for each character of a repeat, by the kind of character.
+fn cardinality(chr_t: char) -> f64 {
+    return match chr_t {
+        '0'..='9' => 10.0,
+        'a'..='z' | 'A'..='Z' => 26.0,
+        _ => 33.0,
+    };
+}
+
+/// Maximal runs of characters where each differs from the one before by
+/// the same step, given as the start and end of each run.
+fn runs(chars: &[char], step_allowed: impl Fn(char, char) -> Option<i32>) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        let mut step = None;
+        while end < chars.len() {
+            let Some(next) = step_allowed(chars[end - 1], chars[end]) else {
+                break;
+            };
+            if step.is_some_and(|x| x != next) {
+                break;
+            }
+            step = Some(next);
+            end += 1;
+        }
+        if end - start >= MIN_MATCH_LENGTH {
+            found.push((start, end));
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    return found;
+}
+
+fn sequence_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let same_class = |a: char, b: char| {
+        (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+            || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+            || (a.is_ascii_digit() && b.is_ascii_digit())
+    };
+    let step = |a: char, b: char| {
+        let delta = b as i32 - a as i32;
+        return (same_class(a, b) && delta.abs() == 1).then_some(delta);
+    };
+    return runs(chars, step)
+        .into_iter()
+        .map(|(start, end)| {
+            let first = chars[start];
+            let base = match first {
+                'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9' => 4.0,
+                '0'..='9' => 10.0,
+                _ => 26.0,
+            };
+            let descending = match chars[start + 1] < first {
+                true => 2.0,
+                false => 1.0,
+            };
+            PatternMatch::new(
+                Pattern::Sequence,
+                start,
+                end,
+                base * descending * (end - start) as f64,
+            )
+        })
+        .collect();
+}
+
+fn repeat_matches(chars: &[char]) -> Vec<PatternMatch> {
+    return runs(chars, |a, b| (a == b).then_some(0))
+        .into_iter()
+        .map(|(start, end)| {
+            PatternMatch::new(
+                Pattern::Repeat,
+                start,
+                end,
+                cardinality(chars[start]) * (end - start) as f64,
+            )
+        })
+        .collect();
+}
+
+/// The current year by the system clock. Years near it are guessed first.
+fn reference_year() -> i32 {
+    return civil_from_days(unix_now().div_euclid(86400)).0 as i32;
+}
+
+fn year_space(year: i32) -> f64 {
+    return (year - reference_year()).abs().max(MIN_YEAR_SPACE) as f64;
+}
+
+/// Read a two or four digit year, guessing the century of a short one.
+fn parse_year(digits: &str) -> Option<i32> {
+    let year: i32 = digits.parse().ok()?;
+    return match digits.len() {
+        2 if year < 50 => Some(2000 + year),
+        2 => Some(1900 + year),
+        4 if (1900..=2099).contains(&year) => Some(year),
+        _ => None,
+    };
+}
+
+/// The year of a date in any common layout, with or without separators.
+fn parse_date(text: &str) -> Option<i32> {
+    let parts: Vec<&str> = match text.chars().find(|x| !x.is_ascii_digit()) {
+        Some(separator) if DATE_SEPARATORS.contains(separator) => text.split(separator).collect(),
+        Some(_) => return None,
+        /* Without separators try every place the fields could split. */
+        None => {
+            return (1..text.len())
+                .flat_map(|x| (x + 1..text.len()).map(move |y| (x, y)))
+                .find_map(|(x, y)| parse_date_parts(&[&text[..x], &text[x..y], &text[y..]]));
+        }
+    };
+    if parts.len() != 3 || parts.iter().any(|x| !x.chars().all(|y| y.is_ascii_digit())) {
+        return None;
+    }
+    return parse_date_parts(&parts);
+}
+
+/// The year of a date split into year, month and day, or day or month first
+/// with the year last.
+fn parse_date_parts(parts: &[&str]) -> Option<i32> {
+    let day_month = |a: &str, b: &str| {
+        let (Ok(a), Ok(b)) = (a.parse::<u32>(), b.parse::<u32>()) else {
+            return false;
+        };
+        return [a, b].iter().all(|x| (1..=31).contains(x)) && (a <= 12 || b <= 12);
+    };
+    let short = |x: &str| (1..=2).contains(&x.len());
+    if parts[0].len() == 4 && short(parts[1]) && short(parts[2]) {
+        return parse_year(parts[0]).filter(|_| day_month(parts[2], parts[1]));
+    }
+    if short(parts[0]) && short(parts[1]) && [2, 4].contains(&parts[2].len()) {
+        return parse_year(parts[2]).filter(|_| day_month(parts[0], parts[1]));
+    }
+    return None;
+}
+
+fn date_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        for end in start + 4..=(start + 10).min(chars.len()) {
+            let text: String = chars[start..end].iter().collect();
+            if end - start == 4
+                && text.chars().all(|x| x.is_ascii_digit())
+                && let Some(year) = parse_year(&text)
+            {
+                matches.push(PatternMatch::new(
+                    Pattern::Year,
+                    start,
+                    end,
+                    year_space(year),
+                ));
+            }
+            if end - start >= 6
+                && let Some(year) = parse_date(&text)
+            {
+                let separated = match text.chars().all(|x| x.is_ascii_digit()) {
+                    true => 1.0,
+                    false => 4.0,
+                };
+                matches.push(PatternMatch::new(
+                    Pattern::Date,
+                    start,
+                    end,
+                    year_space(year) * 365.0 * separated,
+                ));
+            }
+        }
+    }
+    return matches;
+}
+
+/// How hard a password is to guess, and the patterns that explain it best.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PasswordEstimate {
+    pub(crate) length: usize,
+    pub(crate) log_guesses: f64,
+    pub(crate) sequence: Vec<PatternMatch>,
+}
+
+impl PasswordEstimate {
+    /// Find the split of a password into patterns that takes the fewest
+    /// guesses. A split into k patterns costs the product of their guesses
+    /// times k!, for the orders the patterns could be tried in.
+    pub(crate) fn new(password: &str) -> Self {
+        let chars: Vec<char> = password.chars().collect();
+        let length = chars.len();
+
+        let mut matches = Vec::new();
+        matches.extend(dictionary_matches(&chars));
+        matches.extend(keyboard_matches(&chars));
+        matches.extend(sequence_matches(&chars));
+        matches.extend(repeat_matches(&chars));
+        matches.extend(date_matches(&chars));
+        for start in 0..length {
+            for end in start + 1..=length {
+                matches.push(PatternMatch::new(
+                    Pattern::BruteForce,
+                    start,
+                    end,
+                    BRUTE_FORCE_CARDINALITY.powi((end - start) as i32),
+                ));
+            }
+        }
+
+        /* best[k][j] is the cheapest cover of the first j characters with k
+        patterns, and the last pattern used. */
+        let mut best: Vec<Vec<Option<(f64, usize)>>> = vec![vec![None; length + 1]; length + 1];
+        best[0][0] = Some((0.0, usize::MAX));
+        for end in 1..=length {
+            for (index, found) in matches.iter().enumerate().filter(|(_, x)| x.end == end) {
+                for count in 1..=end {
+                    let Some((before, _)) = best[count - 1][found.start] else {
+                        continue;
+                    };
+                    let cost = before + found.log_guesses;
+                    if best[count][end].is_none_or(|(x, _)| cost < x) {
+                        best[count][end] = Some((cost, index));
+                    }
+                }
+            }
+        }
+
+        let factorial = |k: usize| (1..=k).map(|x| (x as f64).log10()).sum::<f64>();
+        let Some((mut count, log_guesses)) = (1..=length)
+            .filter_map(|k| best[k][length].map(|(cost, _)| (k, cost + factorial(k))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            return PasswordEstimate {
+                length,
+                log_guesses: 0.0,
+                sequence: Vec::new(),
+            };
+        };
+
+        let mut sequence = Vec::new();
+        let mut end = length;
+        while count > 0 {
+            let (_, index) = best[count][end].unwrap();
+            sequence.push(matches[index].clone());
+            end = matches[index].start;
+            count -= 1;
+        }
+        sequence.reverse();
+        return PasswordEstimate {
+            length,
+            log_guesses,
+            sequence,
+        };
+    }
+
+    /// A score from 0, guessed almost at once, to 4, out of reach of an
+    /// offline attack, on the same scale as zxcvbn.
+    pub(crate) fn score(&self) -> u8 {
+        return [3.0, 6.0, 8.0, 10.0]
+            .iter()
+            .filter(|x| self.log_guesses >= **x)
+            .count() as u8;
+    }
+
+    /// The fraction of characters explained by a pattern other than brute
+    /// force.
+    pub(crate) fn coverage(&self) -> f64 {
+        if self.length == 0 {
+            return 0.0;
+        }
+        let covered: usize = self
+            .sequence
+            .iter()
+            .filter(|x| x.pattern != Pattern::BruteForce)
+            .map(|x| x.end - x.start)
+            .sum();
+        return covered as f64 / self.length as f64;
+    }
+
+    pub(crate) fn patterns_description(&self) -> String {
+        return self
+            .sequence
+            .iter()
+            .map(|x| x.pattern.description())
+            .collect::<Vec<_>>()
+            .join(" + ");
+    }
+}
+
+/// What a value given as a password looks like.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum PasswordKind {
+    /// A value to be replaced, or a reference to a variable holding the
+    /// real password.
+    Placeholder,
+    HumanChosen,
+    Random,
+}
+
+impl PasswordKind {
+    pub(crate) fn of(value: &str, estimate: &PasswordEstimate) -> Self {
+        let reference = value.starts_with('$')
+            || value.starts_with("{{")
+            || (value.len() > 1 && value.starts_with('%') && value.ends_with('%'));
+        if reference || is_placeholder(value) {
+            return PasswordKind::Placeholder;
+        }
+        if estimate.coverage() >= MIN_HUMAN_COVERAGE {
+            return PasswordKind::HumanChosen;
+        }
+        return PasswordKind::Random;
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        return match self {
+            PasswordKind::Placeholder => "placeholder",
+            PasswordKind::HumanChosen => "human-chosen",
+            PasswordKind::Random => "random",
+        };
+    }
+}
+
+/// A password given as the value of a setting.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PasswordSetting {
+    pub(crate) line: usize,
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
+fn is_name_char(chr_t: u8) -> bool {
+    return chr_t.is_ascii_alphanumeric() || b"_-.".contains(&chr_t);
+}
+
+/// Characters that separate one setting from the next.
+const SETTING_DELIMITERS: [char; 4] = [';', ',', '{', '-'];
+
+/// Does a setting name stand where a setting can start: at the start of the
+/// line, after a delimiter, or as a command line flag.
+fn starts_setting(line: &str, start: usize) -> bool {
+    let before = line[..start].trim_end_matches(['"', '\'']).trim_end();
+    return before.is_empty()
+        || before.ends_with(SETTING_DELIMITERS)
+        || line[start..].starts_with('-');
+}
+
+/// Find the settings whose names say they hold a password, in the
+/// `name=value` and `name: value` forms of config files, connection strings
+/// and scripts, quoted or not. Lines of prose that happen to use the same
+/// forms are skipped: the name has to start a setting, unless it is joined
+/// straight to its value by `=`, and an unquoted value has to be a single
+/// word.
+pub(crate) fn password_settings(text: &str) -> Vec<PasswordSetting> {
+    let mut settings = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let lower = line.to_ascii_lowercase();
+        let mut name_starts = Vec::new();
+
+        for position in PASSWORD_KEYWORDS
+            .iter()
+            .flat_map(|x| lower.match_indices(x).map(|(y, _)| y))
+        {
+            let start = (0..position)
+                .rev()
+                .take_while(|x| is_name_char(bytes[*x]))
+                .last()
+                .unwrap_or(position);
+            let end = (position..bytes.len())
+                .find(|x| !is_name_char(bytes[*x]))
+                .unwrap_or(bytes.len());
+            let name = &line[start..end];
+            if name_starts.contains(&start)
+                || NOT_PASSWORD_WORDS
+                    .iter()
+                    .any(|x| name.to_ascii_lowercase().contains(x))
+            {
+                continue;
+            }
+            name_starts.push(start);
+
+            if !starts_setting(line, start) && !line[end..].starts_with('=') {
+                continue;
+            }
+
+            let rest = line[end..].trim_start_matches(['"', '\'']).trim_start();
+            let Some(rest) = rest
+                .strip_prefix(":=")
+                .or_else(|| rest.strip_prefix(['=', ':']))
+            else {
+                continue;
+            };
+            let rest = rest.trim_start();
+            let value = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or(""),
+                _ => {
+                    let value = rest
+                        .split(|x: char| x.is_whitespace() || ";,".contains(x))
+                        .next()
+                        .unwrap_or("");
+                    let after = rest[value.len()..].trim_start();
+                    if !after.is_empty() && !after.starts_with([';', ',', '#']) {
+                        continue;
+                    }
+                    value
+                }
+            };
+            if value.is_empty() || value.chars().count() > MAX_PASSWORD_LENGTH {
+                continue;
+            }
+            settings.push(PasswordSetting {
+                line: index + 1,
+                name: String::from(name),
+                value: String::from(value),
+            });
+        }
+    }
+    return settings;
+}
+
+/// Report the passwords written into a text file with how easily each could
+/// be guessed, weakest the most serious. Placeholders and references to
+/// variables are skipped, as are files that are not UTF-8 text. The
+/// passwords themselves are never included in the finding.
+pub(crate) fn password_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let text = match String::from_utf8(std::fs::read(file)?) {
+        Ok(text) => text,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut findings = Vec::new();
+    for setting in password_settings(&text) {
+        let estimate = PasswordEstimate::new(&setting.value);
+        let kind = PasswordKind::of(&setting.value, &estimate);
+        if kind == PasswordKind::Placeholder {
+            continue;
+        }
+        let severity = match estimate.score() <= 2 {
+            true => Severity::High,
+            false => Severity::Medium,
+        };
+        let mut finding = Finding::new(file, severity, "Password in plain text");
+        finding.add_detail("line", setting.line.to_string());
+        finding.add_detail("setting", setting.name);
+        finding.add_detail("password type", String::from(kind.description()));
+        finding.add_detail("guesses", format!("10^{:.1}", estimate.log_guesses));
+        finding.add_detail("strength", format!("{}/4", estimate.score()));
+        finding.add_detail("patterns", estimate.patterns_description());
+        finding.add_detail("length", estimate.length.to_string());
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD_STRENGTH: &str = "./tests/testing_files/password_strength";
+
+    fn patterns(password: &str) -> Vec<Pattern> {
+        return PasswordEstimate::new(password)
+            .sequence
+            .iter()
+            .map(|x| x.pattern)
+            .collect();
+    }
+
+    fn findings(name: &str) -> Vec<Finding> {
+        return password_findings(&Path::new(PASSWORD_STRENGTH).join(name)).unwrap();
+    }
+
+    #[test]
+    fn common_password_list() {
+        assert_eq!(dictionary_rank("123456"), Some(1.0));
+        assert_eq!(dictionary_rank("password"), Some(2.0));
+        assert_eq!(dictionary_rank("the"), Some(1.0));
+        assert!(dictionary_rank("house") < dictionary_rank("speculation"));
+        assert_eq!(dictionary_rank("zqxv"), None);
+    }
+
+    #[test]
+    fn capitalisation() {
+        let chars = |x: &str| x.chars().collect::<Vec<char>>();
+        assert_eq!(uppercase_variations(&chars("summer")), 1.0);
+        assert_eq!(uppercase_variations(&chars("Summer")), 2.0);
+        assert_eq!(uppercase_variations(&chars("SUMMER")), 2.0);
+        assert_eq!(uppercase_variations(&chars("sUmmer")), 6.0);
+    }
+
+    #[test]
+    fn word_and_year() {
+        assert_eq!(
+            patterns("Summer2024!"),
+            vec![
+                Pattern::Dictionary { l33t: false },
+                Pattern::Year,
+                Pattern::BruteForce
+            ]
+        );
+        let estimate = PasswordEstimate::new("Summer2024!");
+        assert_eq!(estimate.score(), 1);
+        assert_eq!(
+            PasswordKind::of("Summer2024!", &estimate),
+            PasswordKind::HumanChosen
+        );
+    }
+
+    #[test]
+    fn l33t_substitutions() {
+        assert_eq!(
+            patterns("P@ssw0rd"),
+            vec![Pattern::Dictionary { l33t: true }]
+        );
+        assert!(PasswordEstimate::new("P@ssw0rd").log_guesses < 3.0);
+    }
+
+    #[test]
+    fn keyboard_walks() {
+        assert_eq!(key_direction('q', 'w'), Some((0, 1)));
+        assert_eq!(key_direction('a', 'z'), Some((1, 1)));
+        assert_eq!(key_direction('q', 'p'), None);
+        assert_eq!(patterns("zxcvfr"), vec![Pattern::KeyboardWalk]);
+        assert!(walk_guesses(6, 2) > walk_guesses(6, 1));
+    }
+
+    #[test]
+    fn sequences_and_repeats() {
+        assert_eq!(patterns("lmnop"), vec![Pattern::Sequence]);
+        assert_eq!(patterns("98765"), vec![Pattern::Sequence]);
+        assert_eq!(patterns("%%%%%%"), vec![Pattern::Repeat]);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("15/08/1987"), Some(1987));
+        assert_eq!(parse_date("19870815"), Some(1987));
+        assert_eq!(parse_date("150887"), Some(1987));
+        assert_eq!(parse_date("15/08-1987"), None);
+        assert_eq!(parse_date("45/45/1987"), None);
+        assert_eq!(patterns("15.08.1987"), vec![Pattern::Date]);
+    }
+
+    #[test]
+    fn random_password() {
+        let estimate = PasswordEstimate::new("Zq8vN3kR7tY2pL9mXc4W");
+        assert_eq!(estimate.score(), 4);
+        assert_eq!(
+            PasswordKind::of("Zq8vN3kR7tY2pL9mXc4W", &estimate),
+            PasswordKind::Random
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        for value in [
+            "changeme",
+            "${DB_PASSWORD}",
+            "%DB_PASSWORD%",
+            "{{ db_password }}",
+        ] {
+            let estimate = PasswordEstimate::new(value);
+            assert_eq!(
+                PasswordKind::of(value, &estimate),
+                PasswordKind::Placeholder
+            );
+        }
+        let estimate = PasswordEstimate::new("Example4Zq8vN3kR7");
+        assert_ne!(
+            PasswordKind::of("Example4Zq8vN3kR7", &estimate),
+            PasswordKind::Placeholder
+        );
+    }
+
+    #[test]
+    fn settings_in_lines() {
+        let settings = password_settings(concat!(
+            "\"db_password\": \"hunter2\",\n",
+            "Server=db01;Pwd=Summer2024!;Encrypt=true\n",
+            "password_hash = 5f4dcc3b5aa765d61d8327deb882cf99\n",
+            "Enter your password: \n",
+            "The password: must contain 8 characters\n",
+            "Password: at least twelve characters\n",
+            "Set the admin password: hunter2 works for now\n",
+            "  - password: \"s3cret\"\n",
+            "export ROOT_PASSWORD=Tr0ub4dor # rotate\n",
+        ));
+        let found: Vec<(usize, &str, &str)> = settings
+            .iter()
+            .map(|x| (x.line, x.name.as_str(), x.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "db_password", "hunter2"),
+                (2, "Pwd", "Summer2024!"),
+                (8, "password", "s3cret"),
+                (9, "ROOT_PASSWORD", "Tr0ub4dor")
+            ]
+        );
+    }
+
+    #[test]
+    fn connection_string() {
+        let found = findings("app.config");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(found[0].detail("line"), Some(&String::from("4")));
+        assert_eq!(
+            found[0].detail("password type"),
+            Some(&String::from("human-chosen"))
+        );
+        assert_eq!(found[0].detail("strength"), Some(&String::from("1/4")));
+        assert_eq!(
+            found[0].detail("patterns"),
+            Some(&String::from("dictionary word + year + brute force"))
+        );
+    }
+
+    #[test]
+    fn dotenv_placeholders_skipped() {
+        let found = findings(".env");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(
+            found[0].detail("setting"),
+            Some(&String::from("SMTP_PASSWORD"))
+        );
+        assert_eq!(
+            found[0].detail("password type"),
+            Some(&String::from("random"))
+        );
+    }
+
+    #[test]
+    fn command_line_password() {
+        let found = findings("restore.sh");
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].detail("setting"),
+            Some(&String::from("--password"))
+        );
+        assert_eq!(found[0].detail("strength"), Some(&String::from("1/4")));
+    }
+
+    #[test]
+    fn prose_not_reported() {
+        assert!(findings("readme.txt").is_empty());
+    }
+
+    #[test]
+    fn binary_file_skipped() {
+        assert!(
+            password_findings(Path::new("./tests/testing_files/entropy/backup.hc"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_file() {
+        findings("missing.txt");
+    }
+}
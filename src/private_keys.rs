@@ -0,0 +1,621 @@
+/*
+ * Decode OpenSSH and PEM private keys far enough to tell whether they are
+ * protected by a passphrase and how strong the key is.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::der::{
+    DerElement, TAG_INTEGER, TAG_OCTET_STRING, TAG_SEQUENCE, curve_bits, oid_name, unsigned_bits,
+};
+use crate::encoding::{PemBlock, pem_blocks};
+use crate::findings::{Finding, Severity};
+use std::error::Error;
+use std::path::Path;
+
+/// RSA keys with a smaller modulus than this can be factored by a determined
+/// attacker.
+const MIN_RSA_BITS: u32 = 2048;
+
+/// OpenSSH keys hardened with fewer bcrypt rounds than the default are
+/// quicker to brute force.
+const MIN_BCRYPT_ROUNDS: u64 = 16;
+
+/// The protection and strength of a single private key.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PrivateKeyInfo {
+    pub(crate) format: String,
+    pub(crate) encrypted: bool,
+    pub(crate) cipher: Option<String>,
+    pub(crate) kdf: Option<String>,
+    pub(crate) kdf_rounds: Option<u64>,
+    pub(crate) key_type: Option<String>,
+    pub(crate) key_bits: Option<u32>,
+}
+
+impl PrivateKeyInfo {
+    fn new(format: &str) -> Self {
+        return PrivateKeyInfo {
+            format: String::from(format),
+            encrypted: false,
+            cipher: None,
+            kdf: None,
+            kdf_rounds: None,
+            key_type: None,
+            key_bits: None,
+        };
+    }
+
+    /// Describe anything about the key that makes it easier to recover.
+    pub(crate) fn weaknesses(&self) -> Vec<String> {
+        let mut weaknesses = Vec::new();
+
+        match (self.key_type.as_deref(), self.key_bits) {
+            (Some("RSA"), Some(bits)) if bits < MIN_RSA_BITS => {
+                weaknesses.push(format!("{bits} bit RSA key"));
+            }
+            (Some("DSA"), _) => weaknesses.push(String::from("DSA keys are deprecated")),
+            _ => {}
+        }
+        if self.kdf.as_deref() == Some("bcrypt")
+            && self.kdf_rounds.is_some_and(|x| x < MIN_BCRYPT_ROUNDS)
+        {
+            weaknesses.push(format!(
+                "Only {} bcrypt rounds",
+                self.kdf_rounds.unwrap_or(0)
+            ));
+        }
+        if self.kdf.as_deref() == Some("EVP_BytesToKey MD5") {
+            weaknesses.push(String::from(
+                "Passphrase is not hardened by a slow key derivation",
+            ));
+        }
+        return weaknesses;
+    }
+}
+
+/// Read a length prefixed string from the SSH wire format.
+fn read_ssh_string<'a>(reader: &mut ByteReader<'a>) -> Result<&'a [u8], Box<dyn Error>> {
+    let length = reader.read_u32_be()? as usize;
+    return Ok(reader.read_bytes(length)?);
+}
+
+/// Work out the type and size of a key from an SSH public key blob.
+fn ssh_public_key_strength(blob: &[u8]) -> Result<(String, Option<u32>), Box<dyn Error>> {
+    let mut reader = ByteReader::new(blob);
+    let key_type = String::from_utf8_lossy(read_ssh_string(&mut reader)?).to_string();
+
+    let bits = match key_type.as_str() {
+        "ssh-rsa" => {
+            read_ssh_string(&mut reader)?;
+            Some(unsigned_bits(read_ssh_string(&mut reader)?))
+        }
+        "ssh-dss" => Some(unsigned_bits(read_ssh_string(&mut reader)?)),
+        "ssh-ed25519" | "sk-ssh-ed25519@openssh.com" => Some(256),
+        "ssh-ed448" => Some(448),
+        "ecdsa-sha2-nistp256" | "sk-ecdsa-sha2-nistp256@openssh.com" => Some(256),
+        "ecdsa-sha2-nistp384" => Some(384),
+        "ecdsa-sha2-nistp521" => Some(521),
+        _ => None,
+    };
+    return Ok((key_type, bits));
+}
+
+/// Decode the binary body of an `openssh-key-v1` private key.
+fn parse_openssh_key(data: &[u8]) -> Result<PrivateKeyInfo, Box<dyn Error>> {
+    let mut reader = ByteReader::new(data);
+
+    if reader.read_bytes(15)? != b"openssh-key-v1\0" {
+        return Err(Box::new(invalid_data("Not an openssh-key-v1 private key")));
+    }
+
+    let cipher = String::from_utf8_lossy(read_ssh_string(&mut reader)?).to_string();
+    let kdf = String::from_utf8_lossy(read_ssh_string(&mut reader)?).to_string();
+    let kdf_options = read_ssh_string(&mut reader)?;
+    let mut info = PrivateKeyInfo::new("openssh-key-v1");
+
+    /* The bcrypt options are a salt followed by the number of rounds. */
+    if kdf == "bcrypt" {
+        let mut options = ByteReader::new(kdf_options);
+        read_ssh_string(&mut options)?;
+        info.kdf_rounds = Some(options.read_u32_be()? as u64);
+    }
+
+    /* Only the first key is examined, files with more are very rare. */
+    if reader.read_u32_be()? > 0 {
+        let (key_type, key_bits) = ssh_public_key_strength(read_ssh_string(&mut reader)?)?;
+        info.key_type = Some(key_type);
+        info.key_bits = key_bits;
+    }
+
+    info.encrypted = cipher != "none";
+    if info.encrypted {
+        info.cipher = Some(cipher);
+        info.kdf = Some(kdf);
+    }
+    return Ok(info);
+}
+
+/// The size of an RSA key from a PKCS#1 `RSAPrivateKey` structure.
+fn pkcs1_rsa_bits(data: &[u8]) -> Result<u32, Box<dyn Error>> {
+    let (sequence, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+    let fields = sequence.children()?;
+
+    let Some(modulus) = fields.get(1).filter(|x| x.tag == TAG_INTEGER) else {
+        return Err(Box::new(invalid_data("RSA key has no modulus")));
+    };
+    return Ok(modulus.integer_bits());
+}
+
+/// Decode a legacy OpenSSL style key, which may be encrypted with the
+/// passphrase run once through MD5.
+fn parse_legacy_pem(block: &PemBlock, key_type: &str) -> Result<PrivateKeyInfo, Box<dyn Error>> {
+    let mut info = PrivateKeyInfo::new("PEM");
+    info.key_type = Some(String::from(key_type));
+
+    if block
+        .header("Proc-Type")
+        .is_some_and(|x| x.ends_with("ENCRYPTED"))
+    {
+        info.encrypted = true;
+        info.cipher = block
+            .header("DEK-Info")
+            .and_then(|x| x.split(',').next())
+            .map(String::from);
+        info.kdf = Some(String::from("EVP_BytesToKey MD5"));
+        return Ok(info);
+    }
+
+    let (sequence, _) = DerElement::parse_tagged(&block.data, TAG_SEQUENCE)?;
+    let fields = sequence.children()?;
+
+    info.key_bits = match key_type {
+        "RSA" => Some(pkcs1_rsa_bits(&block.data)?),
+        "DSA" => fields.get(1).map(|x| x.integer_bits()),
+        "EC" => fields
+            .iter()
+            .find(|x| x.tag == 0xA0)
+            .and_then(|x| x.children().ok())
+            .and_then(|x| x.first().and_then(|oid| oid.as_oid().ok()))
+            .and_then(|oid| oid_name(&oid).and_then(curve_bits)),
+        _ => None,
+    };
+    return Ok(info);
+}
+
+/// Decode an unencrypted PKCS#8 `PrivateKeyInfo` structure.
+fn parse_pkcs8(data: &[u8]) -> Result<PrivateKeyInfo, Box<dyn Error>> {
+    let (sequence, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+    let fields = sequence.children()?;
+
+    let (Some(algorithm), Some(private_key)) = (fields.get(1), fields.get(2)) else {
+        return Err(Box::new(invalid_data("Incomplete PKCS#8 private key")));
+    };
+    let algorithm = algorithm.children()?;
+    let Some(algorithm_oid) = algorithm.first() else {
+        return Err(Box::new(invalid_data("PKCS#8 key has no algorithm")));
+    };
+    let key_type = oid_name(&algorithm_oid.as_oid()?).unwrap_or("Unknown");
+
+    let mut info = PrivateKeyInfo::new("PKCS#8");
+    info.key_type = Some(String::from(key_type));
+    info.key_bits = match key_type {
+        "RSA" if private_key.tag == TAG_OCTET_STRING => Some(pkcs1_rsa_bits(private_key.contents)?),
+        "DSA" => algorithm
+            .get(1)
+            .and_then(|x| x.children().ok())
+            .and_then(|x| x.first().map(|p| p.integer_bits())),
+        "EC" => algorithm
+            .get(1)
+            .and_then(|x| x.as_oid().ok())
+            .and_then(|oid| oid_name(&oid).and_then(curve_bits)),
+        _ => curve_bits(key_type),
+    };
+    return Ok(info);
+}
+
+/// Decode a PKCS#8 `EncryptedPrivateKeyInfo`, reporting how the passphrase is
+/// turned into a key.
+fn parse_encrypted_pkcs8(data: &[u8]) -> Result<PrivateKeyInfo, Box<dyn Error>> {
+    let (sequence, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+    let fields = sequence.children()?;
+    let mut info = PrivateKeyInfo::new("PKCS#8");
+    info.encrypted = true;
+
+    let Some(algorithm) = fields.first() else {
+        return Err(Box::new(invalid_data("Encrypted key has no algorithm")));
+    };
+    let algorithm = algorithm.children()?;
+    let Some(scheme_oid) = algorithm.first() else {
+        return Err(Box::new(invalid_data("Encrypted key has no algorithm")));
+    };
+    let scheme = oid_name(&scheme_oid.as_oid()?).unwrap_or("Unknown");
+
+    if scheme != "PBES2" {
+        info.cipher = Some(String::from(scheme));
+        return Ok(info);
+    }
+
+    /* PBES2 names a key derivation function and a cipher separately. */
+    let pbes2 = algorithm
+        .get(1)
+        .map(|x| x.children())
+        .transpose()?
+        .unwrap_or_default();
+    if let Some(kdf) = pbes2.first() {
+        let kdf = kdf.children()?;
+        info.kdf = kdf
+            .first()
+            .and_then(|x| x.as_oid().ok())
+            .map(|oid| String::from(oid_name(&oid).unwrap_or("Unknown")));
+
+        if info.kdf.as_deref() == Some("PBKDF2") {
+            info.kdf_rounds = kdf
+                .get(1)
+                .and_then(|x| x.children().ok())
+                .and_then(|x| x.get(1).and_then(|iter| iter.as_u64().ok()));
+        }
+    }
+    if let Some(cipher) = pbes2.get(1) {
+        info.cipher = cipher
+            .children()?
+            .first()
+            .and_then(|x| x.as_oid().ok())
+            .map(|oid| String::from(oid_name(&oid).unwrap_or("Unknown")));
+    }
+    return Ok(info);
+}
+
+/// Examine every private key held in a text file.
+pub(crate) fn private_keys_from_text(text: &str) -> Vec<PrivateKeyInfo> {
+    let mut keys = Vec::new();
+
+    for block in pem_blocks(text) {
+        let parsed = match block.label.as_str() {
+            "OPENSSH PRIVATE KEY" => parse_openssh_key(&block.data),
+            "RSA PRIVATE KEY" => parse_legacy_pem(&block, "RSA"),
+            "DSA PRIVATE KEY" => parse_legacy_pem(&block, "DSA"),
+            "EC PRIVATE KEY" => parse_legacy_pem(&block, "EC"),
+            "PRIVATE KEY" => parse_pkcs8(&block.data),
+            "ENCRYPTED PRIVATE KEY" => parse_encrypted_pkcs8(&block.data),
+            _ => continue,
+        };
+
+        if let Ok(info) = parsed {
+            keys.push(info);
+        }
+    }
+    return keys;
+}
+
+/// The public value of an SSH public key blob, in the same form a certificate
+/// would carry it.
+fn ssh_public_value(blob: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let mut reader = ByteReader::new(blob);
+    let key_type = read_ssh_string(&mut reader)?;
+
+    let value = match key_type {
+        b"ssh-rsa" => {
+            read_ssh_string(&mut reader)?;
+            unsigned_value(read_ssh_string(&mut reader)?)
+        }
+        b"ssh-dss" => {
+            for _ in 0..3 {
+                read_ssh_string(&mut reader)?;
+            }
+            unsigned_value(read_ssh_string(&mut reader)?)
+        }
+        b"ssh-ed25519" => read_ssh_string(&mut reader)?,
+        _ if key_type.starts_with(b"ecdsa-sha2-") => {
+            read_ssh_string(&mut reader)?;
+            read_ssh_string(&mut reader)?
+        }
+        _ => return Ok(None),
+    };
+    return Ok(Some(value.to_vec()));
+}
+
+/// Strip the zero padding from an SSH or DER encoded unsigned integer.
+fn unsigned_value(bytes: &[u8]) -> &[u8] {
+    return DerElement {
+        tag: TAG_INTEGER,
+        contents: bytes,
+    }
+    .unsigned_value();
+}
+
+/// The public value held inside a PKCS#1 RSA, DSA or SEC1 EC private key.
+fn der_public_value(data: &[u8], key_type: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let (sequence, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+    let fields = sequence.children()?;
+
+    let value = match key_type {
+        "RSA" => fields.get(1).map(|x| x.unsigned_value().to_vec()),
+        "DSA" => fields.get(4).map(|x| x.unsigned_value().to_vec()),
+        /* The optional public key is a bit string inside an explicit [1] tag. */
+        "EC" => fields
+            .iter()
+            .find(|x| x.tag == 0xA1)
+            .and_then(|x| x.children().ok())
+            .and_then(|x| {
+                x.first()
+                    .map(|bits| bits.contents.get(1..).unwrap_or(&[]).to_vec())
+            }),
+        _ => None,
+    };
+    return Ok(value);
+}
+
+/// The public values of every key in a text file that carries one in the
+/// clear, for matching keys against certificates.
+pub(crate) fn private_key_public_values(text: &str) -> Vec<Vec<u8>> {
+    let mut values = Vec::new();
+
+    for block in pem_blocks(text) {
+        let encrypted = block
+            .header("Proc-Type")
+            .is_some_and(|x| x.ends_with("ENCRYPTED"));
+        let value = match block.label.as_str() {
+            /* The public key is stored unencrypted even in protected files. */
+            "OPENSSH PRIVATE KEY" => {
+                let mut reader = ByteReader::new(&block.data);
+                let blob = (|| -> Result<&[u8], Box<dyn Error>> {
+                    reader.skip(15)?;
+                    for _ in 0..3 {
+                        read_ssh_string(&mut reader)?;
+                    }
+                    reader.read_u32_be()?;
+                    return read_ssh_string(&mut reader);
+                })();
+                blob.and_then(ssh_public_value)
+            }
+            "RSA PRIVATE KEY" if !encrypted => der_public_value(&block.data, "RSA"),
+            "DSA PRIVATE KEY" if !encrypted => der_public_value(&block.data, "DSA"),
+            "EC PRIVATE KEY" if !encrypted => der_public_value(&block.data, "EC"),
+            "PRIVATE KEY" => parse_pkcs8(&block.data).and_then(|info| {
+                let (sequence, _) = DerElement::parse_tagged(&block.data, TAG_SEQUENCE)?;
+                let fields = sequence.children()?;
+                match (info.key_type.as_deref(), fields.get(2)) {
+                    (Some(key_type), Some(inner)) => der_public_value(inner.contents, key_type),
+                    _ => Ok(None),
+                }
+            }),
+            _ => continue,
+        };
+
+        if let Ok(Some(value)) = value {
+            values.push(value);
+        }
+    }
+    return values;
+}
+
+/// Report each private key in a file, treating unencrypted keys as critical
+/// and passphrase protected ones as less serious.
+pub(crate) fn private_key_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let text = String::from_utf8_lossy(&std::fs::read(file)?).to_string();
+    let mut findings = Vec::new();
+
+    for key in private_keys_from_text(&text) {
+        let mut finding = if key.encrypted {
+            Finding::new(
+                file,
+                Severity::High.downgrade(),
+                "Passphrase protected private key",
+            )
+        } else {
+            Finding::new(file, Severity::High.escalate(), "Unencrypted private key")
+        };
+
+        finding.add_detail("format", key.format.clone());
+        if let Some(key_type) = &key.key_type {
+            finding.add_detail("key type", key_type.clone());
+        }
+        if let Some(bits) = key.key_bits {
+            finding.add_detail("key size", format!("{bits} bits"));
+        }
+        if let Some(cipher) = &key.cipher {
+            finding.add_detail("cipher", cipher.clone());
+        }
+        if let Some(kdf) = &key.kdf {
+            finding.add_detail("kdf", kdf.clone());
+        }
+        if let Some(rounds) = key.kdf_rounds {
+            finding.add_detail("kdf rounds", rounds.to_string());
+        }
+        for weakness in key.weaknesses() {
+            finding.add_detail("weakness", weakness);
+        }
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys_in(file: &str) -> Vec<PrivateKeyInfo> {
+        let text = std::fs::read_to_string(Path::new(file)).unwrap();
+        return private_keys_from_text(&text);
+    }
+
+    #[test]
+    fn openssh_unencrypted_ed25519() {
+        assert_eq!(
+            keys_in("./tests/testing_files/private_keys/openssh_ed25519_plain"),
+            vec![PrivateKeyInfo {
+                format: String::from("openssh-key-v1"),
+                encrypted: false,
+                cipher: None,
+                kdf: None,
+                kdf_rounds: None,
+                key_type: Some(String::from("ssh-ed25519")),
+                key_bits: Some(256),
+            }]
+        );
+    }
+
+    #[test]
+    fn openssh_encrypted_rsa() {
+        assert_eq!(
+            keys_in("./tests/testing_files/private_keys/openssh_rsa_encrypted"),
+            vec![PrivateKeyInfo {
+                format: String::from("openssh-key-v1"),
+                encrypted: true,
+                cipher: Some(String::from("aes256-ctr")),
+                kdf: Some(String::from("bcrypt")),
+                kdf_rounds: Some(32),
+                key_type: Some(String::from("ssh-rsa")),
+                key_bits: Some(3072),
+            }]
+        );
+    }
+
+    #[test]
+    fn openssh_unencrypted_ecdsa() {
+        let keys = keys_in("./tests/testing_files/private_keys/openssh_ecdsa_plain");
+        assert_eq!(keys[0].key_type, Some(String::from("ecdsa-sha2-nistp384")));
+        assert_eq!(keys[0].key_bits, Some(384));
+    }
+
+    #[test]
+    fn openssh_truncated() {
+        assert!(keys_in("./tests/testing_files/private_keys/openssh_truncated").is_empty());
+    }
+
+    #[test]
+    fn pkcs1_weak_rsa() {
+        let keys = keys_in("./tests/testing_files/private_keys/pkcs1_rsa_1024.pem");
+        assert_eq!(keys[0].key_bits, Some(1024));
+        assert!(!keys[0].encrypted);
+        assert_eq!(keys[0].weaknesses(), vec![String::from("1024 bit RSA key")]);
+    }
+
+    #[test]
+    fn pkcs1_encrypted_rsa() {
+        let keys = keys_in("./tests/testing_files/private_keys/pkcs1_rsa_encrypted.pem");
+        assert!(keys[0].encrypted);
+        assert_eq!(keys[0].cipher, Some(String::from("AES-128-CBC")));
+        assert_eq!(keys[0].key_bits, None);
+    }
+
+    #[test]
+    fn legacy_dsa() {
+        let keys = keys_in("./tests/testing_files/private_keys/dsa_1024.pem");
+        assert_eq!(keys[0].key_type, Some(String::from("DSA")));
+        assert_eq!(keys[0].key_bits, Some(1024));
+        assert_eq!(
+            keys[0].weaknesses(),
+            vec![String::from("DSA keys are deprecated")]
+        );
+    }
+
+    #[test]
+    fn sec1_ec() {
+        let keys = keys_in("./tests/testing_files/private_keys/sec1_ec_p384.pem");
+        assert_eq!(keys[0].key_type, Some(String::from("EC")));
+        assert_eq!(keys[0].key_bits, Some(384));
+    }
+
+    #[test]
+    fn pkcs8_rsa() {
+        let keys = keys_in("./tests/testing_files/private_keys/pkcs8_rsa_2048.pem");
+        assert_eq!(keys[0].format, "PKCS#8");
+        assert_eq!(keys[0].key_type, Some(String::from("RSA")));
+        assert_eq!(keys[0].key_bits, Some(2048));
+        assert!(keys[0].weaknesses().is_empty());
+    }
+
+    #[test]
+    fn pkcs8_ec() {
+        let keys = keys_in("./tests/testing_files/private_keys/pkcs8_ec_p256.pem");
+        assert_eq!(keys[0].key_type, Some(String::from("EC")));
+        assert_eq!(keys[0].key_bits, Some(256));
+    }
+
+    #[test]
+    fn pkcs8_encrypted() {
+        assert_eq!(
+            keys_in("./tests/testing_files/private_keys/pkcs8_encrypted.pem"),
+            vec![PrivateKeyInfo {
+                format: String::from("PKCS#8"),
+                encrypted: true,
+                cipher: Some(String::from("AES-256-CBC")),
+                kdf: Some(String::from("PBKDF2")),
+                kdf_rounds: Some(2048),
+                key_type: None,
+                key_bits: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn public_values() {
+        let public_values = |file: &str| {
+            private_key_public_values(&std::fs::read_to_string(file).unwrap())
+                .iter()
+                .map(|x| x.len())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            public_values("./tests/testing_files/private_keys/openssh_ed25519_plain"),
+            vec![32]
+        );
+        assert_eq!(
+            public_values("./tests/testing_files/private_keys/openssh_rsa_encrypted"),
+            vec![384]
+        );
+        assert_eq!(
+            public_values("./tests/testing_files/private_keys/pkcs1_rsa_1024.pem"),
+            vec![128]
+        );
+        assert_eq!(
+            public_values("./tests/testing_files/private_keys/sec1_ec_p384.pem"),
+            vec![97]
+        );
+        assert_eq!(
+            public_values("./tests/testing_files/private_keys/pkcs8_ec_p256.pem"),
+            vec![65]
+        );
+        assert!(
+            public_values("./tests/testing_files/private_keys/pkcs1_rsa_encrypted.pem").is_empty()
+        );
+    }
+
+    #[test]
+    fn unencrypted_finding() {
+        let findings = private_key_findings(Path::new(
+            "./tests/testing_files/private_keys/openssh_ed25519_plain",
+        ))
+        .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].description, "Unencrypted private key");
+        assert_eq!(
+            findings[0].detail("key size"),
+            Some(&String::from("256 bits"))
+        );
+    }
+
+    #[test]
+    fn encrypted_finding() {
+        let findings = private_key_findings(Path::new(
+            "./tests/testing_files/private_keys/pkcs1_rsa_encrypted.pem",
+        ))
+        .unwrap();
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert!(findings[0].detail("weakness").is_some());
+    }
+
+    #[test]
+    fn no_keys_in_file() {
+        assert!(
+            private_key_findings(Path::new(
+                "./tests/testing_files/read_file_header/text_file.bib"
+            ))
+            .unwrap()
+            .is_empty()
+        );
+    }
+}
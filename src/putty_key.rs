@@ -0,0 +1,241 @@
+/*
+ * Read the plain text headers of PuTTY private key (PPK) files to work out
+ * whether the key inside could be used without a passphrase.
+ */
+
+use crate::byte_reader::invalid_data;
+use crate::findings::{Finding, Severity};
+use std::error::Error;
+use std::path::Path;
+
+/// The settings used to turn a passphrase into a key in version 3 files.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct Argon2Parameters {
+    pub(crate) flavour: String,
+    pub(crate) memory_kib: u64,
+    pub(crate) passes: u64,
+    pub(crate) parallelism: u64,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PuttyKey {
+    pub(crate) version: u8,
+    pub(crate) key_type: String,
+    pub(crate) encryption: String,
+    pub(crate) comment: String,
+    pub(crate) argon2: Option<Argon2Parameters>,
+}
+
+impl PuttyKey {
+    pub(crate) fn from_text(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut version = None;
+        let mut key_type = None;
+        let mut encryption = None;
+        let mut comment = String::new();
+        let mut kdf_flavour = None;
+        let mut memory_kib = 0;
+        let mut passes = 0;
+        let mut parallelism = 0;
+
+        /* Every header is a single "Name: value" line. */
+        for line in text.lines() {
+            let Some((name, value)) = line.split_once(": ") else {
+                continue;
+            };
+            let value = value.trim();
+
+            match name {
+                "PuTTY-User-Key-File-2" => {
+                    version = Some(2);
+                    key_type = Some(String::from(value));
+                }
+                "PuTTY-User-Key-File-3" => {
+                    version = Some(3);
+                    key_type = Some(String::from(value));
+                }
+                "Encryption" => encryption = Some(String::from(value)),
+                "Comment" => comment = String::from(value),
+                "Key-Derivation" => kdf_flavour = Some(String::from(value)),
+                "Argon2-Memory" => memory_kib = value.parse()?,
+                "Argon2-Passes" => passes = value.parse()?,
+                "Argon2-Parallelism" => parallelism = value.parse()?,
+                _ => {}
+            }
+        }
+
+        let (Some(version), Some(key_type)) = (version, key_type) else {
+            return Err(Box::new(invalid_data("Not a PuTTY private key")));
+        };
+        let Some(encryption) = encryption else {
+            return Err(Box::new(invalid_data("PuTTY key has no encryption header")));
+        };
+
+        return Ok(PuttyKey {
+            version,
+            key_type,
+            encryption,
+            comment,
+            argon2: kdf_flavour.map(|flavour| Argon2Parameters {
+                flavour,
+                memory_kib,
+                passes,
+                parallelism,
+            }),
+        });
+    }
+
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return PuttyKey::from_text(&String::from_utf8_lossy(&std::fs::read(file)?));
+    }
+
+    pub(crate) fn is_encrypted(&self) -> bool {
+        return self.encryption != "none";
+    }
+}
+
+/// Report a PuTTY private key, treating an unencrypted key as critical and a
+/// passphrase protected one as less serious.
+pub(crate) fn putty_key_finding(file: &Path) -> Result<Finding, Box<dyn Error>> {
+    let key = PuttyKey::from_file(file)?;
+    let is_encrypted = key.is_encrypted();
+
+    let mut finding = if is_encrypted {
+        Finding::new(
+            file,
+            Severity::High.downgrade(),
+            "Passphrase protected PuTTY private key",
+        )
+    } else {
+        Finding::new(
+            file,
+            Severity::High.escalate(),
+            "Unencrypted PuTTY private key",
+        )
+    };
+
+    finding.add_detail("format version", key.version.to_string());
+    finding.add_detail("key type", key.key_type);
+    finding.add_detail("encryption", key.encryption);
+    finding.add_detail("comment", key.comment);
+
+    /* Version 2 keys derive their encryption key with a single SHA-1 pass. */
+    if is_encrypted && key.version == 2 {
+        finding.add_detail(
+            "weakness",
+            String::from("Passphrase is not hardened by a slow key derivation"),
+        );
+    }
+    if let Some(argon2) = key.argon2 {
+        finding.add_detail("key derivation", argon2.flavour);
+        finding.add_detail("argon2 memory", format!("{} KiB", argon2.memory_kib));
+        finding.add_detail("argon2 passes", argon2.passes.to_string());
+        finding.add_detail("argon2 parallelism", argon2.parallelism.to_string());
+    }
+    return Ok(finding);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unencrypted_v2() {
+        assert_eq!(
+            PuttyKey::from_file(Path::new(
+                "./tests/testing_files/putty_key/unencrypted_v2.ppk"
+            ))
+            .unwrap(),
+            PuttyKey {
+                version: 2,
+                key_type: String::from("ssh-rsa"),
+                encryption: String::from("none"),
+                comment: String::from("rsa-key-20240105"),
+                argon2: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_encrypted_v3() {
+        assert_eq!(
+            PuttyKey::from_file(Path::new(
+                "./tests/testing_files/putty_key/encrypted_v3.ppk"
+            ))
+            .unwrap(),
+            PuttyKey {
+                version: 3,
+                key_type: String::from("ssh-ed25519"),
+                encryption: String::from("aes256-cbc"),
+                comment: String::from("deploy@buildserver"),
+                argon2: Some(Argon2Parameters {
+                    flavour: String::from("Argon2id"),
+                    memory_kib: 8192,
+                    passes: 21,
+                    parallelism: 1,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_unencrypted_v3() {
+        let key = PuttyKey::from_file(Path::new(
+            "./tests/testing_files/putty_key/unencrypted_v3.ppk",
+        ))
+        .unwrap();
+        assert_eq!(key.key_type, "ecdsa-sha2-nistp256");
+        assert!(!key.is_encrypted());
+        assert_eq!(key.argon2, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_missing_encryption() {
+        PuttyKey::from_file(Path::new(
+            "./tests/testing_files/putty_key/missing_encryption.ppk",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_not_a_key() {
+        PuttyKey::from_file(Path::new(
+            "./tests/testing_files/read_file_header/text_file.bib",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn unencrypted_is_critical() {
+        let finding = putty_key_finding(Path::new(
+            "./tests/testing_files/putty_key/unencrypted_v2.ppk",
+        ))
+        .unwrap();
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.detail("weakness"), None);
+    }
+
+    #[test]
+    fn encrypted_is_downgraded() {
+        let finding = putty_key_finding(Path::new(
+            "./tests/testing_files/putty_key/encrypted_v3.ppk",
+        ))
+        .unwrap();
+        assert_eq!(finding.severity, Severity::Medium);
+        assert_eq!(
+            finding.detail("argon2 memory"),
+            Some(&String::from("8192 KiB"))
+        );
+    }
+
+    #[test]
+    fn encrypted_v2_weakness() {
+        let finding = putty_key_finding(Path::new(
+            "./tests/testing_files/putty_key/encrypted_v2.ppk",
+        ))
+        .unwrap();
+        assert_eq!(finding.severity, Severity::Medium);
+        assert!(finding.detail("weakness").is_some());
+    }
+}
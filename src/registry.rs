@@ -0,0 +1,921 @@
+/*
+ * Read keys and values from offline copies of Windows registry hives, such as
+ * SAM, SYSTEM, SOFTWARE or NTUSER.DAT, without any help from Windows, and
+ * look for the credentials applications are known to leave in them.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::findings::{Finding, Severity};
+use crate::x509::format_timestamp;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::Path;
+
+/// Hive bins start straight after the 4 KiB base block, and every cell
+/// offset in the hive is relative to that point.
+const BASE_BLOCK_SIZE: usize = 4096;
+
+/// Values larger than this are split over several cells by a "db" record.
+const BIG_DATA_THRESHOLD: u32 = 16344;
+
+/// Stops malformed hives with looping subkey lists from recursing forever.
+const MAX_LIST_DEPTH: usize = 8;
+
+const KEY_COMP_NAME: u16 = 0x0020;
+const VALUE_COMP_NAME: u16 = 0x0001;
+const DATA_INLINE: u32 = 0x8000_0000;
+const NO_OFFSET: u32 = 0xFFFF_FFFF;
+
+const REG_SZ: u32 = 1;
+const REG_EXPAND_SZ: u32 = 2;
+const REG_BINARY: u32 = 3;
+const REG_DWORD: u32 = 4;
+const REG_DWORD_BIG_ENDIAN: u32 = 5;
+const REG_MULTI_SZ: u32 = 7;
+const REG_QWORD: u32 = 11;
+
+/// A place in a hive where an application stores a secret. Key paths are
+/// relative to the hive root, and a segment ending in `*` matches any key
+/// name starting with the text before it.
+struct RegistryCredential {
+    name: &'static str,
+    key_path: &'static str,
+    /// The values holding the secret. An empty list means the value names
+    /// are themselves the secrets.
+    secret_values: &'static [&'static str],
+    /// Values worth reporting alongside the secret, such as the user name.
+    context_values: &'static [&'static str],
+    severity: Severity,
+}
+
+const REGISTRY_CREDENTIALS: [RegistryCredential; 12] = [
+    RegistryCredential {
+        name: "PuTTY session proxy password",
+        key_path: "Software\\SimonTatham\\PuTTY\\Sessions\\*",
+        secret_values: &["ProxyPassword"],
+        context_values: &["HostName", "ProxyHost", "ProxyUsername"],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "KiTTY session password",
+        key_path: "Software\\9bis.com\\KiTTY\\Sessions\\*",
+        secret_values: &["Password", "ProxyPassword"],
+        context_values: &["HostName", "UserName", "ProxyUsername"],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "WinSCP stored session password",
+        key_path: "Software\\Martin Prikryl\\WinSCP 2\\Sessions\\*",
+        secret_values: &["Password", "ProxyPassword", "TunnelPassword"],
+        context_values: &["HostName", "UserName"],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "RealVNC password",
+        key_path: "RealVNC\\vncserver",
+        secret_values: &["Password", "PasswordViewOnly"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "RealVNC password",
+        key_path: "RealVNC\\WinVNC4",
+        secret_values: &["Password"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "RealVNC password",
+        key_path: "Software\\RealVNC\\WinVNC4",
+        secret_values: &["Password"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "TightVNC password",
+        key_path: "TightVNC\\Server",
+        secret_values: &["Password", "PasswordViewOnly", "ControlPassword"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "TightVNC password",
+        key_path: "Software\\TightVNC\\Server",
+        secret_values: &["Password", "PasswordViewOnly", "ControlPassword"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "UltraVNC password",
+        key_path: "ORL\\WinVNC3*",
+        secret_values: &["Password", "Password2"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "UltraVNC password",
+        key_path: "Software\\ORL\\WinVNC3*",
+        secret_values: &["Password", "Password2"],
+        context_values: &[],
+        severity: Severity::High,
+    },
+    RegistryCredential {
+        name: "Windows autologon password",
+        key_path: "Microsoft\\Windows NT\\CurrentVersion\\Winlogon",
+        secret_values: &["DefaultPassword", "AltDefaultPassword"],
+        context_values: &["DefaultDomainName", "DefaultUserName", "AutoAdminLogon"],
+        severity: Severity::Critical,
+    },
+    RegistryCredential {
+        name: "SNMP community string",
+        key_path: "ControlSet*\\Services\\SNMP\\Parameters\\ValidCommunities",
+        secret_values: &[],
+        context_values: &[],
+        severity: Severity::Medium,
+    },
+];
+
+/// SNMP communities that every scanner tries first.
+const DEFAULT_SNMP_COMMUNITIES: [&str; 2] = ["public", "private"];
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum RegistryData {
+    String(String),
+    ExpandString(String),
+    Binary(Vec<u8>),
+    Dword(u32),
+    MultiString(Vec<String>),
+    Qword(u64),
+    Other(u32, Vec<u8>),
+}
+
+impl RegistryData {
+    fn decode(value_type: u32, bytes: &[u8]) -> Self {
+        return match value_type {
+            REG_SZ => RegistryData::String(utf16_string(bytes)),
+            REG_EXPAND_SZ => RegistryData::ExpandString(utf16_string(bytes)),
+            REG_BINARY => RegistryData::Binary(bytes.to_vec()),
+            REG_DWORD if bytes.len() == 4 => {
+                RegistryData::Dword(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            REG_DWORD_BIG_ENDIAN if bytes.len() == 4 => {
+                RegistryData::Dword(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            REG_MULTI_SZ => RegistryData::MultiString(
+                utf16_string(bytes)
+                    .split('\0')
+                    .filter(|x| !x.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ),
+            REG_QWORD if bytes.len() == 8 => {
+                RegistryData::Qword(u64::from_le_bytes(bytes.try_into().unwrap_or([0; 8])))
+            }
+            _ => RegistryData::Other(value_type, bytes.to_vec()),
+        };
+    }
+
+    /// The data as text, for the string types only.
+    pub(crate) fn as_string(&self) -> Option<&str> {
+        return match self {
+            RegistryData::String(text) | RegistryData::ExpandString(text) => Some(text),
+            _ => None,
+        };
+    }
+
+    /// The raw bytes of binary data and of types this parser does not know.
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        return match self {
+            RegistryData::Binary(bytes) | RegistryData::Other(_, bytes) => Some(bytes),
+            _ => None,
+        };
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct RegistryValue {
+    /// The name of the value, which is empty for a key's default value.
+    pub(crate) name: String,
+    pub(crate) data: RegistryData,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct RegistryKey {
+    pub(crate) name: String,
+    /// When the key was last changed, in seconds since the Unix epoch.
+    pub(crate) last_written: i64,
+    cell: u32,
+    subkey_count: u32,
+    subkey_list: u32,
+    value_count: u32,
+    value_list: u32,
+}
+
+pub(crate) struct RegistryHive {
+    data: Vec<u8>,
+    /// The path the hive was loaded from on the original machine.
+    pub(crate) file_name: String,
+    /// A hive that was not cleanly written may have changes waiting in its
+    /// transaction logs.
+    pub(crate) is_dirty: bool,
+    root_cell: u32,
+}
+
+impl RegistryHive {
+    pub(crate) fn from_bytes(data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let mut reader = ByteReader::new(&data);
+
+        if reader.read_bytes(4)? != b"regf" {
+            return Err(Box::new(invalid_data("Not a registry hive")));
+        }
+        let primary_sequence = reader.read_u32_le()?;
+        let secondary_sequence = reader.read_u32_le()?;
+
+        reader.seek(36)?;
+        let root_cell = reader.read_u32_le()?;
+
+        /* The file name is the last 64 bytes of the original path. */
+        reader.seek(48)?;
+        let file_name = utf16_string(reader.read_bytes(64)?);
+
+        if data.len() < BASE_BLOCK_SIZE + 4
+            || &data[BASE_BLOCK_SIZE..BASE_BLOCK_SIZE + 4] != b"hbin"
+        {
+            return Err(Box::new(invalid_data("Registry hive has no hive bins")));
+        }
+
+        return Ok(RegistryHive {
+            data,
+            file_name,
+            is_dirty: primary_sequence != secondary_sequence,
+            root_cell,
+        });
+    }
+
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return RegistryHive::from_bytes(std::fs::read(file)?);
+    }
+
+    /// The contents of an allocated cell.
+    fn cell(&self, offset: u32) -> Result<&[u8], Box<dyn Error>> {
+        let mut reader = ByteReader::new(&self.data);
+        reader.seek(BASE_BLOCK_SIZE + offset as usize)?;
+
+        /* Allocated cells have a negative size, free ones a positive size. */
+        let size = reader.read_u32_le()? as i32;
+        if size >= 0 {
+            return Err(Box::new(invalid_data(&format!(
+                "Cell at {offset:#x} is not allocated"
+            ))));
+        }
+        let length = (size.unsigned_abs() as usize).saturating_sub(4);
+        return Ok(reader.read_bytes(length)?);
+    }
+
+    fn read_key(&self, offset: u32) -> Result<RegistryKey, Box<dyn Error>> {
+        let cell = self.cell(offset)?;
+        let mut reader = ByteReader::new(cell);
+
+        if reader.read_bytes(2)? != b"nk" {
+            return Err(Box::new(invalid_data(&format!(
+                "Cell at {offset:#x} is not a key"
+            ))));
+        }
+        let flags = reader.read_u16_le()?;
+        let last_written = filetime_to_unix(reader.read_u64_le()?);
+
+        reader.seek(20)?;
+        let subkey_count = reader.read_u32_le()?;
+        reader.skip(4)?;
+        let subkey_list = reader.read_u32_le()?;
+        reader.skip(4)?;
+        let value_count = reader.read_u32_le()?;
+        let value_list = reader.read_u32_le()?;
+
+        reader.seek(72)?;
+        let name_length = reader.read_u16_le()? as usize;
+        reader.skip(2)?;
+        let name = decode_name(reader.read_bytes(name_length)?, flags & KEY_COMP_NAME != 0);
+
+        return Ok(RegistryKey {
+            name,
+            last_written,
+            cell: offset,
+            subkey_count,
+            subkey_list,
+            value_count,
+            value_list,
+        });
+    }
+
+    pub(crate) fn root_key(&self) -> Result<RegistryKey, Box<dyn Error>> {
+        return self.read_key(self.root_cell);
+    }
+
+    /// Collect the key offsets held in a subkey list, following index roots
+    /// down to the lists they point to.
+    fn subkey_offsets(
+        &self,
+        list: u32,
+        depth: usize,
+        offsets: &mut Vec<u32>,
+    ) -> Result<(), Box<dyn Error>> {
+        if depth > MAX_LIST_DEPTH {
+            return Err(Box::new(invalid_data("Subkey lists are nested too deeply")));
+        }
+
+        let cell = self.cell(list)?;
+        let mut reader = ByteReader::new(cell);
+        let signature = reader.read_bytes(2)?;
+        let count = reader.read_u16_le()?;
+
+        for _ in 0..count {
+            match signature {
+                /* Fast and hash leaves follow each offset with a name hint. */
+                b"lf" | b"lh" => {
+                    offsets.push(reader.read_u32_le()?);
+                    reader.skip(4)?;
+                }
+                b"li" => offsets.push(reader.read_u32_le()?),
+                b"ri" => self.subkey_offsets(reader.read_u32_le()?, depth + 1, offsets)?,
+                _ => {
+                    return Err(Box::new(invalid_data(&format!(
+                        "Unknown subkey list at {list:#x}"
+                    ))));
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    pub(crate) fn subkeys(&self, key: &RegistryKey) -> Result<Vec<RegistryKey>, Box<dyn Error>> {
+        if key.subkey_count == 0 || key.subkey_list == NO_OFFSET {
+            return Ok(Vec::new());
+        }
+
+        let mut offsets = Vec::new();
+        self.subkey_offsets(key.subkey_list, 0, &mut offsets)?;
+        return offsets.into_iter().map(|x| self.read_key(x)).collect();
+    }
+
+    pub(crate) fn values(&self, key: &RegistryKey) -> Result<Vec<RegistryValue>, Box<dyn Error>> {
+        if key.value_count == 0 || key.value_list == NO_OFFSET {
+            return Ok(Vec::new());
+        }
+
+        let mut list = ByteReader::new(self.cell(key.value_list)?);
+        let mut values = Vec::new();
+        for _ in 0..key.value_count {
+            values.push(self.read_value(list.read_u32_le()?)?);
+        }
+        return Ok(values);
+    }
+
+    fn read_value(&self, offset: u32) -> Result<RegistryValue, Box<dyn Error>> {
+        let mut reader = ByteReader::new(self.cell(offset)?);
+
+        if reader.read_bytes(2)? != b"vk" {
+            return Err(Box::new(invalid_data(&format!(
+                "Cell at {offset:#x} is not a value"
+            ))));
+        }
+        let name_length = reader.read_u16_le()? as usize;
+        let data_size = reader.read_u32_le()?;
+        let data_offset = reader.read_u32_le()?;
+        let value_type = reader.read_u32_le()?;
+        let flags = reader.read_u16_le()?;
+        reader.skip(2)?;
+        let name = decode_name(
+            reader.read_bytes(name_length)?,
+            flags & VALUE_COMP_NAME != 0,
+        );
+
+        /* Up to four bytes of data are stored in the offset field itself. */
+        let data = if data_size & DATA_INLINE != 0 {
+            let length = std::cmp::min((data_size & !DATA_INLINE) as usize, 4);
+            data_offset.to_le_bytes()[..length].to_vec()
+        } else if data_size > BIG_DATA_THRESHOLD && self.cell(data_offset)?.starts_with(b"db") {
+            self.read_big_data(data_offset, data_size as usize)?
+        } else {
+            let cell = self.cell(data_offset)?;
+            if cell.len() < data_size as usize {
+                return Err(Box::new(invalid_data("Value data overruns its cell")));
+            }
+            cell[..data_size as usize].to_vec()
+        };
+
+        return Ok(RegistryValue {
+            name,
+            data: RegistryData::decode(value_type, &data),
+        });
+    }
+
+    /// Join the segments of a value that was too large for a single cell.
+    fn read_big_data(&self, offset: u32, size: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut reader = ByteReader::new(self.cell(offset)?);
+        reader.skip(2)?;
+        let segment_count = reader.read_u16_le()?;
+        let mut segments = ByteReader::new(self.cell(reader.read_u32_le()?)?);
+
+        let mut data = Vec::new();
+        for _ in 0..segment_count {
+            let segment = self.cell(segments.read_u32_le()?)?;
+            let wanted = std::cmp::min(size - data.len(), BIG_DATA_THRESHOLD as usize);
+            data.extend_from_slice(&segment[..std::cmp::min(wanted, segment.len())]);
+        }
+        if data.len() != size {
+            return Err(Box::new(invalid_data("Big data value is incomplete")));
+        }
+        return Ok(data);
+    }
+
+    /// Find a key from a backslash separated path below the root key. Key
+    /// names are compared without regard to case, as Windows does.
+    pub(crate) fn open_key(&self, path: &str) -> Result<Option<RegistryKey>, Box<dyn Error>> {
+        let mut key = self.root_key()?;
+
+        for part in path.split('\\').filter(|x| !x.is_empty()) {
+            let part = part.to_lowercase();
+            match self
+                .subkeys(&key)?
+                .into_iter()
+                .find(|x| x.name.to_lowercase() == part)
+            {
+                Some(subkey) => key = subkey,
+                None => return Ok(None),
+            }
+        }
+        return Ok(Some(key));
+    }
+
+    /// Every key in the hive along with its path below the root key, parents
+    /// before their children.
+    pub(crate) fn walk(&self) -> Result<Vec<(String, RegistryKey)>, Box<dyn Error>> {
+        let mut keys = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![(String::new(), self.root_key()?)];
+
+        while let Some((path, key)) = pending.pop() {
+            /* A key reachable twice means the hive is corrupt or malicious. */
+            if !visited.insert(key.cell) {
+                continue;
+            }
+            for subkey in self.subkeys(&key)?.into_iter().rev() {
+                let subkey_path = if path.is_empty() {
+                    subkey.name.clone()
+                } else {
+                    format!("{path}\\{}", subkey.name)
+                };
+                pending.push((subkey_path, subkey));
+            }
+            if !path.is_empty() {
+                keys.push((path, key));
+            }
+        }
+        return Ok(keys);
+    }
+}
+
+/// Decode a name stored either as Latin-1 or as UTF-16.
+fn decode_name(bytes: &[u8], compressed: bool) -> String {
+    if compressed {
+        return bytes.iter().map(|x| *x as char).collect();
+    }
+    return utf16_string(bytes);
+}
+
+/// Decode little endian UTF-16 text, dropping anything after a terminating
+/// null unless more strings follow it.
+fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|x| u16::from_le_bytes([x[0], x[1]]))
+        .collect();
+    return String::from_utf16_lossy(&units)
+        .trim_end_matches('\0')
+        .to_string();
+}
+
+/// Convert a Windows FILETIME, counted in 100ns steps since 1601, into
+/// seconds since the Unix epoch.
+fn filetime_to_unix(filetime: u64) -> i64 {
+    return (filetime / 10_000_000) as i64 - 11_644_473_600;
+}
+
+/// Does a key path match a catalogue pattern, ignoring case.
+fn key_path_matches(path: &str, pattern: &str) -> bool {
+    let path: Vec<String> = path.split('\\').map(|x| x.to_lowercase()).collect();
+    let pattern: Vec<String> = pattern.split('\\').map(|x| x.to_lowercase()).collect();
+
+    return path.len() == pattern.len()
+        && path
+            .iter()
+            .zip(pattern.iter())
+            .all(|(name, part)| match part.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == part,
+            });
+}
+
+/// Does a value hold anything at all.
+fn has_content(data: &RegistryData) -> bool {
+    return match data {
+        RegistryData::String(text) | RegistryData::ExpandString(text) => !text.is_empty(),
+        RegistryData::Binary(bytes) | RegistryData::Other(_, bytes) => !bytes.is_empty(),
+        RegistryData::MultiString(strings) => !strings.is_empty(),
+        RegistryData::Dword(_) | RegistryData::Qword(_) => true,
+    };
+}
+
+/// Start a finding that points at a key in a hive.
+fn key_finding(
+    file: &Path,
+    hive: &RegistryHive,
+    severity: Severity,
+    description: &str,
+    key_path: &str,
+    key: &RegistryKey,
+) -> Finding {
+    let mut finding = Finding::new(file, severity, description);
+    finding.add_detail("hive", hive.file_name.clone());
+    finding.add_detail("key path", String::from(key_path));
+    finding.add_detail("last written", format_timestamp(key.last_written));
+    return finding;
+}
+
+/// Start a finding that points at a single value in a hive.
+fn value_finding(
+    file: &Path,
+    hive: &RegistryHive,
+    severity: Severity,
+    description: &str,
+    key_path: &str,
+    key: &RegistryKey,
+    value_name: &str,
+) -> Finding {
+    let mut finding = key_finding(file, hive, severity, description, key_path, key);
+    finding.add_detail("value name", String::from(value_name));
+    return finding;
+}
+
+/// Look through a hive for credentials stored by known applications, and
+/// for Group Policy preference passwords left in any string value. The
+/// secrets themselves are never copied into the findings.
+pub(crate) fn registry_credential_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let hive = RegistryHive::from_file(file)?;
+    let mut findings = Vec::new();
+
+    for (path, key) in hive.walk()? {
+        let catalogue = REGISTRY_CREDENTIALS
+            .iter()
+            .filter(|x| key_path_matches(&path, x.key_path));
+        let values = hive.values(&key)?;
+
+        for credential in catalogue {
+            /* Some stores use the value names themselves as the secret. */
+            let secrets = values.iter().filter(|x| {
+                credential.secret_values.is_empty()
+                    || credential
+                        .secret_values
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(&x.name))
+            });
+
+            for (index, secret) in secrets.enumerate().filter(|(_, x)| has_content(&x.data)) {
+                /* When the name is the secret only its position and length are given. */
+                let mut finding = if credential.secret_values.is_empty() {
+                    let mut finding = key_finding(
+                        file,
+                        &hive,
+                        credential.severity,
+                        credential.name,
+                        &path,
+                        &key,
+                    );
+                    finding.add_detail("value index", index.to_string());
+                    finding.add_detail("secret length", secret.name.chars().count().to_string());
+                    finding
+                } else {
+                    value_finding(
+                        file,
+                        &hive,
+                        credential.severity,
+                        credential.name,
+                        &path,
+                        &key,
+                        &secret.name,
+                    )
+                };
+
+                for context in values.iter().filter(|x| {
+                    credential
+                        .context_values
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(&x.name))
+                }) {
+                    if let Some(text) = context.data.as_string() {
+                        finding.add_detail(&context.name, String::from(text));
+                    }
+                }
+                if credential.secret_values.is_empty()
+                    && DEFAULT_SNMP_COMMUNITIES.contains(&secret.name.to_lowercase().as_str())
+                {
+                    finding.add_detail("weakness", String::from("Default community string"));
+                }
+                findings.push(finding);
+            }
+        }
+
+        /* Group Policy preference XML carries passwords under a published key. */
+        for value in values.iter() {
+            let Some(text) = value.data.as_string() else {
+                continue;
+            };
+            if text.to_lowercase().contains("cpassword=\"")
+                && !text.to_lowercase().contains("cpassword=\"\"")
+            {
+                findings.push(value_finding(
+                    file,
+                    &hive,
+                    Severity::High,
+                    "Group Policy preference password",
+                    &path,
+                    &key,
+                    &value.name,
+                ));
+            }
+        }
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_hive() -> RegistryHive {
+        return RegistryHive::from_file(Path::new("./tests/testing_files/registry/SOFTWARE"))
+            .unwrap();
+    }
+
+    #[test]
+    fn read_header() {
+        let hive = sample_hive();
+        assert_eq!(hive.file_name, "emRoot\\System32\\Config\\SOFTWARE");
+        assert!(!hive.is_dirty);
+        assert_eq!(hive.root_key().unwrap().name, "ROOT");
+    }
+
+    #[test]
+    fn root_subkeys() {
+        let hive = sample_hive();
+        let root = hive.root_key().unwrap();
+
+        assert_eq!(
+            hive.subkeys(&root)
+                .unwrap()
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Microsoft", "Many", "Legacy", "Types"]
+        );
+        assert_eq!(root.last_written, 1705526400);
+    }
+
+    #[test]
+    fn open_key_ignores_case() {
+        let hive = sample_hive();
+        let key = hive
+            .open_key("microsoft\\WINDOWS NT\\CurrentVersion")
+            .unwrap()
+            .unwrap();
+        let values = hive.values(&key).unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                RegistryValue {
+                    name: String::from("ProductName"),
+                    data: RegistryData::String(String::from("Windows 10 Pro")),
+                },
+                RegistryValue {
+                    name: String::from("CurrentBuildNumber"),
+                    data: RegistryData::String(String::from("19045")),
+                },
+                RegistryValue {
+                    name: String::from("InstallDate"),
+                    data: RegistryData::Dword(1700000000),
+                },
+                RegistryValue {
+                    name: String::from("SystemRoot"),
+                    data: RegistryData::ExpandString(String::from("%SystemDrive%\\Windows")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn open_missing_key() {
+        assert_eq!(sample_hive().open_key("Microsoft\\Office").unwrap(), None);
+    }
+
+    #[test]
+    fn index_root_and_leaf_lists() {
+        let hive = sample_hive();
+        let many = hive.open_key("Many").unwrap().unwrap();
+        let legacy = hive.open_key("Legacy").unwrap().unwrap();
+
+        assert_eq!(hive.subkeys(&many).unwrap().len(), 6);
+        assert_eq!(hive.subkeys(&legacy).unwrap()[0].name, "Old");
+    }
+
+    #[test]
+    fn value_types() {
+        let hive = sample_hive();
+        let key = hive.open_key("Types").unwrap().unwrap();
+        let values = hive.values(&key).unwrap();
+
+        assert_eq!(values[0].name, "");
+        assert_eq!(values[0].data.as_string(), Some("default value"));
+        assert_eq!(
+            values[1].data,
+            RegistryData::Binary(vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06])
+        );
+        assert_eq!(
+            values[2].data,
+            RegistryData::MultiString(vec![String::from("alpha"), String::from("beta")])
+        );
+        assert_eq!(values[3].data, RegistryData::Qword(1 << 40));
+        assert_eq!(values[4].name, "Café ☕");
+    }
+
+    #[test]
+    fn big_data_value() {
+        let hive = sample_hive();
+        let key = hive.open_key("Types").unwrap().unwrap();
+        let values = hive.values(&key).unwrap();
+        let data = values[5].data.as_bytes().unwrap();
+
+        assert_eq!(data.len(), 20480);
+        assert_eq!(data[16344], (16344 % 256) as u8);
+    }
+
+    #[test]
+    fn walk_every_key() {
+        let keys = sample_hive().walk().unwrap();
+
+        assert_eq!(keys.len(), 13);
+        assert_eq!(keys[0].0, "Microsoft");
+        assert_eq!(keys[2].0, "Microsoft\\Windows NT\\CurrentVersion");
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_hive() {
+        let hive =
+            RegistryHive::from_file(Path::new("./tests/testing_files/registry/truncated.hiv"))
+                .unwrap();
+        hive.walk().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_hive() {
+        RegistryHive::from_file(Path::new(
+            "./tests/testing_files/signiture_match/valid.sqlite",
+        ))
+        .unwrap();
+    }
+
+    fn findings_by_description(file: &str) -> Vec<(String, String)> {
+        return registry_credential_findings(Path::new(file))
+            .unwrap()
+            .iter()
+            .map(|x| {
+                (
+                    x.description.clone(),
+                    x.detail("value name").unwrap().clone(),
+                )
+            })
+            .collect();
+    }
+
+    #[test]
+    fn key_path_patterns() {
+        assert!(key_path_matches(
+            "software\\SimonTatham\\PuTTY\\Sessions\\web",
+            "Software\\SimonTatham\\PuTTY\\Sessions\\*"
+        ));
+        assert!(key_path_matches(
+            "ControlSet002\\Services",
+            "ControlSet*\\Services"
+        ));
+        assert!(!key_path_matches(
+            "Software\\SimonTatham\\PuTTY\\Sessions",
+            "Software\\SimonTatham\\PuTTY\\Sessions\\*"
+        ));
+    }
+
+    #[test]
+    fn user_hive_credentials() {
+        assert_eq!(
+            findings_by_description("./tests/testing_files/registry/NTUSER.DAT"),
+            vec![
+                (
+                    String::from("PuTTY session proxy password"),
+                    String::from("ProxyPassword")
+                ),
+                (
+                    String::from("KiTTY session password"),
+                    String::from("Password")
+                ),
+                (
+                    String::from("WinSCP stored session password"),
+                    String::from("Password")
+                ),
+                (String::from("TightVNC password"), String::from("Password")),
+            ]
+        );
+    }
+
+    #[test]
+    fn finding_details() {
+        let findings =
+            registry_credential_findings(Path::new("./tests/testing_files/registry/NTUSER.DAT"))
+                .unwrap();
+
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(
+            findings[0].detail("key path"),
+            Some(&String::from(
+                "Software\\SimonTatham\\PuTTY\\Sessions\\bastion"
+            ))
+        );
+        assert_eq!(
+            findings[0].detail("ProxyUsername"),
+            Some(&String::from("proxyuser"))
+        );
+        assert!(
+            !findings[0]
+                .details
+                .iter()
+                .any(|(_, value)| value.contains("Pr0xyPass"))
+        );
+    }
+
+    #[test]
+    fn software_hive_credentials() {
+        let findings = registry_credential_findings(Path::new(
+            "./tests/testing_files/registry/SOFTWARE_credentials",
+        ))
+        .unwrap();
+
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].description, "Windows autologon password");
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(
+            findings[0].detail("DefaultUserName"),
+            Some(&String::from("kiosk"))
+        );
+        assert_eq!(findings[1].description, "RealVNC password");
+        assert_eq!(findings[2].description, "Group Policy preference password");
+        assert_eq!(
+            findings[2].detail("value name"),
+            Some(&String::from("DriveMap"))
+        );
+    }
+
+    #[test]
+    fn snmp_communities() {
+        let findings =
+            registry_credential_findings(Path::new("./tests/testing_files/registry/SYSTEM"))
+                .unwrap();
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(
+            findings[0].detail("weakness"),
+            Some(&String::from("Default community string"))
+        );
+        assert_eq!(findings[1].detail("value index"), Some(&String::from("1")));
+        assert_eq!(
+            findings[1].detail("secret length"),
+            Some(&String::from("9"))
+        );
+        assert_eq!(findings[1].detail("weakness"), None);
+        assert!(findings.iter().all(|x| {
+            x.details
+                .iter()
+                .all(|(_, value)| !value.contains("s3cr3t-rw"))
+                && !x.description.contains("s3cr3t-rw")
+        }));
+    }
+
+    #[test]
+    fn no_registry_credentials() {
+        assert!(
+            registry_credential_findings(Path::new("./tests/testing_files/registry/SOFTWARE"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
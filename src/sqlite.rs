@@ -0,0 +1,588 @@
+/*
+ * A read-only SQLite file parser that walks the b-tree pages directly, used to
+ * recognise browser and application credential stores.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::findings::{Finding, Severity};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// A database layout that is known to hold credentials, recognised by the
+/// tables it contains.
+struct CredentialSchema {
+    name: &'static str,
+    tables: &'static [&'static str],
+    credential_table: &'static str,
+    severity: Severity,
+}
+
+const CREDENTIAL_SCHEMAS: [CredentialSchema; 6] = [
+    CredentialSchema {
+        name: "Chromium saved logins",
+        tables: &["logins"],
+        credential_table: "logins",
+        severity: Severity::High,
+    },
+    CredentialSchema {
+        name: "Chromium cookies",
+        tables: &["cookies"],
+        credential_table: "cookies",
+        severity: Severity::Medium,
+    },
+    CredentialSchema {
+        name: "Chromium payment cards",
+        tables: &["credit_cards"],
+        credential_table: "credit_cards",
+        severity: Severity::High,
+    },
+    CredentialSchema {
+        name: "Firefox saved logins",
+        tables: &["moz_logins"],
+        credential_table: "moz_logins",
+        severity: Severity::High,
+    },
+    CredentialSchema {
+        name: "Firefox cookies",
+        tables: &["moz_cookies"],
+        credential_table: "moz_cookies",
+        severity: Severity::Medium,
+    },
+    CredentialSchema {
+        name: "NSS key database",
+        tables: &["metadata", "nssprivate"],
+        credential_table: "nssprivate",
+        severity: Severity::High,
+    },
+];
+
+/// A single value from a database record.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum SqliteValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// A table listed in the schema along with the number of rows it holds.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct SqliteTable {
+    pub(crate) name: String,
+    pub(crate) root_page: u32,
+    pub(crate) row_count: u64,
+}
+
+pub(crate) struct SqliteDatabase<R: Read + Seek> {
+    source: R,
+    page_size: usize,
+    usable_size: usize,
+    page_count: u32,
+}
+
+impl SqliteDatabase<File> {
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return SqliteDatabase::from_reader(File::open(file)?);
+    }
+}
+
+impl SqliteDatabase<Cursor<Vec<u8>>> {
+    pub(crate) fn from_bytes(data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        return SqliteDatabase::from_reader(Cursor::new(data));
+    }
+}
+
+impl<R: Read + Seek> SqliteDatabase<R> {
+    /// Check the database header and work out the page geometry. Pages are
+    /// only read from the source when they are needed.
+    pub(crate) fn from_reader(mut source: R) -> Result<Self, Box<dyn Error>> {
+        let mut header = [0; 100];
+        source.seek(SeekFrom::Start(0))?;
+        source.read_exact(&mut header)?;
+
+        if &header[0..16] != b"SQLite format 3\0" {
+            return Err(Box::new(invalid_data("Not a SQLite database")));
+        }
+
+        /* A page size of one is used to represent 65536. */
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) as usize {
+            1 => 65536,
+            size => size,
+        };
+        if !page_size.is_power_of_two() || page_size < 512 {
+            return Err(Box::new(invalid_data("Invalid SQLite page size")));
+        }
+
+        let file_size = source.seek(SeekFrom::End(0))?;
+        return Ok(SqliteDatabase {
+            source,
+            page_size,
+            usable_size: page_size - header[20] as usize,
+            page_count: (file_size / page_size as u64) as u32,
+        });
+    }
+
+    /// Read a whole page, numbered from one.
+    fn read_page(&mut self, page_num: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+        if page_num == 0 || page_num > self.page_count {
+            return Err(Box::new(invalid_data(&format!(
+                "Page {page_num} is outside the database"
+            ))));
+        }
+        let mut page = vec![0; self.page_size];
+        self.source.seek(SeekFrom::Start(
+            (page_num as u64 - 1) * self.page_size as u64,
+        ))?;
+        self.source.read_exact(&mut page)?;
+        return Ok(page);
+    }
+
+    /// Collect the payload of a table leaf cell, following overflow pages if
+    /// the payload does not fit on the page.
+    fn read_payload(
+        &mut self,
+        page: &[u8],
+        offset: usize,
+        payload_len: usize,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        let min_local = ((usable - 12) * 32 / 255) - 23;
+
+        /* Work out how much of the payload is stored on this page. */
+        let local_len = if payload_len <= max_local {
+            payload_len
+        } else {
+            let spill = min_local + ((payload_len - min_local) % (usable - 4));
+            if spill <= max_local { spill } else { min_local }
+        };
+
+        let mut reader = ByteReader::new(page);
+        reader.seek(offset)?;
+        let mut payload = reader.read_bytes(local_len)?.to_vec();
+
+        if local_len < payload_len {
+            let mut overflow_page = reader.read_u32_be()?;
+            let mut visited = HashSet::new();
+
+            while payload.len() < payload_len && overflow_page != 0 {
+                if !visited.insert(overflow_page) {
+                    return Err(Box::new(invalid_data("Overflow page loop")));
+                }
+                let page = self.read_page(overflow_page)?;
+                let take = std::cmp::min(payload_len - payload.len(), usable - 4);
+                let mut page_reader = ByteReader::new(&page);
+
+                overflow_page = page_reader.read_u32_be()?;
+                payload.extend_from_slice(page_reader.read_bytes(take)?);
+            }
+        }
+        return Ok(payload);
+    }
+
+    /// Visit every row of a table b-tree, returning the payload of each row.
+    fn table_rows(&mut self, root_page: u32) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let mut rows = Vec::new();
+        let mut to_visit = vec![root_page];
+        let mut visited = HashSet::new();
+
+        while let Some(page_num) = to_visit.pop() {
+            if !visited.insert(page_num) {
+                return Err(Box::new(invalid_data("B-tree page loop")));
+            }
+            let page = self.read_page(page_num)?;
+            let header_start = if page_num == 1 { 100 } else { 0 };
+            let mut reader = ByteReader::new(&page);
+            reader.seek(header_start)?;
+
+            let page_type = reader.read_u8()?;
+            reader.skip(2)?;
+            let cell_count = reader.read_u16_be()? as usize;
+            reader.skip(3)?;
+
+            match page_type {
+                0x05 => to_visit.push(reader.read_u32_be()?),
+                0x0D => {}
+                _ => return Err(Box::new(invalid_data("Not a table b-tree page"))),
+            }
+
+            for cell_idx in 0..cell_count {
+                reader
+                    .seek(header_start + if page_type == 0x05 { 12 } else { 8 } + cell_idx * 2)?;
+                let cell_offset = reader.read_u16_be()? as usize;
+                reader.seek(cell_offset)?;
+
+                if page_type == 0x05 {
+                    to_visit.push(reader.read_u32_be()?);
+                    continue;
+                }
+                let payload_len = read_varint(&mut reader)? as usize;
+                read_varint(&mut reader)?;
+                let payload_start = reader.position;
+                rows.push(self.read_payload(&page, payload_start, payload_len)?);
+            }
+        }
+        return Ok(rows);
+    }
+
+    /// Count the entries in a table or index b-tree without decoding them.
+    fn count_entries(&mut self, root_page: u32) -> Result<u64, Box<dyn Error>> {
+        let mut count = 0;
+        let mut to_visit = vec![root_page];
+        let mut visited = HashSet::new();
+
+        while let Some(page_num) = to_visit.pop() {
+            if !visited.insert(page_num) {
+                return Err(Box::new(invalid_data("B-tree page loop")));
+            }
+            let page = self.read_page(page_num)?;
+            let header_start = if page_num == 1 { 100 } else { 0 };
+            let mut reader = ByteReader::new(&page);
+            reader.seek(header_start)?;
+
+            let page_type = reader.read_u8()?;
+            reader.skip(2)?;
+            let cell_count = reader.read_u16_be()? as usize;
+            reader.skip(3)?;
+
+            match page_type {
+                /* Leaf pages hold one entry per cell. */
+                0x0A | 0x0D => count += cell_count as u64,
+                /* Interior index cells are entries themselves, interior table
+                 * cells only hold keys. */
+                0x02 | 0x05 => {
+                    if page_type == 0x02 {
+                        count += cell_count as u64;
+                    }
+                    to_visit.push(reader.read_u32_be()?);
+
+                    for cell_idx in 0..cell_count {
+                        reader.seek(header_start + 12 + cell_idx * 2)?;
+                        let cell_offset = reader.read_u16_be()? as usize;
+                        reader.seek(cell_offset)?;
+                        to_visit.push(reader.read_u32_be()?);
+                    }
+                }
+                _ => return Err(Box::new(invalid_data("Not a b-tree page"))),
+            }
+        }
+        return Ok(count);
+    }
+
+    /// List every table in the database with its row count.
+    pub(crate) fn tables(&mut self) -> Result<Vec<SqliteTable>, Box<dyn Error>> {
+        let mut tables = Vec::new();
+
+        /* The schema table always has its root on the first page. */
+        for row in self.table_rows(1)? {
+            let values = decode_record(&row)?;
+
+            let (Some(SqliteValue::Text(row_type)), Some(SqliteValue::Text(name))) =
+                (values.first(), values.get(1))
+            else {
+                continue;
+            };
+            let Some(SqliteValue::Integer(root_page)) = values.get(3) else {
+                continue;
+            };
+
+            /* Virtual tables have no b-tree of their own. */
+            if row_type != "table" || *root_page <= 0 {
+                continue;
+            }
+            tables.push(SqliteTable {
+                name: name.clone(),
+                root_page: *root_page as u32,
+                row_count: self.count_entries(*root_page as u32)?,
+            });
+        }
+        return Ok(tables);
+    }
+
+    /// Decode every row of a table, matching its name case-insensitively.
+    pub(crate) fn rows(&mut self, table: &str) -> Result<Vec<Vec<SqliteValue>>, Box<dyn Error>> {
+        let Some(root_page) = self
+            .tables()?
+            .into_iter()
+            .find(|x| x.name.eq_ignore_ascii_case(table))
+            .map(|x| x.root_page)
+        else {
+            return Err(Box::new(invalid_data(&format!("No table named {table}"))));
+        };
+
+        let mut rows = Vec::new();
+        for row in self.table_rows(root_page)? {
+            rows.push(decode_record(&row)?);
+        }
+        return Ok(rows);
+    }
+}
+
+/// Read a big-endian variable length integer of up to nine bytes.
+fn read_varint(reader: &mut ByteReader) -> Result<u64, Box<dyn Error>> {
+    let mut value: u64 = 0;
+
+    for byte_idx in 0..9 {
+        let byte = reader.read_u8()?;
+
+        /* The ninth byte contributes all eight of its bits. */
+        if byte_idx == 8 {
+            return Ok((value << 8) | byte as u64);
+        }
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    return Ok(value);
+}
+
+/// Decode a record into its column values.
+fn decode_record(payload: &[u8]) -> Result<Vec<SqliteValue>, Box<dyn Error>> {
+    let mut header = ByteReader::new(payload);
+    let header_len = read_varint(&mut header)? as usize;
+    let mut serial_types = Vec::new();
+
+    while header.position < header_len {
+        serial_types.push(read_varint(&mut header)?);
+    }
+
+    let mut body = ByteReader::new(payload);
+    body.seek(header_len)?;
+    let mut values = Vec::new();
+
+    for serial_type in serial_types {
+        let value = match serial_type {
+            0 => SqliteValue::Null,
+            1..=6 => {
+                let size = [1, 2, 3, 4, 6, 8][serial_type as usize - 1];
+                let bytes = body.read_bytes(size)?;
+
+                /* Sign extend from the most significant byte. */
+                let mut int_val: i64 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+                for byte in bytes {
+                    int_val = (int_val << 8) | *byte as i64;
+                }
+                SqliteValue::Integer(int_val)
+            }
+            7 => SqliteValue::Real(f64::from_bits(body.read_u64_be()?)),
+            8 => SqliteValue::Integer(0),
+            9 => SqliteValue::Integer(1),
+            10 | 11 => return Err(Box::new(invalid_data("Reserved record serial type"))),
+            _ if serial_type % 2 == 0 => {
+                SqliteValue::Blob(body.read_bytes((serial_type as usize - 12) / 2)?.to_vec())
+            }
+            _ => SqliteValue::Text(
+                String::from_utf8_lossy(body.read_bytes((serial_type as usize - 13) / 2)?)
+                    .to_string(),
+            ),
+        };
+        values.push(value);
+    }
+    return Ok(values);
+}
+
+/// Report each known credential store layout found in a SQLite database with
+/// the number of credentials it holds.
+pub(crate) fn sqlite_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let tables = SqliteDatabase::from_file(file)?.tables()?;
+    let mut findings = Vec::new();
+
+    let row_count = |name: &str| {
+        tables
+            .iter()
+            .find(|x| x.name.to_lowercase() == name)
+            .map(|x| x.row_count)
+    };
+
+    for schema in CREDENTIAL_SCHEMAS.iter() {
+        if !schema.tables.iter().all(|x| row_count(x).is_some()) {
+            continue;
+        }
+        let credentials = row_count(schema.credential_table).unwrap_or(0);
+
+        /* An empty store is worth knowing about but is not a leak. */
+        let severity = if credentials == 0 {
+            Severity::Info
+        } else {
+            schema.severity
+        };
+
+        let mut finding = Finding::new(file, severity, schema.name);
+        finding.add_detail("stored credentials", credentials.to_string());
+        for table in tables.iter() {
+            finding.add_detail(
+                "table",
+                format!("{} ({} rows)", table.name, table.row_count),
+            );
+        }
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_counts(file: &str) -> Vec<(String, u64)> {
+        return SqliteDatabase::from_file(Path::new(file))
+            .unwrap()
+            .tables()
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.name, x.row_count))
+            .collect();
+    }
+
+    #[test]
+    fn varint_single_byte() {
+        let data = vec![0x7F];
+        assert_eq!(read_varint(&mut ByteReader::new(&data)).unwrap(), 0x7F);
+    }
+
+    #[test]
+    fn varint_multi_byte() {
+        let data = vec![0x81, 0x00];
+        assert_eq!(read_varint(&mut ByteReader::new(&data)).unwrap(), 0x80);
+    }
+
+    #[test]
+    fn varint_nine_bytes() {
+        let data = vec![0xFF; 9];
+        assert_eq!(read_varint(&mut ByteReader::new(&data)).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn decode_mixed_record() {
+        assert_eq!(
+            decode_record(&vec![0x06, 0x00, 0x01, 0x08, 0x0F, 0x0E, 0xFF, b'a', 0x01]).unwrap(),
+            vec![
+                SqliteValue::Null,
+                SqliteValue::Integer(-1),
+                SqliteValue::Integer(0),
+                SqliteValue::Text(String::from("a")),
+                SqliteValue::Blob(vec![0x01]),
+            ]
+        );
+    }
+
+    #[test]
+    fn login_data_tables() {
+        assert_eq!(
+            table_counts("./tests/testing_files/sqlite/Login Data"),
+            vec![(String::from("meta"), 2), (String::from("logins"), 250)]
+        );
+    }
+
+    #[test]
+    fn cookies_tables() {
+        assert_eq!(
+            table_counts("./tests/testing_files/sqlite/Cookies"),
+            vec![(String::from("cookies"), 12)]
+        );
+    }
+
+    #[test]
+    fn cookies_rows() {
+        let rows = SqliteDatabase::from_file(Path::new("./tests/testing_files/sqlite/Cookies"))
+            .unwrap()
+            .rows("COOKIES")
+            .unwrap();
+        assert_eq!(rows.len(), 12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rows_of_missing_table() {
+        SqliteDatabase::from_file(Path::new("./tests/testing_files/sqlite/Cookies"))
+            .unwrap()
+            .rows("logins")
+            .unwrap();
+    }
+
+    #[test]
+    fn without_rowid_tables() {
+        assert_eq!(
+            table_counts("./tests/testing_files/sqlite/cache.db"),
+            vec![
+                (String::from("entries"), 300),
+                (String::from("settings"), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn login_data_finding() {
+        let findings =
+            sqlite_findings(Path::new("./tests/testing_files/sqlite/Login Data")).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].description, "Chromium saved logins");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(
+            findings[0].detail("stored credentials"),
+            Some(&String::from("250"))
+        );
+    }
+
+    #[test]
+    fn key4_finding() {
+        let findings = sqlite_findings(Path::new("./tests/testing_files/sqlite/key4.db")).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].description, "NSS key database");
+        assert_eq!(
+            findings[0].detail("stored credentials"),
+            Some(&String::from("1"))
+        );
+    }
+
+    #[test]
+    fn empty_store_finding() {
+        let findings = sqlite_findings(Path::new(
+            "./tests/testing_files/sqlite/empty_logins.sqlite",
+        ))
+        .unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn unrelated_database() {
+        assert!(
+            sqlite_findings(Path::new("./tests/testing_files/sqlite/cache.db"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_database() {
+        SqliteDatabase::from_file(Path::new("./tests/testing_files/sqlite/truncated.sqlite"))
+            .unwrap()
+            .tables()
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_database() {
+        SqliteDatabase::from_file(Path::new(
+            "./tests/testing_files/read_file_header/text_file.bib",
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn database_too_short() {
+        SqliteDatabase::from_bytes(b"SQLite format 3\0".to_vec()).unwrap();
+    }
+}
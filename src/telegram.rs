@@ -0,0 +1,377 @@
+/*
+ * Recognise Telegram Desktop `tdata` profile directories and judge whether the
+ * sessions inside could be taken over simply by copying them. Only the local
+ * key is ever tried, with an empty passcode; nothing it protects is opened.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::crypto::{Aes256, pbkdf2_sha512, sha1, sha512};
+use crate::file_signatures::FileSigniture;
+use crate::findings::{Finding, Severity};
+use crate::os_interactions::{all_files, read_file_header};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// The file holding the encrypted local key that protects every other file.
+const KEY_FILE: &str = "key_datas";
+
+/// Files with this suffix are the most recently written safe copy.
+const SAFE_FILE_SUFFIX: char = 's';
+
+/// Account folders are named with 16 upper case hex digits, such as
+/// D877F783D5D3EF8C for the first account.
+const ACCOUNT_NAME_LEN: usize = 16;
+
+const LOCAL_SALT_SIZE: usize = 32;
+
+/// Telegram uses a single PBKDF2 round when no passcode is set.
+const NO_PASSCODE_ITERATIONS: u32 = 1;
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct TelegramProfile {
+    pub(crate) directory: PathBuf,
+    /// Accounts with both their key file and their folder of maps.
+    pub(crate) accounts: Vec<String>,
+    /// Accounts missing the files needed to restore the session.
+    pub(crate) incomplete_accounts: Vec<String>,
+    /// Whether a local passcode is set, or `None` when the key file could
+    /// not be read.
+    pub(crate) passcode_set: Option<bool>,
+    pub(crate) tdf_files: Vec<PathBuf>,
+}
+
+impl TelegramProfile {
+    pub(crate) fn from_directory(directory: &Path) -> Result<Self, Box<dyn Error>> {
+        let passcode_set = passcode_is_set(&directory.join(KEY_FILE)).ok();
+
+        let mut accounts = Vec::new();
+        let mut incomplete_accounts = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let Some(account) = name.strip_suffix(SAFE_FILE_SUFFIX) else {
+                continue;
+            };
+            if account.len() != ACCOUNT_NAME_LEN
+                || !account.chars().all(|x| x.is_ascii_hexdigit())
+                || !path.is_file()
+            {
+                continue;
+            }
+
+            if directory.join(account).join("maps").is_file() {
+                accounts.push(String::from(account));
+            } else {
+                incomplete_accounts.push(String::from(account));
+            }
+        }
+        accounts.sort();
+        incomplete_accounts.sort();
+
+        let mut tdf_files: Vec<PathBuf> = all_files(directory)?
+            .into_iter()
+            .filter(|x| {
+                read_file_header(x).is_ok_and(|header| {
+                    matches!(
+                        FileSigniture::from_bytes(&header),
+                        FileSigniture::TelegramDesktopFile
+                            | FileSigniture::TelegramDesktopEncryptedFile
+                    )
+                })
+            })
+            .collect();
+        tdf_files.sort();
+
+        return Ok(TelegramProfile {
+            directory: directory.to_path_buf(),
+            accounts,
+            incomplete_accounts,
+            passcode_set,
+            tdf_files,
+        });
+    }
+
+    /// Does the profile hold everything needed to restore a session.
+    pub(crate) fn is_complete(&self) -> bool {
+        return self.passcode_set.is_some() && !self.accounts.is_empty();
+    }
+}
+
+/// Read the payload of a TDF file, dropping the magic, version and the MD5
+/// checksum that follows the data.
+fn read_tdf(file: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = std::fs::read(file)?;
+    if data.len() < 24 || &data[..4] != b"TDF$" {
+        return Err(Box::new(invalid_data("Not a Telegram Desktop file")));
+    }
+    return Ok(data[8..data.len() - 16].to_vec());
+}
+
+/// Read a Qt `QByteArray`, which is prefixed by a big endian length.
+fn read_byte_array<'a>(reader: &mut ByteReader<'a>) -> Result<&'a [u8], Box<dyn Error>> {
+    let length = reader.read_u32_be()?;
+    if length == 0xFFFF_FFFF {
+        return Ok(&[]);
+    }
+    return Ok(reader.read_bytes(length as usize)?);
+}
+
+/// Turn a passcode into the 256 byte key that protects the local key.
+fn create_local_key(passcode: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+    let mut salted = salt.to_vec();
+    salted.extend_from_slice(passcode);
+    salted.extend_from_slice(salt);
+    return pbkdf2_sha512(&sha512(&salted), salt, iterations, 256);
+}
+
+/// Derive the AES key and IGE initialisation vector for a message key in the
+/// way MTProto 1.0 did for incoming messages.
+fn prepare_aes(auth_key: &[u8], msg_key: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let x = 8;
+    let hash = |parts: &[&[u8]]| sha1(&parts.concat());
+
+    let sha1_a = hash(&[msg_key, &auth_key[x..x + 32]]);
+    let sha1_b = hash(&[
+        &auth_key[32 + x..48 + x],
+        msg_key,
+        &auth_key[48 + x..64 + x],
+    ]);
+    let sha1_c = hash(&[&auth_key[64 + x..96 + x], msg_key]);
+    let sha1_d = hash(&[msg_key, &auth_key[96 + x..128 + x]]);
+
+    let mut aes_key = [0u8; 32];
+    aes_key[..8].copy_from_slice(&sha1_a[..8]);
+    aes_key[8..20].copy_from_slice(&sha1_b[8..20]);
+    aes_key[20..].copy_from_slice(&sha1_c[4..16]);
+
+    let mut aes_iv = [0u8; 32];
+    aes_iv[..12].copy_from_slice(&sha1_a[8..20]);
+    aes_iv[12..20].copy_from_slice(&sha1_b[..8]);
+    aes_iv[20..24].copy_from_slice(&sha1_c[16..20]);
+    aes_iv[24..].copy_from_slice(&sha1_d[..8]);
+    return (aes_key, aes_iv);
+}
+
+/// Decrypt data in AES infinite garble extension mode.
+fn aes_ige_decrypt(data: &[u8], key: &[u8; 32], iv: &[u8; 32]) -> Vec<u8> {
+    let cipher = Aes256::new(key);
+    let mut previous_cipher: [u8; 16] = iv[..16].try_into().unwrap_or([0; 16]);
+    let mut previous_plain: [u8; 16] = iv[16..].try_into().unwrap_or([0; 16]);
+    let mut decrypted = Vec::with_capacity(data.len());
+
+    for block in data.chunks_exact(16) {
+        let mut input = [0u8; 16];
+        for idx in 0..16 {
+            input[idx] = block[idx] ^ previous_plain[idx];
+        }
+        let mut plain = cipher.decrypt_block(&input);
+        for idx in 0..16 {
+            plain[idx] ^= previous_cipher[idx];
+        }
+
+        previous_cipher.copy_from_slice(block);
+        previous_plain = plain;
+        decrypted.extend_from_slice(&plain);
+    }
+    return decrypted;
+}
+
+/// Decrypt a block of local storage, returning `None` when the key is wrong.
+/// The first 16 bytes are a SHA-1 based message key that doubles as a check.
+fn decrypt_local(encrypted: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+    if encrypted.len() <= 16 || !encrypted.len().is_multiple_of(16) || key.len() < 136 {
+        return None;
+    }
+
+    let (msg_key, data) = encrypted.split_at(16);
+    let (aes_key, aes_iv) = prepare_aes(key, msg_key);
+    let decrypted = aes_ige_decrypt(data, &aes_key, &aes_iv);
+
+    if sha1(&decrypted)[..16] != *msg_key {
+        return None;
+    }
+    let length = u32::from_le_bytes(decrypted[..4].try_into().ok()?) as usize;
+    if length > decrypted.len() || length + 16 <= decrypted.len() || length < 4 {
+        return None;
+    }
+    return Some(decrypted[4..length].to_vec());
+}
+
+/// Check whether the local key can be opened with an empty passcode, which
+/// means anyone with a copy of the profile can use the session. The key
+/// itself is dropped at once and the account data it protects is left
+/// encrypted.
+fn passcode_is_set(file: &Path) -> Result<bool, Box<dyn Error>> {
+    let payload = read_tdf(file)?;
+    let mut reader = ByteReader::new(&payload);
+
+    let salt = read_byte_array(&mut reader)?;
+    let key_encrypted = read_byte_array(&mut reader)?;
+    if salt.len() != LOCAL_SALT_SIZE {
+        return Err(Box::new(invalid_data("Unexpected Telegram salt size")));
+    }
+
+    let passcode_key = create_local_key(&[], salt, NO_PASSCODE_ITERATIONS);
+    return Ok(decrypt_local(key_encrypted, &passcode_key).is_none());
+}
+
+/// Report each Telegram Desktop profile below a directory as a single
+/// finding, however many TDF files it is made of.
+pub(crate) fn telegram_session_findings(directory: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let mut profile_directories: Vec<PathBuf> = all_files(directory)?
+        .iter()
+        .filter(|x| x.file_name().is_some_and(|name| name == KEY_FILE))
+        .filter_map(|x| x.parent().map(Path::to_path_buf))
+        .collect();
+    profile_directories.sort();
+    profile_directories.dedup();
+
+    let mut findings = Vec::new();
+    for profile_directory in profile_directories {
+        let profile = TelegramProfile::from_directory(&profile_directory)?;
+
+        let mut finding = match (profile.is_complete(), profile.passcode_set) {
+            (true, Some(false)) => Finding::new(
+                &profile.directory,
+                Severity::Critical,
+                "Telegram Desktop session without a local passcode",
+            ),
+            (true, _) => Finding::new(
+                &profile.directory,
+                Severity::Medium,
+                "Passcode protected Telegram Desktop session",
+            ),
+            (false, _) => Finding::new(
+                &profile.directory,
+                Severity::Low,
+                "Incomplete Telegram Desktop profile",
+            ),
+        };
+
+        finding.add_detail(
+            "passcode",
+            String::from(match profile.passcode_set {
+                Some(true) => "set",
+                Some(false) => "not set",
+                None => "unknown",
+            }),
+        );
+        for account in &profile.accounts {
+            finding.add_detail("account", account.clone());
+        }
+        for account in &profile.incomplete_accounts {
+            finding.add_detail("incomplete account", account.clone());
+        }
+        finding.add_detail("tdf files", profile.tdf_files.len().to_string());
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_without_passcode() {
+        let profile = TelegramProfile::from_directory(Path::new(
+            "./tests/testing_files/telegram/open_profile/tdata",
+        ))
+        .unwrap();
+
+        assert_eq!(profile.passcode_set, Some(false));
+        assert_eq!(profile.accounts, vec![String::from("D877F783D5D3EF8C")]);
+        assert!(profile.incomplete_accounts.is_empty());
+        assert_eq!(profile.tdf_files.len(), 5);
+        assert!(profile.is_complete());
+    }
+
+    #[test]
+    fn profile_with_passcode() {
+        let profile = TelegramProfile::from_directory(Path::new(
+            "./tests/testing_files/telegram/locked_profile/tdata",
+        ))
+        .unwrap();
+
+        assert_eq!(profile.passcode_set, Some(true));
+        assert_eq!(
+            profile.accounts,
+            vec![
+                String::from("A7FDF864FBC10B77"),
+                String::from("D877F783D5D3EF8C")
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_missing_maps() {
+        let profile = TelegramProfile::from_directory(Path::new(
+            "./tests/testing_files/telegram/partial_profile/tdata",
+        ))
+        .unwrap();
+
+        assert!(profile.accounts.is_empty());
+        assert_eq!(
+            profile.incomplete_accounts,
+            vec![String::from("D877F783D5D3EF8C")]
+        );
+        assert!(!profile.is_complete());
+    }
+
+    #[test]
+    fn one_finding_per_profile() {
+        let findings =
+            telegram_session_findings(Path::new("./tests/testing_files/telegram")).unwrap();
+
+        assert_eq!(
+            findings
+                .iter()
+                .map(|x| (x.description.as_str(), x.severity))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "Passcode protected Telegram Desktop session",
+                    Severity::Medium
+                ),
+                (
+                    "Telegram Desktop session without a local passcode",
+                    Severity::Critical
+                ),
+                ("Incomplete Telegram Desktop profile", Severity::Low),
+            ]
+        );
+        assert_eq!(findings[0].detail("tdf files"), Some(&String::from("8")));
+        assert_eq!(
+            findings[1].detail("passcode"),
+            Some(&String::from("not set"))
+        );
+        assert_eq!(findings[1].detail("logged in accounts"), None);
+    }
+
+    #[test]
+    fn wrong_key_is_rejected() {
+        let payload = read_tdf(Path::new(
+            "./tests/testing_files/telegram/open_profile/tdata/key_datas",
+        ))
+        .unwrap();
+        let mut reader = ByteReader::new(&payload);
+        let salt = read_byte_array(&mut reader).unwrap();
+        let key_encrypted = read_byte_array(&mut reader).unwrap();
+
+        assert!(decrypt_local(key_encrypted, &create_local_key(&[], salt, 1)).is_some());
+        assert!(decrypt_local(key_encrypted, &create_local_key(b"guess", salt, 1)).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_tdf_file() {
+        read_tdf(Path::new(
+            "./tests/testing_files/telegram/open_profile/tdata/usertag",
+        ))
+        .unwrap();
+    }
+}
@@ -0,0 +1,310 @@
+/*
+ * Fingerprint text files by their character distribution to tell what kind
+ * of text they hold.
+ */
+
+use crate::byte_stats::ByteStats;
+use crate::findings::{Finding, Severity};
+use std::error::Error;
+use std::path::Path;
+
+/// Files with fewer ASCII characters than this are too short to fingerprint.
+const MIN_CHARACTERS: usize = 256;
+
+/// Bytes read from across a large file to fingerprint it.
+const SAMPLE_SIZE: u64 = 1024 * 1024;
+
+/// Text files are almost all printable ASCII and whitespace.
+const MIN_PRINTABLE_RATIO: f64 = 0.95;
+
+/// Fingerprints further than this from every reference are left unknown.
+const MAX_DISTANCE: f64 = 0.3;
+
+pub(crate) const CHAR_GROUPS: usize = 12;
+
+/* Reference fingerprints, measured from licence texts and prose; Rust and
+Python sources; files from /etc; random bytes as wrapped base64; and random
+bytes as hex strings and xxd dumps. */
+const PROSE_PROFILE: [f64; CHAR_GROUPS] = [
+    0.2163, 0.5097, 0.0123, 0.0329, 0.0031, 0.1792, 0.0192, 0.0207, 0.0054, 0.0005, 0.0008, 0.0,
+];
+const SOURCE_CODE_PROFILE: [f64; CHAR_GROUPS] = [
+    0.1666, 0.3325, 0.0141, 0.0253, 0.0171, 0.2882, 0.0282, 0.0458, 0.0620, 0.0166, 0.0037, 0.0,
+];
+const CONFIG_PROFILE: [f64; CHAR_GROUPS] = [
+    0.1785, 0.3784, 0.0258, 0.0454, 0.0578, 0.1507, 0.0358, 0.0501, 0.0582, 0.0082, 0.0112, 0.0,
+];
+const BASE64_PROFILE: [f64; CHAR_GROUPS] = [
+    0.0918, 0.3106, 0.0930, 0.3071, 0.1533, 0.0, 0.0130, 0.0, 0.0, 0.0, 0.0313, 0.0,
+];
+const HEX_PROFILE: [f64; CHAR_GROUPS] = [
+    0.2509, 0.0127, 0.0038, 0.0127, 0.4720, 0.1014, 0.0149, 0.1056, 0.0134, 0.0114, 0.0012, 0.0,
+];
+
+/// The group of characters an ASCII code counts towards. Hex letters are
+/// kept apart from the rest of the alphabet so hex stands out from prose,
+/// and the characters base64 adds to the alphanumerics have their own group.
+fn char_group(code: usize) -> usize {
+    let chr_t = code as u8 as char;
+    return match chr_t {
+        'a'..='f' => 0,
+        'g'..='z' => 1,
+        'A'..='F' => 2,
+        'G'..='Z' => 3,
+        '0'..='9' => 4,
+        ' ' | '\t' => 5,
+        '\r' | '\n' => 6,
+        '.' | ',' | '\'' | '"' | '!' | '?' | '-' => 7,
+        '(' | ')' | '[' | ']' | '{' | '}' | ';' | '<' | '>' | '_' | '*' | '&' | '|' | '#' | '@'
+        | '$' | '%' | '^' | '~' | '`' | '\\' => 8,
+        ':' | '=' => 9,
+        '+' | '/' => 10,
+        _ => 11,
+    };
+}
+
+/// The kinds of text a file can be fingerprinted as.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum TextKind {
+    Prose,
+    SourceCode,
+    Config,
+    Base64,
+    Hex,
+}
+
+impl TextKind {
+    const ALL: [TextKind; 5] = [
+        TextKind::Prose,
+        TextKind::SourceCode,
+        TextKind::Config,
+        TextKind::Base64,
+        TextKind::Hex,
+    ];
+
+    fn profile(&self) -> &'static [f64; CHAR_GROUPS] {
+        return match self {
+            TextKind::Prose => &PROSE_PROFILE,
+            TextKind::SourceCode => &SOURCE_CODE_PROFILE,
+            TextKind::Config => &CONFIG_PROFILE,
+            TextKind::Base64 => &BASE64_PROFILE,
+            TextKind::Hex => &HEX_PROFILE,
+        };
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        return match self {
+            TextKind::Prose => "prose",
+            TextKind::SourceCode => "source code",
+            TextKind::Config => "configuration",
+            TextKind::Base64 => "base64",
+            TextKind::Hex => "hex",
+        };
+    }
+
+    /// Is text of this kind encoded binary data rather than something a
+    /// person wrote.
+    pub(crate) fn is_encoded(&self) -> bool {
+        return matches!(self, TextKind::Base64 | TextKind::Hex);
+    }
+}
+
+/// The fraction of a file's ASCII characters in each character group.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct TextFingerprint {
+    pub(crate) characters: usize,
+    pub(crate) groups: [f64; CHAR_GROUPS],
+}
+
+impl TextFingerprint {
+    /// Build a fingerprint from ASCII character counts, as `file_char_cnt`
+    /// produces. Byte counts above 127 are ignored.
+    pub(crate) fn from_counts(counts: &[usize]) -> Self {
+        let mut groups = [0.0; CHAR_GROUPS];
+        for (code, count) in counts.iter().enumerate().take(128) {
+            groups[char_group(code)] += *count as f64;
+        }
+        let characters = counts.iter().take(128).sum::<usize>();
+        if characters > 0 {
+            groups.iter_mut().for_each(|x| *x /= characters as f64);
+        }
+        return TextFingerprint { characters, groups };
+    }
+
+    /// The Jensen-Shannon distance to a reference, from 0 for the same
+    /// distribution to 1 for distributions with nothing in common.
+    pub(crate) fn distance(&self, reference: &[f64; CHAR_GROUPS]) -> f64 {
+        let kl = |p: &[f64], m: &[f64]| -> f64 {
+            return p
+                .iter()
+                .zip(m.iter())
+                .filter(|(x, _)| **x > 0.0)
+                .map(|(x, y)| x * (x / y).log2())
+                .sum();
+        };
+        let mid: Vec<f64> = self
+            .groups
+            .iter()
+            .zip(reference.iter())
+            .map(|(x, y)| (x + y) / 2.0)
+            .collect();
+        let divergence = (kl(&self.groups, &mid) + kl(reference, &mid)) / 2.0;
+        return divergence.max(0.0).sqrt();
+    }
+
+    /// The closest reference kind of text and its distance, or None when the
+    /// text is too short or unlike all of them.
+    pub(crate) fn classify(&self) -> Option<(TextKind, f64)> {
+        if self.characters < MIN_CHARACTERS {
+            return None;
+        }
+        return TextKind::ALL
+            .iter()
+            .map(|x| (*x, self.distance(x.profile())))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, distance)| *distance <= MAX_DISTANCE);
+    }
+}
+
+/// Report text files that are mostly base64 or hex, which hide keys and
+/// other binary data behind ordinary looking extensions. Files that are not
+/// mostly ASCII text are skipped.
+pub(crate) fn text_profile_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let stats = ByteStats::from_file(file, Some(SAMPLE_SIZE))?;
+    if stats.printable_ratio() < MIN_PRINTABLE_RATIO {
+        return Ok(Vec::new());
+    }
+
+    let fingerprint = TextFingerprint::from_counts(&stats.counts);
+    let Some((kind, distance)) = fingerprint.classify() else {
+        return Ok(Vec::new());
+    };
+    if !kind.is_encoded() {
+        return Ok(Vec::new());
+    }
+
+    let mut finding = Finding::new(file, Severity::Medium, "Text file of encoded data");
+    finding.add_detail("text type", String::from(kind.description()));
+    finding.add_detail("distance", format!("{:.3}", distance));
+    finding.add_detail("characters", fingerprint.characters.to_string());
+    return Ok(vec![finding]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os_interactions::file_char_cnt;
+
+    const TEXT_PROFILE: &str = "./tests/testing_files/text_profile";
+
+    fn classify(file: &Path) -> Option<TextKind> {
+        return TextFingerprint::from_counts(&file_char_cnt(file).unwrap())
+            .classify()
+            .map(|(kind, _)| kind);
+    }
+
+    #[test]
+    fn reference_profiles_sum_to_one() {
+        for kind in TextKind::ALL {
+            assert!((kind.profile().iter().sum::<f64>() - 1.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn distances() {
+        let fingerprint = TextFingerprint {
+            characters: 1000,
+            groups: PROSE_PROFILE,
+        };
+        assert_eq!(fingerprint.distance(&PROSE_PROFILE), 0.0);
+        assert!(fingerprint.distance(&HEX_PROFILE) > fingerprint.distance(&CONFIG_PROFILE));
+
+        let mut only_digits = [0.0; CHAR_GROUPS];
+        only_digits[4] = 1.0;
+        let mut only_spaces = [0.0; CHAR_GROUPS];
+        only_spaces[5] = 1.0;
+        let fingerprint = TextFingerprint {
+            characters: 1000,
+            groups: only_digits,
+        };
+        assert_eq!(fingerprint.distance(&only_spaces), 1.0);
+    }
+
+    #[test]
+    fn groups_from_counts() {
+        let mut counts = vec![0; 128];
+        counts['a' as usize] = 3;
+        counts['Z' as usize] = 1;
+        let fingerprint = TextFingerprint::from_counts(&counts);
+        assert_eq!(fingerprint.characters, 4);
+        assert_eq!(fingerprint.groups[0], 0.75);
+        assert_eq!(fingerprint.groups[3], 0.25);
+    }
+
+    #[test]
+    fn classify_prose() {
+        assert_eq!(
+            classify(Path::new(
+                "./tests/testing_files/file_char_freq/ascii_file_1.txt"
+            )),
+            Some(TextKind::Prose)
+        );
+    }
+
+    #[test]
+    fn classify_source_code() {
+        assert_eq!(
+            classify(&Path::new(TEXT_PROFILE).join("parser.py")),
+            Some(TextKind::SourceCode)
+        );
+    }
+
+    #[test]
+    fn too_short() {
+        assert_eq!(
+            classify(Path::new(
+                "./tests/testing_files/file_char_freq/empty_file.txt"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn base64_key_bundle() {
+        let found = text_profile_findings(&Path::new(TEXT_PROFILE).join("notes.txt")).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].severity, Severity::Medium);
+        assert_eq!(found[0].detail("text type"), Some(&String::from("base64")));
+    }
+
+    #[test]
+    fn hex_dump() {
+        let found = text_profile_findings(&Path::new(TEXT_PROFILE).join("dump.log")).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].detail("text type"), Some(&String::from("hex")));
+    }
+
+    #[test]
+    fn config_not_reported() {
+        assert!(
+            text_profile_findings(&Path::new(TEXT_PROFILE).join("settings.conf"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn binary_file_skipped() {
+        assert!(
+            text_profile_findings(Path::new("./tests/testing_files/entropy/backup.hc"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_file() {
+        text_profile_findings(&Path::new(TEXT_PROFILE).join("missing.txt")).unwrap();
+    }
+}
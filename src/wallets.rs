@@ -0,0 +1,833 @@
+/*
+ * Recognise cryptocurrency wallets other than MultiBit: Bitcoin Core wallet
+ * files, Electrum and Ethereum keystores, and the directories Exodus, Atomic
+ * and MetaMask keep their vaults in. Only the layout and protection of each
+ * wallet is read, never the keys.
+ */
+
+use crate::byte_reader::{ByteReader, invalid_data};
+use crate::encoding::base64_decode;
+use crate::findings::{Finding, Severity};
+use crate::json::JsonValue;
+use crate::os_interactions::all_files;
+use crate::sqlite::{SqliteDatabase, SqliteValue};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+const BERKELEY_DB_BTREE_MAGIC: u32 = 0x00053162;
+
+const BERKELEY_DB_LEAF_PAGE: u8 = 5;
+
+/// Items of this type hold their bytes inline rather than on overflow pages.
+const BERKELEY_DB_KEY_DATA: u8 = 1;
+
+/// The size of the header at the start of every Berkeley DB page.
+const BERKELEY_DB_PAGE_HEADER: usize = 26;
+
+/// Record types that only appear in Bitcoin Core wallets.
+const BITCOIN_RECORD_TYPES: [&str; 12] = [
+    "minversion",
+    "version",
+    "key",
+    "wkey",
+    "ckey",
+    "mkey",
+    "name",
+    "hdchain",
+    "bestblock",
+    "walletdescriptor",
+    "walletdescriptorkey",
+    "walletdescriptorckey",
+];
+
+/// Bitcoin Core never calibrates its master key derivation below this many
+/// SHA-512 rounds.
+const BITCOIN_MIN_DERIVE_ITERATIONS: u32 = 25000;
+
+/// The scrypt cost and PBKDF2 rounds geth uses for standard keystores. Its
+/// "light" settings are far cheaper to brute force.
+const ETHEREUM_STANDARD_SCRYPT_N: f64 = 262144.0;
+const ETHEREUM_STANDARD_PBKDF2_ROUNDS: f64 = 262144.0;
+
+/// Electrum files encrypted as a whole are base64 of an ECIES message.
+const ELECTRUM_ENCRYPTED_MAGIC: &[u8] = b"BIE1";
+
+/// Exodus keeps each encrypted file in a SECO container.
+const EXODUS_SECO_MAGIC: &[u8] = b"SECO";
+
+/// The MetaMask extension IDs in the Chrome and Edge web stores.
+const METAMASK_EXTENSION_IDS: [(&str, &str); 2] = [
+    ("nkbihfbeogaeaoehlefnkodbefgpgknn", "Chrome"),
+    ("ejbalbakoplchlghecdalmeeeajnimhm", "Edge"),
+];
+
+/// Vaults created before MetaMask recorded key metadata use this many
+/// PBKDF2 rounds.
+const METAMASK_LEGACY_ITERATIONS: f64 = 10000.0;
+
+/// The modern MetaMask default, below which a vault is flagged.
+const METAMASK_MIN_ITERATIONS: f64 = 600000.0;
+
+/// A key and value pair from a wallet database.
+type WalletRecord = (Vec<u8>, Vec<u8>);
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum BitcoinWalletFormat {
+    BerkeleyDb,
+    Sqlite,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct BitcoinWallet {
+    pub(crate) format: BitcoinWalletFormat,
+    /// A master key is present, so private keys are encrypted.
+    pub(crate) encrypted: bool,
+    pub(crate) plain_keys: usize,
+    pub(crate) encrypted_keys: usize,
+    pub(crate) descriptors: usize,
+    pub(crate) address_book: usize,
+    pub(crate) transactions: usize,
+    pub(crate) derive_iterations: Option<u32>,
+}
+
+impl BitcoinWallet {
+    pub(crate) fn from_bytes(data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        if data.starts_with(b"SQLite format 3\0") {
+            let mut records = Vec::new();
+            for row in SqliteDatabase::from_bytes(data)?.rows("main")? {
+                if let [SqliteValue::Blob(key), SqliteValue::Blob(value)] = row.as_slice() {
+                    records.push((key.clone(), value.clone()));
+                }
+            }
+            return BitcoinWallet::from_records(BitcoinWalletFormat::Sqlite, &records);
+        }
+        return BitcoinWallet::from_records(
+            BitcoinWalletFormat::BerkeleyDb,
+            &berkeley_db_records(&data)?,
+        );
+    }
+
+    pub(crate) fn from_file(file: &Path) -> Result<Self, Box<dyn Error>> {
+        return BitcoinWallet::from_bytes(std::fs::read(file)?);
+    }
+
+    /// Tally the wallet records by type. Each key starts with its type as a
+    /// length prefixed string.
+    fn from_records(
+        format: BitcoinWalletFormat,
+        records: &[WalletRecord],
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut wallet = BitcoinWallet {
+            format,
+            encrypted: false,
+            plain_keys: 0,
+            encrypted_keys: 0,
+            descriptors: 0,
+            address_book: 0,
+            transactions: 0,
+            derive_iterations: None,
+        };
+        let mut recognised = false;
+
+        for (key, value) in records {
+            let Some(record_type) = key
+                .split_first()
+                .and_then(|(length, rest)| rest.get(..*length as usize))
+                .and_then(|x| std::str::from_utf8(x).ok())
+            else {
+                continue;
+            };
+            recognised |= BITCOIN_RECORD_TYPES.contains(&record_type);
+
+            match record_type {
+                "key" | "wkey" | "walletdescriptorkey" => wallet.plain_keys += 1,
+                "ckey" | "walletdescriptorckey" => wallet.encrypted_keys += 1,
+                "walletdescriptor" => wallet.descriptors += 1,
+                "name" => wallet.address_book += 1,
+                "tx" => wallet.transactions += 1,
+                "mkey" => {
+                    wallet.encrypted = true;
+                    wallet.derive_iterations = master_key_iterations(value).ok();
+                }
+                _ => {}
+            }
+        }
+
+        if !recognised {
+            return Err(Box::new(invalid_data("Not a Bitcoin Core wallet")));
+        }
+        return Ok(wallet);
+    }
+
+    pub(crate) fn weaknesses(&self) -> Vec<String> {
+        let mut weaknesses = Vec::new();
+        if let Some(iterations) = self.derive_iterations
+            && iterations < BITCOIN_MIN_DERIVE_ITERATIONS
+        {
+            weaknesses.push(format!("Only {iterations} key derivation rounds"));
+        }
+        return weaknesses;
+    }
+}
+
+/// Read the derivation rounds from a master key record, which follow the
+/// encrypted key, the salt and the derivation method.
+fn master_key_iterations(value: &[u8]) -> Result<u32, Box<dyn Error>> {
+    let mut reader = ByteReader::new(value);
+    for _ in 0..2 {
+        let length = reader.read_u8()?;
+        if length >= 0xFD {
+            return Err(Box::new(invalid_data("Master key field too long")));
+        }
+        reader.skip(length as usize)?;
+    }
+    reader.skip(4)?;
+    return Ok(reader.read_u32_le()?);
+}
+
+/// Collect the key and data pairs from every B-tree leaf page of a Berkeley
+/// DB file. Subdatabases are not followed, as wallet records are recognised by
+/// their contents, and items on overflow pages are read as empty.
+fn berkeley_db_records(data: &[u8]) -> Result<Vec<WalletRecord>, Box<dyn Error>> {
+    let mut reader = ByteReader::new(data);
+    reader.seek(12)?;
+    let magic = reader.read_u32_le()?;
+    reader.skip(4)?;
+
+    /* Databases written on big endian machines keep that byte order. */
+    let big_endian = match magic {
+        BERKELEY_DB_BTREE_MAGIC => false,
+        x if x.swap_bytes() == BERKELEY_DB_BTREE_MAGIC => true,
+        _ => return Err(Box::new(invalid_data("Not a Berkeley DB B-tree"))),
+    };
+    let read_u16 = |reader: &mut ByteReader| -> Result<u16, std::io::Error> {
+        return if big_endian {
+            reader.read_u16_be()
+        } else {
+            reader.read_u16_le()
+        };
+    };
+    let page_size = if big_endian {
+        reader.read_u32_be()?
+    } else {
+        reader.read_u32_le()?
+    } as usize;
+    if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
+        return Err(Box::new(invalid_data("Invalid Berkeley DB page size")));
+    }
+
+    let mut records = Vec::new();
+    for page in data.chunks_exact(page_size) {
+        if page[25] != BERKELEY_DB_LEAF_PAGE {
+            continue;
+        }
+        let mut page_reader = ByteReader::new(page);
+        page_reader.seek(20)?;
+        let entries = read_u16(&mut page_reader)? as usize;
+
+        let mut items = Vec::new();
+        for entry in 0..entries {
+            page_reader.seek(BERKELEY_DB_PAGE_HEADER + entry * 2)?;
+            let offset = read_u16(&mut page_reader)? as usize;
+            page_reader.seek(offset)?;
+            let length = read_u16(&mut page_reader)? as usize;
+
+            if page_reader.read_u8()? & 0x7F == BERKELEY_DB_KEY_DATA {
+                items.push(page_reader.read_bytes(length)?.to_vec());
+            } else {
+                items.push(Vec::new());
+            }
+        }
+
+        /* Leaf entries alternate between a key and its data. */
+        for pair in items.chunks_exact(2) {
+            records.push((pair[0].clone(), pair[1].clone()));
+        }
+    }
+    return Ok(records);
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct ElectrumWallet {
+    /// The whole file is encrypted, hiding even the addresses.
+    pub(crate) storage_encrypted: bool,
+    /// Keys and seed inside the file are encrypted with the wallet password.
+    pub(crate) password_protected: bool,
+    pub(crate) wallet_type: Option<String>,
+    pub(crate) seed_version: Option<u32>,
+    pub(crate) keystore_types: Vec<String>,
+    pub(crate) has_private_keys: bool,
+    pub(crate) addresses: usize,
+}
+
+impl ElectrumWallet {
+    pub(crate) fn from_text(text: &str) -> Result<Self, Box<dyn Error>> {
+        let text = text.trim();
+        if !text.starts_with('{') {
+            if base64_decode(text)?.starts_with(ELECTRUM_ENCRYPTED_MAGIC) {
+                return Ok(ElectrumWallet {
+                    storage_encrypted: true,
+                    password_protected: true,
+                    wallet_type: None,
+                    seed_version: None,
+                    keystore_types: Vec::new(),
+                    has_private_keys: false,
+                    addresses: 0,
+                });
+            }
+            return Err(Box::new(invalid_data("Not an Electrum wallet")));
+        }
+
+        let wallet = JsonValue::parse(text)?;
+        let Some(seed_version) = wallet.get("seed_version").and_then(|x| x.as_f64()) else {
+            return Err(Box::new(invalid_data("Not an Electrum wallet")));
+        };
+
+        /* Multisig wallets name their keystores x1/, x2/ and so on. */
+        let JsonValue::Object(members) = &wallet else {
+            return Err(Box::new(invalid_data("Not an Electrum wallet")));
+        };
+        let keystores: Vec<&JsonValue> = members
+            .iter()
+            .filter(|(name, _)| {
+                name == "keystore" || (name.starts_with('x') && name.ends_with('/'))
+            })
+            .map(|(_, keystore)| keystore)
+            .collect();
+
+        let has_private_keys = keystores.iter().any(|keystore| {
+            ["xprv", "seed"]
+                .iter()
+                .any(|x| keystore.get(x).and_then(|x| x.as_str()).is_some())
+                || matches!(keystore.get("keypairs"), Some(JsonValue::Object(x)) if !x.is_empty())
+        });
+
+        let addresses = ["receiving", "change"]
+            .iter()
+            .filter_map(|x| wallet.path(&["addresses", x]).and_then(|x| x.as_array()))
+            .map(|x| x.len())
+            .sum();
+
+        return Ok(ElectrumWallet {
+            storage_encrypted: false,
+            password_protected: wallet
+                .get("use_encryption")
+                .and_then(|x| x.as_bool())
+                .unwrap_or(false),
+            wallet_type: wallet
+                .get("wallet_type")
+                .and_then(|x| x.as_str())
+                .map(String::from),
+            seed_version: Some(seed_version as u32),
+            keystore_types: keystores
+                .iter()
+                .filter_map(|x| x.get("type").and_then(|x| x.as_str()))
+                .map(String::from)
+                .collect(),
+            has_private_keys,
+            addresses,
+        });
+    }
+}
+
+/// A Web3 Secret Storage (version 3) keystore, as written by geth and most
+/// Ethereum wallets.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct EthereumKeystore {
+    pub(crate) address: Option<String>,
+    pub(crate) cipher: String,
+    pub(crate) kdf: String,
+    /// The scrypt N parameter or PBKDF2 round count.
+    pub(crate) kdf_cost: Option<f64>,
+}
+
+impl EthereumKeystore {
+    pub(crate) fn from_text(text: &str) -> Result<Self, Box<dyn Error>> {
+        let keystore = JsonValue::parse(text)?;
+        if keystore.get("version").and_then(|x| x.as_f64()) != Some(3.0) {
+            return Err(Box::new(invalid_data("Not a version 3 keystore")));
+        }
+
+        /* Older clients capitalised the crypto section. */
+        let Some(crypto) = keystore.get("crypto").or(keystore.get("Crypto")) else {
+            return Err(Box::new(invalid_data("Keystore has no crypto section")));
+        };
+        if crypto.get("ciphertext").is_none() {
+            return Err(Box::new(invalid_data("Keystore has no ciphertext")));
+        }
+
+        let kdf = crypto
+            .get("kdf")
+            .and_then(|x| x.as_str())
+            .unwrap_or("unknown");
+        let cost_name = if kdf == "scrypt" { "n" } else { "c" };
+
+        return Ok(EthereumKeystore {
+            address: keystore
+                .get("address")
+                .and_then(|x| x.as_str())
+                .map(|x| format!("0x{}", x.trim_start_matches("0x"))),
+            cipher: String::from(
+                crypto
+                    .get("cipher")
+                    .and_then(|x| x.as_str())
+                    .unwrap_or("unknown"),
+            ),
+            kdf: String::from(kdf),
+            kdf_cost: crypto
+                .path(&["kdfparams", cost_name])
+                .and_then(|x| x.as_f64()),
+        });
+    }
+
+    pub(crate) fn weaknesses(&self) -> Vec<String> {
+        let mut weaknesses = Vec::new();
+        match (self.kdf.as_str(), self.kdf_cost) {
+            ("scrypt", Some(n)) if n < ETHEREUM_STANDARD_SCRYPT_N => {
+                weaknesses.push(format!("Scrypt cost of only {n}"));
+            }
+            ("pbkdf2", Some(c)) if c < ETHEREUM_STANDARD_PBKDF2_ROUNDS => {
+                weaknesses.push(format!("Only {c} PBKDF2 rounds"));
+            }
+            _ => {}
+        }
+        return weaknesses;
+    }
+}
+
+/// Report a Bitcoin Core, Electrum or Ethereum wallet file. Files that are
+/// none of these give no findings.
+pub(crate) fn wallet_file_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = std::fs::read(file)?;
+
+    if data.starts_with(b"SQLite format 3\0")
+        || data
+            .get(12..16)
+            .is_some_and(|x| x == [0x62, 0x31, 0x05, 0x00] || x == [0x00, 0x05, 0x31, 0x62])
+    {
+        return match BitcoinWallet::from_bytes(data) {
+            Ok(wallet) => Ok(vec![bitcoin_finding(file, &wallet)]),
+            Err(_) => Ok(Vec::new()),
+        };
+    }
+
+    let Ok(text) = String::from_utf8(data) else {
+        return Ok(Vec::new());
+    };
+    if let Ok(wallet) = ElectrumWallet::from_text(&text) {
+        return Ok(vec![electrum_finding(file, &wallet)]);
+    }
+    if let Ok(keystore) = EthereumKeystore::from_text(&text) {
+        return Ok(vec![ethereum_finding(file, &keystore)]);
+    }
+    return Ok(Vec::new());
+}
+
+fn bitcoin_finding(file: &Path, wallet: &BitcoinWallet) -> Finding {
+    let weaknesses = wallet.weaknesses();
+    let mut finding = if wallet.plain_keys > 0 {
+        Finding::new(file, Severity::Critical, "Unencrypted Bitcoin Core wallet")
+    } else if wallet.encrypted_keys == 0 {
+        Finding::new(file, Severity::Low, "Watch-only Bitcoin Core wallet")
+    } else if weaknesses.is_empty() {
+        Finding::new(file, Severity::Medium, "Encrypted Bitcoin Core wallet")
+    } else {
+        Finding::new(file, Severity::High, "Weakly encrypted Bitcoin Core wallet")
+    };
+
+    finding.add_detail(
+        "format",
+        String::from(match wallet.format {
+            BitcoinWalletFormat::BerkeleyDb => "Berkeley DB",
+            BitcoinWalletFormat::Sqlite => "SQLite descriptor",
+        }),
+    );
+    finding.add_detail("unencrypted keys", wallet.plain_keys.to_string());
+    finding.add_detail("encrypted keys", wallet.encrypted_keys.to_string());
+    if wallet.descriptors > 0 {
+        finding.add_detail("descriptors", wallet.descriptors.to_string());
+    }
+    finding.add_detail("address book entries", wallet.address_book.to_string());
+    finding.add_detail("transactions", wallet.transactions.to_string());
+    if let Some(iterations) = wallet.derive_iterations {
+        finding.add_detail("key derivation rounds", iterations.to_string());
+    }
+    for weakness in weaknesses {
+        finding.add_detail("weakness", weakness);
+    }
+    return finding;
+}
+
+fn electrum_finding(file: &Path, wallet: &ElectrumWallet) -> Finding {
+    let mut finding = if wallet.storage_encrypted {
+        Finding::new(file, Severity::Medium, "Encrypted Electrum wallet")
+    } else if !wallet.has_private_keys {
+        Finding::new(file, Severity::Low, "Watch-only Electrum wallet")
+    } else if wallet.password_protected {
+        Finding::new(file, Severity::Medium, "Password protected Electrum wallet")
+    } else {
+        Finding::new(file, Severity::Critical, "Unencrypted Electrum wallet")
+    };
+
+    if wallet.storage_encrypted {
+        return finding;
+    }
+    if let Some(wallet_type) = &wallet.wallet_type {
+        finding.add_detail("wallet type", wallet_type.clone());
+    }
+    if let Some(seed_version) = wallet.seed_version {
+        finding.add_detail("seed version", seed_version.to_string());
+    }
+    for keystore_type in &wallet.keystore_types {
+        finding.add_detail("keystore", keystore_type.clone());
+    }
+    finding.add_detail("addresses", wallet.addresses.to_string());
+    /* Addresses and balances are readable whenever the file itself is not encrypted. */
+    finding.add_detail("weakness", String::from("Addresses stored in plain text"));
+    return finding;
+}
+
+fn ethereum_finding(file: &Path, keystore: &EthereumKeystore) -> Finding {
+    let weaknesses = keystore.weaknesses();
+    let mut finding = if weaknesses.is_empty() {
+        Finding::new(file, Severity::Medium, "Ethereum keystore")
+    } else {
+        Finding::new(file, Severity::High, "Weakly protected Ethereum keystore")
+    };
+
+    if let Some(address) = &keystore.address {
+        finding.add_detail("address", address.clone());
+    }
+    finding.add_detail("cipher", keystore.cipher.clone());
+    finding.add_detail("kdf", keystore.kdf.clone());
+    if let Some(cost) = keystore.kdf_cost {
+        finding.add_detail("kdf cost", cost.to_string());
+    }
+    for weakness in weaknesses {
+        finding.add_detail("weakness", weakness);
+    }
+    return finding;
+}
+
+/// Read the log and table files of a LevelDB directory. Log files are stored
+/// uncompressed, so recent writes can be searched directly.
+fn leveldb_contents(directory: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "log" || x == "ldb") {
+            contents.extend(std::fs::read(path)?);
+        }
+    }
+    return Ok(contents);
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    return haystack.windows(needle.len()).any(|x| x == needle);
+}
+
+/// Find the MetaMask vaults in LevelDB contents. A vault is JSON holding the
+/// encrypted data, IV and salt, usually stored as an escaped string inside the
+/// extension state.
+fn metamask_vaults(contents: &[u8]) -> Vec<JsonValue> {
+    let text = String::from_utf8_lossy(contents);
+    let mut vaults = Vec::new();
+
+    for (pattern, escaped) in [("{\\\"data\\\":\\\"", true), ("{\"data\":\"", false)] {
+        for (start, _) in text.match_indices(pattern) {
+            /* Base64 never contains braces, so depth counting finds the end. */
+            let mut depth = 0;
+            let Some(end) = text[start..].char_indices().find_map(|(idx, x)| {
+                match x {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(start + idx + 1)
+            }) else {
+                continue;
+            };
+
+            let mut vault = String::from(&text[start..end]);
+            if escaped {
+                vault = vault.replace("\\\"", "\"").replace("\\\\", "\\");
+            }
+            if let Ok(vault) = JsonValue::parse(&vault)
+                && ["data", "iv", "salt"]
+                    .iter()
+                    .all(|x| vault.get(x).is_some())
+            {
+                vaults.push(vault);
+            }
+        }
+    }
+    return vaults;
+}
+
+fn exodus_finding(directory: &Path) -> Option<Finding> {
+    let seed = std::fs::read(directory.join("seed.seco")).ok()?;
+    if !seed.starts_with(EXODUS_SECO_MAGIC) {
+        return None;
+    }
+
+    /* Exodus only writes its passphrase to disk when no password is set. */
+    let mut finding = if directory.join("passphrase.json").is_file() {
+        Finding::new(
+            directory,
+            Severity::Critical,
+            "Exodus wallet without a password",
+        )
+    } else {
+        Finding::new(
+            directory,
+            Severity::Medium,
+            "Password protected Exodus wallet",
+        )
+    };
+
+    let mut seco_files: Vec<String> = std::fs::read_dir(directory)
+        .ok()?
+        .filter_map(|x| x.ok())
+        .map(|x| x.file_name().to_string_lossy().to_string())
+        .filter(|x| x.ends_with(".seco"))
+        .collect();
+    seco_files.sort();
+    for seco_file in seco_files {
+        finding.add_detail("seco file", seco_file);
+    }
+    return Some(finding);
+}
+
+fn atomic_finding(directory: &Path) -> Option<Finding> {
+    let contents = leveldb_contents(directory).ok()?;
+    let has_mnemonic = contains(&contents, b"general_mnemonic");
+
+    let mut finding = if has_mnemonic {
+        Finding::new(directory, Severity::High, "Atomic Wallet storage")
+    } else {
+        Finding::new(directory, Severity::Low, "Atomic Wallet storage")
+    };
+    finding.add_detail(
+        "encrypted mnemonic",
+        String::from(if has_mnemonic { "present" } else { "not found" }),
+    );
+    return Some(finding);
+}
+
+fn metamask_finding(directory: &Path, browser: &str) -> Option<Finding> {
+    let vaults = metamask_vaults(&leveldb_contents(directory).ok()?);
+    let Some(vault) = vaults.first() else {
+        let mut finding = Finding::new(directory, Severity::Low, "MetaMask extension storage");
+        finding.add_detail("browser", String::from(browser));
+        return Some(finding);
+    };
+
+    let iterations = vault
+        .path(&["keyMetadata", "params", "iterations"])
+        .and_then(|x| x.as_f64())
+        .unwrap_or(METAMASK_LEGACY_ITERATIONS);
+
+    let mut finding = if iterations < METAMASK_MIN_ITERATIONS {
+        let mut finding =
+            Finding::new(directory, Severity::High, "Weakly protected MetaMask vault");
+        finding.add_detail("weakness", format!("Only {iterations} PBKDF2 rounds"));
+        finding
+    } else {
+        Finding::new(directory, Severity::Medium, "MetaMask vault")
+    };
+    finding.add_detail("browser", String::from(browser));
+    finding.add_detail("kdf rounds", iterations.to_string());
+    finding.add_detail("vaults", vaults.len().to_string());
+    return Some(finding);
+}
+
+/// Report the Exodus, Atomic Wallet and MetaMask data directories below a
+/// directory, one finding for each.
+pub(crate) fn wallet_directory_findings(directory: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let directories: BTreeSet<PathBuf> = all_files(directory)?
+        .iter()
+        .filter_map(|x| x.parent().map(Path::to_path_buf))
+        .collect();
+    let name_of = |path: &Path| {
+        path.file_name()
+            .map(|x| x.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+
+    let mut findings = Vec::new();
+    for wallet_directory in directories {
+        let name = name_of(&wallet_directory);
+        let parent = wallet_directory.parent().map(name_of).unwrap_or_default();
+
+        let finding = if name == "exodus.wallet" || wallet_directory.join("seed.seco").is_file() {
+            exodus_finding(&wallet_directory)
+        } else if name == "leveldb"
+            && parent == "local storage"
+            && wallet_directory
+                .components()
+                .any(|x| x.as_os_str().to_string_lossy().to_lowercase() == "atomic")
+        {
+            atomic_finding(&wallet_directory)
+        } else if parent == "local extension settings" {
+            METAMASK_EXTENSION_IDS
+                .iter()
+                .find(|(id, _)| *id == name)
+                .and_then(|(_, browser)| metamask_finding(&wallet_directory, browser))
+        } else {
+            None
+        };
+        findings.extend(finding);
+    }
+    return Ok(findings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLETS: &str = "./tests/testing_files/wallets";
+
+    fn file_finding(file: &str) -> Finding {
+        let findings = wallet_file_findings(&Path::new(WALLETS).join(file)).unwrap();
+        assert_eq!(findings.len(), 1);
+        return findings[0].clone();
+    }
+
+    #[test]
+    fn legacy_bitcoin_wallet() {
+        let wallet =
+            BitcoinWallet::from_file(&Path::new(WALLETS).join("bitcoin_core/legacy/wallet.dat"))
+                .unwrap();
+        assert_eq!(
+            wallet,
+            BitcoinWallet {
+                format: BitcoinWalletFormat::BerkeleyDb,
+                encrypted: false,
+                plain_keys: 3,
+                encrypted_keys: 0,
+                descriptors: 0,
+                address_book: 2,
+                transactions: 1,
+                derive_iterations: None,
+            }
+        );
+        assert_eq!(
+            file_finding("bitcoin_core/legacy/wallet.dat").severity,
+            Severity::Critical
+        );
+    }
+
+    #[test]
+    fn weakly_encrypted_bitcoin_wallet() {
+        let finding = file_finding("bitcoin_core/encrypted_legacy/wallet.dat");
+        assert_eq!(finding.description, "Weakly encrypted Bitcoin Core wallet");
+        assert_eq!(finding.detail("encrypted keys"), Some(&String::from("4")));
+        assert_eq!(
+            finding.detail("key derivation rounds"),
+            Some(&String::from("8000"))
+        );
+    }
+
+    #[test]
+    fn descriptor_bitcoin_wallet() {
+        let wallet = BitcoinWallet::from_file(
+            &Path::new(WALLETS).join("bitcoin_core/descriptor/wallet.dat"),
+        )
+        .unwrap();
+        assert_eq!(wallet.format, BitcoinWalletFormat::Sqlite);
+        assert_eq!(wallet.descriptors, 2);
+        assert_eq!(wallet.encrypted_keys, 2);
+        assert_eq!(wallet.derive_iterations, Some(183142));
+        assert!(wallet.weaknesses().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn truncated_bitcoin_wallet() {
+        BitcoinWallet::from_file(&Path::new(WALLETS).join("bitcoin_core/truncated/wallet.dat"))
+            .unwrap();
+    }
+
+    #[test]
+    fn electrum_wallets() {
+        let finding = file_finding("electrum/default_wallet");
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.detail("addresses"), Some(&String::from("7")));
+        assert_eq!(finding.detail("keystore"), Some(&String::from("bip32")));
+
+        assert_eq!(
+            file_finding("electrum/password_wallet").description,
+            "Password protected Electrum wallet"
+        );
+        assert_eq!(
+            file_finding("electrum/watch_only_wallet").severity,
+            Severity::Low
+        );
+
+        let finding = file_finding("electrum/encrypted_wallet");
+        assert_eq!(finding.description, "Encrypted Electrum wallet");
+        assert!(finding.details.is_empty());
+    }
+
+    #[test]
+    fn ethereum_keystores() {
+        let finding = file_finding(
+            "ethereum/UTC--2024-01-01T00-00-00.000000000Z--6b8afff4254e249bf7da2aa791c23ef200e720da",
+        );
+        assert_eq!(finding.severity, Severity::Medium);
+        assert_eq!(
+            finding.detail("address"),
+            Some(&String::from("0x6b8afff4254e249bf7da2aa791c23ef200e720da"))
+        );
+        assert_eq!(finding.detail("kdf"), Some(&String::from("scrypt")));
+
+        let finding = file_finding("ethereum/light_keystore.json");
+        assert_eq!(finding.severity, Severity::High);
+        assert_eq!(
+            finding.detail("weakness"),
+            Some(&String::from("Only 10240 PBKDF2 rounds"))
+        );
+    }
+
+    #[test]
+    fn unrelated_files() {
+        assert!(
+            wallet_file_findings(Path::new("./tests/testing_files/sqlite/Cookies"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            wallet_file_findings(Path::new("./tests/testing_files/x509/server.crt"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn wallet_directories() {
+        let findings = wallet_directory_findings(Path::new(WALLETS)).unwrap();
+        assert_eq!(
+            findings
+                .iter()
+                .map(|x| (x.description.as_str(), x.severity))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Atomic Wallet storage", Severity::High),
+                ("Password protected Exodus wallet", Severity::Medium),
+                ("Exodus wallet without a password", Severity::Critical),
+                ("Weakly protected MetaMask vault", Severity::High),
+                ("MetaMask vault", Severity::Medium),
+            ]
+        );
+        assert_eq!(
+            findings[2].detail("seco file"),
+            Some(&String::from("info.seco"))
+        );
+        assert_eq!(findings[3].detail("browser"), Some(&String::from("Edge")));
+        assert_eq!(
+            findings[4].detail("kdf rounds"),
+            Some(&String::from("600000"))
+        );
+    }
+}
@@ -0,0 +1,745 @@
+/*
+ * Decode the parts of X.509 certificates and certificate signing requests
+ * that matter when judging what a leaked certificate is for and whether it
+ * is still valid.
+ */
+
+use crate::byte_reader::invalid_data;
+use crate::der::{
+    DerElement, TAG_BIT_STRING, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE, curve_bits,
+    oid_name,
+};
+use crate::encoding::pem_blocks;
+use crate::findings::{Finding, Severity};
+use crate::private_keys::private_key_public_values;
+use std::error::Error;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_BMP_STRING: u8 = 0x1E;
+const TAG_VERSION: u8 = 0xA0;
+const TAG_EXTENSIONS: u8 = 0xA3;
+const TAG_ATTRIBUTES: u8 = 0xA0;
+
+const OID_SUBJECT_ALT_NAME: &str = "2.5.29.17";
+const OID_BASIC_CONSTRAINTS: &str = "2.5.29.19";
+const OID_EXTENDED_KEY_USAGE: &str = "2.5.29.37";
+const OID_EXTENSION_REQUEST: &str = "1.2.840.113549.1.9.14";
+
+/// The algorithm, size and public value of a subject's key. The value is the
+/// RSA modulus, DSA public integer or raw curve point, which is enough to pair
+/// a certificate with its private key.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct PublicKey {
+    pub(crate) algorithm: String,
+    pub(crate) bits: Option<u32>,
+    pub(crate) value: Vec<u8>,
+}
+
+impl PublicKey {
+    /// Decode a `SubjectPublicKeyInfo` structure.
+    fn from_der(spki: &DerElement) -> Result<Self, Box<dyn Error>> {
+        let fields = spki.children()?;
+        let (Some(algorithm), Some(key)) = (fields.first(), fields.get(1)) else {
+            return Err(Box::new(invalid_data("Malformed public key")));
+        };
+        if key.tag != TAG_BIT_STRING || key.contents.is_empty() {
+            return Err(Box::new(invalid_data("Malformed public key")));
+        }
+
+        let algorithm = algorithm.children()?;
+        let Some(algorithm_oid) = algorithm.first() else {
+            return Err(Box::new(invalid_data("Public key has no algorithm")));
+        };
+        let algorithm_oid = algorithm_oid.as_oid()?;
+        let name = String::from(oid_name(&algorithm_oid).unwrap_or(&algorithm_oid));
+
+        /* The first byte of a bit string counts the unused trailing bits. */
+        let key_bytes = &key.contents[1..];
+        let (bits, value) = match name.as_str() {
+            "RSA" => {
+                let (sequence, _) = DerElement::parse_tagged(key_bytes, TAG_SEQUENCE)?;
+                let Some(modulus) = sequence.children()?.first().copied() else {
+                    return Err(Box::new(invalid_data("RSA key has no modulus")));
+                };
+                (Some(modulus.integer_bits()), modulus.unsigned_value())
+            }
+            "DSA" => {
+                let (public, _) = DerElement::parse_tagged(key_bytes, TAG_INTEGER)?;
+                let prime_bits = algorithm
+                    .get(1)
+                    .and_then(|x| x.children().ok())
+                    .and_then(|x| x.first().map(|p| p.integer_bits()));
+                (prime_bits, public.unsigned_value())
+            }
+            "EC" => {
+                let curve = algorithm
+                    .get(1)
+                    .filter(|x| x.tag == TAG_OID)
+                    .and_then(|x| x.as_oid().ok());
+                (
+                    curve.and_then(|oid| oid_name(&oid).and_then(curve_bits)),
+                    key_bytes,
+                )
+            }
+            _ => (curve_bits(&name), key_bytes),
+        };
+
+        return Ok(PublicKey {
+            algorithm: name,
+            bits,
+            value: value.to_vec(),
+        });
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct Certificate {
+    pub(crate) subject: String,
+    pub(crate) issuer: String,
+    /// Start of the validity window in seconds since the Unix epoch.
+    pub(crate) not_before: i64,
+    /// End of the validity window in seconds since the Unix epoch.
+    pub(crate) not_after: i64,
+    pub(crate) public_key: PublicKey,
+    pub(crate) subject_alt_names: Vec<String>,
+    pub(crate) is_ca: bool,
+    pub(crate) extended_key_usages: Vec<String>,
+}
+
+impl Certificate {
+    pub(crate) fn from_der(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let (certificate, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+        let Some(tbs_certificate) = certificate.children()?.first().copied() else {
+            return Err(Box::new(invalid_data("Empty certificate")));
+        };
+        let mut fields = tbs_certificate.children()?;
+
+        /* The version is optional and defaults to v1 when missing. */
+        if fields.first().is_some_and(|x| x.tag == TAG_VERSION) {
+            fields.remove(0);
+        }
+        if fields.len() < 6 || fields[0].tag != TAG_INTEGER {
+            return Err(Box::new(invalid_data("Malformed certificate")));
+        }
+
+        let validity = fields[3].children()?;
+        if validity.len() != 2 {
+            return Err(Box::new(invalid_data("Malformed certificate validity")));
+        }
+
+        let mut certificate = Certificate {
+            subject: format_name(&fields[4])?,
+            issuer: format_name(&fields[2])?,
+            not_before: parse_time(&validity[0])?,
+            not_after: parse_time(&validity[1])?,
+            public_key: PublicKey::from_der(&fields[5])?,
+            subject_alt_names: Vec::new(),
+            is_ca: false,
+            extended_key_usages: Vec::new(),
+        };
+
+        /* Extensions only exist in version 3 certificates. */
+        if let Some(extensions) = fields.iter().find(|x| x.tag == TAG_EXTENSIONS) {
+            let Some(extensions) = extensions.children()?.first().copied() else {
+                return Err(Box::new(invalid_data("Malformed certificate extensions")));
+            };
+            for (oid, value) in read_extensions(&extensions)? {
+                match oid.as_str() {
+                    OID_SUBJECT_ALT_NAME => {
+                        certificate.subject_alt_names = format_general_names(&value)?;
+                    }
+                    OID_BASIC_CONSTRAINTS => {
+                        certificate.is_ca = value.children()?.first().is_some_and(|x| {
+                            x.tag == TAG_BOOLEAN && x.contents.first().is_some_and(|b| *b != 0)
+                        });
+                    }
+                    OID_EXTENDED_KEY_USAGE => {
+                        for usage in value.children()? {
+                            let usage = usage.as_oid()?;
+                            certificate
+                                .extended_key_usages
+                                .push(String::from(key_usage_name(&usage).unwrap_or(&usage)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        return Ok(certificate);
+    }
+
+    pub(crate) fn is_expired(&self, now: i64) -> bool {
+        return self.not_after < now;
+    }
+
+    /// Self-signed certificates name themselves as their issuer.
+    pub(crate) fn is_self_signed(&self) -> bool {
+        return self.subject == self.issuer;
+    }
+
+    pub(crate) fn is_code_signing(&self) -> bool {
+        return self.extended_key_usages.iter().any(|x| x == "code signing");
+    }
+}
+
+/// A PKCS#10 certificate signing request.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct CertificateRequest {
+    pub(crate) subject: String,
+    pub(crate) public_key: PublicKey,
+    pub(crate) subject_alt_names: Vec<String>,
+}
+
+impl CertificateRequest {
+    pub(crate) fn from_der(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let (request, _) = DerElement::parse_tagged(data, TAG_SEQUENCE)?;
+        let Some(request_info) = request.children()?.first().copied() else {
+            return Err(Box::new(invalid_data("Empty certificate request")));
+        };
+        let fields = request_info.children()?;
+        if fields.len() < 3 || fields[0].tag != TAG_INTEGER {
+            return Err(Box::new(invalid_data("Malformed certificate request")));
+        }
+
+        let mut request = CertificateRequest {
+            subject: format_name(&fields[1])?,
+            public_key: PublicKey::from_der(&fields[2])?,
+            subject_alt_names: Vec::new(),
+        };
+
+        /* Requested extensions are carried in an extensionRequest attribute. */
+        let attributes = fields.get(3).filter(|x| x.tag == TAG_ATTRIBUTES);
+        for attribute in attributes.map_or(Ok(Vec::new()), |x| x.children())? {
+            let attribute = attribute.children()?;
+            let (Some(oid), Some(values)) = (attribute.first(), attribute.get(1)) else {
+                continue;
+            };
+            if oid.as_oid()? != OID_EXTENSION_REQUEST {
+                continue;
+            }
+            for extensions in values.children()? {
+                for (oid, value) in read_extensions(&extensions)? {
+                    if oid == OID_SUBJECT_ALT_NAME {
+                        request.subject_alt_names = format_general_names(&value)?;
+                    }
+                }
+            }
+        }
+        return Ok(request);
+    }
+}
+
+/// Split a sequence of extensions into their identifiers and decoded values.
+fn read_extensions<'a>(
+    extensions: &DerElement<'a>,
+) -> Result<Vec<(String, DerElement<'a>)>, Box<dyn Error>> {
+    let mut parsed = Vec::new();
+
+    for extension in extensions.children()? {
+        let fields = extension.children()?;
+        let (Some(oid), Some(value)) = (fields.first(), fields.last()) else {
+            return Err(Box::new(invalid_data("Malformed extension")));
+        };
+        if value.tag != TAG_OCTET_STRING {
+            return Err(Box::new(invalid_data("Malformed extension")));
+        }
+        parsed.push((oid.as_oid()?, DerElement::parse(value.contents)?.0));
+    }
+    return Ok(parsed);
+}
+
+/// Render the names of a subject alternative name extension, such as
+/// "DNS:example.com" or "IP:192.0.2.1".
+fn format_general_names(names: &DerElement) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut formatted = Vec::new();
+
+    for name in names.children()? {
+        let text = String::from_utf8_lossy(name.contents);
+        formatted.push(match name.tag {
+            0x81 => format!("email:{text}"),
+            0x82 => format!("DNS:{text}"),
+            0x86 => format!("URI:{text}"),
+            0x87 => match name.contents.len() {
+                4 => format!("IP:{}", Ipv4Addr::from(<[u8; 4]>::try_from(name.contents)?)),
+                16 => format!(
+                    "IP:{}",
+                    Ipv6Addr::from(<[u8; 16]>::try_from(name.contents)?)
+                ),
+                _ => continue,
+            },
+            _ => continue,
+        });
+    }
+    return Ok(formatted);
+}
+
+/// Friendly names for the extended key usages that say what a certificate
+/// may be used for.
+fn key_usage_name(oid: &str) -> Option<&'static str> {
+    return match oid {
+        "1.3.6.1.5.5.7.3.1" => Some("server authentication"),
+        "1.3.6.1.5.5.7.3.2" => Some("client authentication"),
+        "1.3.6.1.5.5.7.3.3" => Some("code signing"),
+        "1.3.6.1.5.5.7.3.4" => Some("email protection"),
+        "1.3.6.1.5.5.7.3.8" => Some("time stamping"),
+        "1.3.6.1.5.5.7.3.9" => Some("OCSP signing"),
+        "1.3.6.1.4.1.311.10.3.13" => Some("lifetime signing"),
+        _ => None,
+    };
+}
+
+/// Short names for the attributes normally found in a distinguished name.
+fn attribute_name(oid: &str) -> Option<&'static str> {
+    return match oid {
+        "2.5.4.3" => Some("CN"),
+        "2.5.4.5" => Some("SERIALNUMBER"),
+        "2.5.4.6" => Some("C"),
+        "2.5.4.7" => Some("L"),
+        "2.5.4.8" => Some("ST"),
+        "2.5.4.9" => Some("STREET"),
+        "2.5.4.10" => Some("O"),
+        "2.5.4.11" => Some("OU"),
+        "0.9.2342.19200300.100.1.25" => Some("DC"),
+        "1.2.840.113549.1.9.1" => Some("E"),
+        _ => None,
+    };
+}
+
+/// Render a distinguished name in the usual most specific first order, for
+/// example "CN=example.com, O=Example, C=GB".
+fn format_name(name: &DerElement) -> Result<String, Box<dyn Error>> {
+    let mut parts = Vec::new();
+
+    for relative_name in name.children()?.iter().rev() {
+        for attribute in relative_name.children()? {
+            let pair = attribute.children()?;
+            if pair.len() != 2 {
+                return Err(Box::new(invalid_data("Malformed name attribute")));
+            }
+
+            let oid = pair[0].as_oid()?;
+            let value = if pair[1].tag == TAG_BMP_STRING {
+                let units: Vec<u16> = pair[1]
+                    .contents
+                    .chunks_exact(2)
+                    .map(|x| u16::from_be_bytes([x[0], x[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            } else {
+                String::from_utf8_lossy(pair[1].contents).to_string()
+            };
+            parts.push(format!("{}={value}", attribute_name(&oid).unwrap_or(&oid)));
+        }
+    }
+    return Ok(parts.join(", "));
+}
+
+/// Convert a UTCTime or GeneralizedTime into seconds since the Unix epoch.
+fn parse_time(time: &DerElement) -> Result<i64, Box<dyn Error>> {
+    let text = String::from_utf8_lossy(time.contents);
+    let digits = text.trim_end_matches('Z');
+    if !digits.bytes().all(|x| x.is_ascii_digit()) {
+        return Err(Box::new(invalid_data("Unsupported certificate time")));
+    }
+
+    let (year, rest) = match time.tag {
+        TAG_UTC_TIME if digits.len() >= 10 => {
+            /* Two digit years from 50 onwards belong to the 1900s. */
+            let year: i64 = digits[..2].parse()?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                &digits[2..],
+            )
+        }
+        TAG_GENERALIZED_TIME if digits.len() >= 12 => (digits[..4].parse()?, &digits[4..]),
+        _ => return Err(Box::new(invalid_data("Unsupported certificate time"))),
+    };
+
+    let month: u32 = rest[0..2].parse()?;
+    let day: u32 = rest[2..4].parse()?;
+    let hour: i64 = rest[4..6].parse()?;
+    let minute: i64 = rest[6..8].parse()?;
+    let second: i64 = rest.get(8..10).map_or(Ok(0), |x| x.parse())?;
+
+    return Ok(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second);
+}
+
+/// The number of days between the Unix epoch and a date in the proleptic
+/// Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    return era * 146097 + day_of_era - 719468;
+}
+
+/// The date in the proleptic Gregorian calendar a number of days after the
+/// Unix epoch.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year, month, day);
+}
+
+/// Add the details shared by certificates and signing requests.
+fn add_key_details(finding: &mut Finding, public_key: &PublicKey, alt_names: &Vec<String>) {
+    finding.add_detail("key algorithm", public_key.algorithm.clone());
+    if let Some(bits) = public_key.bits {
+        finding.add_detail("key size", format!("{bits} bits"));
+    }
+    for name in alt_names {
+        finding.add_detail("subject alt name", name.clone());
+    }
+}
+
+/// Report every certificate and certificate signing request in a PEM or DER
+/// file. A certificate stored next to its own private key is a usable
+/// identity, so is treated far more seriously than one on its own.
+pub(crate) fn certificate_findings(file: &Path) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let data = std::fs::read(file)?;
+    let text = String::from_utf8_lossy(&data).to_string();
+    let key_values = private_key_public_values(&text);
+    let now = unix_now();
+
+    let mut certificates = Vec::new();
+    let mut requests = Vec::new();
+    let blocks = pem_blocks(&text);
+
+    for block in &blocks {
+        match block.label.as_str() {
+            "CERTIFICATE" | "X509 CERTIFICATE" => {
+                certificates.extend(Certificate::from_der(&block.data).ok());
+            }
+            "CERTIFICATE REQUEST" | "NEW CERTIFICATE REQUEST" => {
+                requests.extend(CertificateRequest::from_der(&block.data).ok());
+            }
+            _ => {}
+        }
+    }
+
+    /* Files without any armour may be a single binary certificate or request. */
+    if blocks.is_empty() {
+        if let Ok(certificate) = Certificate::from_der(&data) {
+            certificates.push(certificate);
+        } else if let Ok(request) = CertificateRequest::from_der(&data) {
+            requests.push(request);
+        }
+    }
+
+    let mut findings = Vec::new();
+
+    for certificate in certificates {
+        let has_key = key_values.contains(&certificate.public_key.value);
+        let mut finding = if has_key {
+            Finding::new(
+                file,
+                Severity::High,
+                "Certificate stored with its private key",
+            )
+        } else {
+            Finding::new(file, Severity::Info, "Certificate")
+        };
+
+        /* Code signing keys let an attacker publish trusted software. */
+        if certificate.is_code_signing() {
+            finding.severity = finding.severity.escalate();
+            finding.add_detail("flag", String::from("code signing"));
+        }
+        if certificate.is_expired(now) {
+            finding.severity = finding.severity.downgrade();
+            finding.add_detail("flag", String::from("expired"));
+        }
+        if certificate.is_self_signed() {
+            finding.add_detail("flag", String::from("self-signed"));
+        }
+        if certificate.is_ca {
+            finding.add_detail("flag", String::from("certificate authority"));
+        }
+
+        finding.add_detail("subject", certificate.subject.clone());
+        finding.add_detail("issuer", certificate.issuer.clone());
+        finding.add_detail("valid from", format_timestamp(certificate.not_before));
+        finding.add_detail("valid until", format_timestamp(certificate.not_after));
+        add_key_details(
+            &mut finding,
+            &certificate.public_key,
+            &certificate.subject_alt_names,
+        );
+        for usage in &certificate.extended_key_usages {
+            finding.add_detail("extended key usage", usage.clone());
+        }
+        finding.add_detail("matching private key", has_key.to_string());
+        findings.push(finding);
+    }
+
+    for request in requests {
+        let has_key = key_values.contains(&request.public_key.value);
+        let mut finding = if has_key {
+            Finding::new(
+                file,
+                Severity::High,
+                "Certificate signing request stored with its private key",
+            )
+        } else {
+            Finding::new(file, Severity::Info, "Certificate signing request")
+        };
+
+        finding.add_detail("subject", request.subject.clone());
+        add_key_details(
+            &mut finding,
+            &request.public_key,
+            &request.subject_alt_names,
+        );
+        finding.add_detail("matching private key", has_key.to_string());
+        findings.push(finding);
+    }
+    return Ok(findings);
+}
+
+/// Render seconds since the Unix epoch as a UTC date and time.
+pub(crate) fn format_timestamp(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time_of_day = seconds.rem_euclid(86400);
+
+    return format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time_of_day / 3600,
+        (time_of_day % 3600) / 60,
+        time_of_day % 60
+    );
+}
+
+/// The current time in seconds since the Unix epoch.
+pub(crate) fn unix_now() -> i64 {
+    return SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs() as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn decode_certificate() {
+        let data =
+            std::fs::read(Path::new("./tests/testing_files/java_keystore/server.cer")).unwrap();
+        let certificate = Certificate::from_der(&data).unwrap();
+
+        assert_eq!(
+            certificate.subject,
+            "CN=server.example.com, O=Example Ltd, C=GB"
+        );
+        assert_eq!(certificate.issuer, certificate.subject);
+        assert_eq!(
+            format_timestamp(certificate.not_before),
+            "2024-01-01 14:05:06 UTC"
+        );
+        assert!(certificate.not_after > certificate.not_before);
+        assert_eq!(certificate.public_key.algorithm, "RSA");
+        assert_eq!(certificate.public_key.bits, Some(2048));
+    }
+
+    #[test]
+    fn decode_extensions() {
+        let data = std::fs::read(Path::new("./tests/testing_files/x509/ca.der")).unwrap();
+        let certificate = Certificate::from_der(&data).unwrap();
+
+        assert!(certificate.is_ca);
+        assert!(certificate.is_self_signed());
+        assert_eq!(certificate.public_key.algorithm, "EC");
+        assert_eq!(certificate.public_key.bits, Some(256));
+        assert_eq!(certificate.public_key.value.len(), 65);
+    }
+
+    #[test]
+    fn decode_alt_names() {
+        let blocks =
+            pem_blocks(&std::fs::read_to_string("./tests/testing_files/x509/server.crt").unwrap());
+        let certificate = Certificate::from_der(&blocks[0].data).unwrap();
+
+        assert_eq!(
+            certificate.subject_alt_names,
+            vec![
+                "DNS:www.example.com",
+                "DNS:example.com",
+                "IP:192.0.2.10",
+                "email:admin@example.com"
+            ]
+        );
+        assert_eq!(
+            certificate.extended_key_usages,
+            vec!["server authentication"]
+        );
+        assert_eq!(certificate.public_key.bits, Some(2048));
+        assert!(!certificate.is_code_signing());
+    }
+
+    #[test]
+    fn decode_request() {
+        let blocks =
+            pem_blocks(&std::fs::read_to_string("./tests/testing_files/x509/request.csr").unwrap());
+        let request = CertificateRequest::from_der(&blocks[0].data).unwrap();
+
+        assert_eq!(request.subject, "CN=api.example.com");
+        assert_eq!(request.subject_alt_names, vec!["DNS:api.example.com"]);
+        assert_eq!(request.public_key.algorithm, "RSA");
+    }
+
+    #[test]
+    fn certificate_with_key() {
+        let findings =
+            certificate_findings(Path::new("./tests/testing_files/x509/server_with_key.pem"))
+                .unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(
+            findings[0].detail("matching private key"),
+            Some(&String::from("true"))
+        );
+        assert_eq!(
+            findings[0].detail("flag"),
+            Some(&String::from("self-signed"))
+        );
+    }
+
+    #[test]
+    fn code_signing_with_key() {
+        let findings = certificate_findings(Path::new(
+            "./tests/testing_files/x509/codesign_with_key.pem",
+        ))
+        .unwrap();
+
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(
+            findings[0].detail("flag"),
+            Some(&String::from("code signing"))
+        );
+        assert_eq!(
+            findings[0].detail("issuer"),
+            Some(&String::from("CN=Example Code Signing CA, O=Example Ltd"))
+        );
+    }
+
+    #[test]
+    fn certificate_with_other_key() {
+        let findings =
+            certificate_findings(Path::new("./tests/testing_files/x509/mismatched_key.pem"))
+                .unwrap();
+
+        assert_eq!(findings[0].severity, Severity::Low);
+        assert_eq!(
+            findings[0].detail("matching private key"),
+            Some(&String::from("false"))
+        );
+    }
+
+    #[test]
+    fn expired_der_certificate() {
+        let findings =
+            certificate_findings(Path::new("./tests/testing_files/x509/expired.cer")).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Info);
+        assert!(
+            findings[0]
+                .details
+                .contains(&(String::from("flag"), String::from("expired")))
+        );
+    }
+
+    #[test]
+    fn request_findings() {
+        let findings =
+            certificate_findings(Path::new("./tests/testing_files/x509/request_with_key.pem"))
+                .unwrap();
+        assert_eq!(findings[0].severity, Severity::High);
+
+        let findings =
+            certificate_findings(Path::new("./tests/testing_files/x509/request.csr")).unwrap();
+        assert_eq!(findings[0].severity, Severity::Info);
+        assert_eq!(
+            findings[0].detail("subject alt name"),
+            Some(&String::from("DNS:api.example.com"))
+        );
+    }
+
+    #[test]
+    fn no_certificates() {
+        assert!(
+            certificate_findings(Path::new(
+                "./tests/testing_files/read_file_header/text_file.bib"
+            ))
+            .unwrap()
+            .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_not_a_certificate() {
+        Certificate::from_der(&vec![0x30, 0x03, 0x02, 0x01, 0x00]).unwrap();
+    }
+
+    #[test]
+    fn utc_time_century() {
+        let element = DerElement {
+            tag: TAG_UTC_TIME,
+            contents: b"491231235959Z",
+        };
+        assert_eq!(
+            format_timestamp(parse_time(&element).unwrap()),
+            "2049-12-31 23:59:59 UTC"
+        );
+
+        let element = DerElement {
+            tag: TAG_UTC_TIME,
+            contents: b"500101000000Z",
+        };
+        assert_eq!(parse_time(&element).unwrap(), -631152000);
+    }
+
+    #[test]
+    fn generalized_time() {
+        let element = DerElement {
+            tag: TAG_GENERALIZED_TIME,
+            contents: b"20240229120000Z",
+        };
+        assert_eq!(
+            format_timestamp(parse_time(&element).unwrap()),
+            "2024-02-29 12:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn epoch_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(days_from_civil(2000, 3, 1)), (2000, 3, 1));
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    }
+}
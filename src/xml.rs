@@ -0,0 +1,378 @@
+/*
+ * A small XML reader for the configuration and deployment files that
+ * Windows and IIS write. Only elements, attributes and text are kept;
+ * namespaces are dropped so elements can be found by their local name.
+ */
+
+use crate::byte_reader::invalid_data;
+use std::error::Error;
+
+/// Nesting deeper than this is treated as malformed rather than risking the
+/// stack on hostile input.
+const MAX_DEPTH: usize = 128;
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct XmlElement {
+    /// The element name without any namespace prefix.
+    pub(crate) name: String,
+    /// Attributes in file order, with namespace prefixes removed.
+    pub(crate) attributes: Vec<(String, String)>,
+    /// All the text directly inside the element, with entities decoded.
+    pub(crate) text: String,
+    pub(crate) children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// Parse a document and return its root element.
+    pub(crate) fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut parser = XmlParser {
+            text: text.trim_start_matches('\u{feff}').as_bytes(),
+            position: 0,
+        };
+        parser.skip_misc()?;
+        let root = parser.parse_element(0)?;
+        parser.skip_misc()?;
+        if parser.position != parser.text.len() {
+            return Err(Box::new(invalid_data(
+                "Trailing data after XML root element",
+            )));
+        }
+        return Ok(root);
+    }
+
+    /// Decode a file as UTF-8, or as UTF-16 when it starts with a byte
+    /// order mark, and parse it.
+    pub(crate) fn parse_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let text = match bytes {
+            [0xFF, 0xFE, rest @ ..] => String::from_utf16(
+                &rest
+                    .chunks_exact(2)
+                    .map(|x| u16::from_le_bytes([x[0], x[1]]))
+                    .collect::<Vec<u16>>(),
+            )?,
+            [0xFE, 0xFF, rest @ ..] => String::from_utf16(
+                &rest
+                    .chunks_exact(2)
+                    .map(|x| u16::from_be_bytes([x[0], x[1]]))
+                    .collect::<Vec<u16>>(),
+            )?,
+            _ => String::from_utf8(bytes.to_vec())?,
+        };
+        return XmlElement::parse(&text);
+    }
+
+    /// The value of an attribute, ignoring case in its name.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        return self
+            .attributes
+            .iter()
+            .find(|(x, _)| x.eq_ignore_ascii_case(name))
+            .map(|(_, x)| x.as_str());
+    }
+
+    /// The first child element with a name, ignoring case.
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlElement> {
+        return self
+            .children
+            .iter()
+            .find(|x| x.name.eq_ignore_ascii_case(name));
+    }
+
+    /// The trimmed text of the first child element with a name.
+    pub(crate) fn child_text(&self, name: &str) -> Option<&str> {
+        return self.child(name).map(|x| x.text.trim());
+    }
+
+    /// Every element below this one in document order, each paired with
+    /// its parent.
+    pub(crate) fn descendants(&self) -> Vec<(&XmlElement, &XmlElement)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<(&XmlElement, &XmlElement)> =
+            self.children.iter().rev().map(|x| (self, x)).collect();
+
+        while let Some((parent, element)) = stack.pop() {
+            found.push((parent, element));
+            stack.extend(element.children.iter().rev().map(|x| (element, x)));
+        }
+        return found;
+    }
+}
+
+struct XmlParser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+/// Remove any namespace prefix from an element or attribute name.
+fn local_name(name: &str) -> String {
+    return String::from(name.rsplit(':').next().unwrap_or(name));
+}
+
+/// Replace the predefined and numeric character entities.
+fn decode_entities(text: &str) -> Result<String, Box<dyn Error>> {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| invalid_data("Unterminated XML entity"))?;
+        let entity = &rest[start + 1..start + end];
+
+        let character = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                {
+                    u32::from_str_radix(hex, 16)?
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse()?
+                } else {
+                    return Err(Box::new(invalid_data(&format!(
+                        "Unknown XML entity &{entity};"
+                    ))));
+                };
+                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+        };
+        decoded.push(character);
+        rest = &rest[start + end + 1..];
+    }
+    decoded.push_str(rest);
+    return Ok(decoded);
+}
+
+impl XmlParser<'_> {
+    fn starts_with(&self, literal: &[u8]) -> bool {
+        return self.text[self.position..].starts_with(literal);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.position)
+            .is_some_and(|x| x.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Move past the next occurrence of a terminator.
+    fn skip_past(&mut self, terminator: &[u8]) -> Result<&[u8], Box<dyn Error>> {
+        let start = self.position;
+        let offset = self.text[start..]
+            .windows(terminator.len())
+            .position(|x| x == terminator)
+            .ok_or_else(|| invalid_data("Unterminated XML markup"))?;
+        self.position = start + offset + terminator.len();
+        return Ok(&self.text[start..start + offset]);
+    }
+
+    /// Skip the whitespace, comments, processing instructions and document
+    /// type declaration allowed around the root element.
+    fn skip_misc(&mut self) -> Result<(), Box<dyn Error>> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with(b"<?") {
+                self.skip_past(b"?>")?;
+            } else if self.starts_with(b"<!--") {
+                self.skip_past(b"-->")?;
+            } else if self.starts_with(b"<!DOCTYPE") {
+                self.skip_doctype()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// A document type declaration may hold an internal subset in brackets
+    /// which itself contains `>` characters.
+    fn skip_doctype(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut in_subset = false;
+        while let Some(byte) = self.text.get(self.position) {
+            self.position += 1;
+            match byte {
+                b'[' => in_subset = true,
+                b']' => in_subset = false,
+                b'>' if !in_subset => return Ok(()),
+                _ => {}
+            }
+        }
+        return Err(Box::new(invalid_data("Unterminated XML document type")));
+    }
+
+    fn parse_name(&mut self) -> Result<String, Box<dyn Error>> {
+        let start = self.position;
+        while self.text.get(self.position).is_some_and(|x| {
+            !x.is_ascii_whitespace() && !matches!(x, b'=' | b'>' | b'/' | b'<' | b'"' | b'\'')
+        }) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(Box::new(invalid_data(&format!(
+                "Expected XML name at offset {start}"
+            ))));
+        }
+        return Ok(String::from_utf8(self.text[start..self.position].to_vec())?);
+    }
+
+    fn parse_attribute_value(&mut self) -> Result<String, Box<dyn Error>> {
+        let quote = match self.text.get(self.position) {
+            Some(x @ (b'"' | b'\'')) => *x,
+            _ => return Err(Box::new(invalid_data("Unquoted XML attribute value"))),
+        };
+        self.position += 1;
+        let value = self.skip_past(&[quote])?;
+        return decode_entities(std::str::from_utf8(value)?);
+    }
+
+    fn parse_element(&mut self, depth: usize) -> Result<XmlElement, Box<dyn Error>> {
+        if depth > MAX_DEPTH {
+            return Err(Box::new(invalid_data("XML nested too deeply")));
+        }
+        if !self.starts_with(b"<") {
+            return Err(Box::new(invalid_data(&format!(
+                "Expected XML element at offset {}",
+                self.position
+            ))));
+        }
+        self.position += 1;
+        let full_name = self.parse_name()?;
+        let mut element = XmlElement {
+            name: local_name(&full_name),
+            attributes: Vec::new(),
+            text: String::new(),
+            children: Vec::new(),
+        };
+
+        /* Attributes, up to the end of the start tag. */
+        loop {
+            self.skip_whitespace();
+            if self.starts_with(b"/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if self.starts_with(b">") {
+                self.position += 1;
+                break;
+            }
+            let name = self.parse_name()?;
+            self.skip_whitespace();
+            if !self.starts_with(b"=") {
+                return Err(Box::new(invalid_data("XML attribute without a value")));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            let value = self.parse_attribute_value()?;
+            element.attributes.push((local_name(&name), value));
+        }
+
+        /* Content, up to the matching end tag. */
+        loop {
+            if self.position >= self.text.len() {
+                return Err(Box::new(invalid_data("Unterminated XML element")));
+            }
+            if self.starts_with(b"</") {
+                self.position += 2;
+                let end_name = self.parse_name()?;
+                if end_name != full_name {
+                    return Err(Box::new(invalid_data(&format!(
+                        "XML end tag {end_name} does not match {full_name}"
+                    ))));
+                }
+                self.skip_whitespace();
+                self.skip_past(b">")?;
+                return Ok(element);
+            } else if self.starts_with(b"<!--") {
+                self.skip_past(b"-->")?;
+            } else if self.starts_with(b"<![CDATA[") {
+                self.position += 9;
+                let data = self.skip_past(b"]]>")?;
+                element.text.push_str(std::str::from_utf8(data)?);
+            } else if self.starts_with(b"<?") {
+                self.skip_past(b"?>")?;
+            } else if self.starts_with(b"<") {
+                element.children.push(self.parse_element(depth + 1)?);
+            } else {
+                let start = self.position;
+                while self.text.get(self.position).is_some_and(|x| *x != b'<') {
+                    self.position += 1;
+                }
+                let text = std::str::from_utf8(&self.text[start..self.position])?;
+                element.text.push_str(&decode_entities(text)?);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_document() {
+        let root = XmlElement::parse(concat!(
+            "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+            "<!-- Generated -->\n",
+            "<!DOCTYPE config [<!ENTITY x \"y\">]>\n",
+            "<cfg:config xmlns:cfg=\"urn:example\" cfg:mode='a &amp; b'>\n",
+            "  <item name=\"first\"/>\n",
+            "  <item name=\"second\">Text &lt;here&gt; &#65;&#x42;<![CDATA[<raw>]]></item>\n",
+            "</cfg:config>\n",
+        ))
+        .unwrap();
+
+        assert_eq!(root.name, "config");
+        assert_eq!(root.attribute("MODE"), Some("a & b"));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.child("ITEM").unwrap().attribute("name"), Some("first"));
+        assert_eq!(root.children[1].text, "Text <here> AB<raw>");
+        assert_eq!(root.child_text("missing"), None);
+    }
+
+    #[test]
+    fn descendants_in_order() {
+        let root = XmlElement::parse("<a><b><c/></b><d/></a>").unwrap();
+        let names: Vec<(&str, &str)> = root
+            .descendants()
+            .iter()
+            .map(|(parent, x)| (parent.name.as_str(), x.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![("a", "b"), ("b", "c"), ("a", "d")]);
+    }
+
+    #[test]
+    fn parse_utf16() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "<a><b>x</b></a>".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        let root = XmlElement::parse_bytes(&bytes).unwrap();
+        assert_eq!(root.child_text("b"), Some("x"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_mismatched_tags() {
+        XmlElement::parse("<a><b></a></b>").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_unterminated() {
+        XmlElement::parse("<configuration><appSettings>").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_too_deep() {
+        XmlElement::parse(&"<a>".repeat(1000)).unwrap();
+    }
+}
@@ -0,0 +1,2 @@
+# Nightly backup upload
+powershell.exe -NoProfile -EncodedCommand JABwAGEAcwBzAHcAbwByAGQAIAA9ACAAJwBaAHEAOAB2AE4AMwBrAFIANwB0AFkAMgBwAEwAOQBtAFgAYwA0AFcAJwAKAEkAbgB2AG8AawBlAC0AVwBlAGIAUgBlAHEAdQBlAHMAdAAgAC0AVQByAGkAIABoAHQAdABwAHMAOgAvAC8AYgBhAGMAawB1AHAALgBpAG4AdABlAHIAbgBhAGwALwB1AHAAbABvAGEAZAAgAC0ATQBlAHQAaABvAGQAIABQAG8AcwB0AAoA
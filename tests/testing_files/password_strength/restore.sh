@@ -0,0 +1,3 @@
+#!/bin/sh
+# Restore last night's dump into the reporting database
+mysql -u root --password="jessica1987" reporting < /backups/reporting.sql
@@ -0,0 +1,32 @@
+"""Parse the nightly export into a list of records."""
+
+import csv
+from dataclasses import dataclass
+
+
+@dataclass
+class Record:
+    account: str
+    amount: float
+    posted: str
+
+
+def parse_export(path):
+    records = []
+    with open(path, newline="") as handle:
+        for row in csv.DictReader(handle):
+            if not row.get("account"):
+                continue
+            try:
+                amount = float(row["amount"].replace(",", ""))
+            except ValueError:
+                raise ValueError(f"Bad amount on line {len(records) + 2}: {row['amount']!r}")
+            records.append(Record(row["account"], amount, row["posted"]))
+    return records
+
+
+def totals_by_account(records):
+    totals = {}
+    for record in records:
+        totals[record.account] = totals.get(record.account, 0.0) + record.amount
+    return dict(sorted(totals.items()))
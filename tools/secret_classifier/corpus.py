@@ -0,0 +1,158 @@
+"""
+Generate the labelled tokens the secret classifier is trained on.
+
+Every row is synthetic, drawn from a seeded random generator so the corpus is
+the same on every run. Writes corpus.tsv next to this script with the columns
+label (1 for a secret), token, the line it sits on and the file name.
+"""
+
+import base64
+import hashlib
+import os
+import random
+import string
+import uuid
+
+rng = random.Random(45)
+ALPHANUMERIC = string.ascii_letters + string.digits
+HEX = "0123456789abcdef"
+
+
+def random_string(chars, length):
+    return "".join(rng.choice(chars) for _ in range(length))
+
+
+def random_base64(num_bytes):
+    return base64.b64encode(bytes(rng.getrandbits(8) for _ in range(num_bytes))).decode()
+
+
+WORDS = (
+    "account buffer client config default element handler manager request "
+    "response service session storage template update window container "
+    "internationalization authentication configuration implementation"
+).split()
+
+SECRET_LINES = [
+    'password = "{}"',
+    "API_KEY={}",
+    "secret_key: '{}'",
+    '"token": "{}"',
+    "auth_token = '{}'",
+    "export AWS_SECRET_ACCESS_KEY={}",
+    "client_secret={}",
+    "Authorization: Bearer {}",
+    'private_key = "{}"',
+    "STRIPE_SECRET_KEY={}",
+    "DATABASE_PASSWORD={}",
+    "GITHUB_ACCESS_TOKEN={}",
+    "SENDGRID_API_KEY: {}",
+]
+PLAIN_LINES = ['x = "{}"', "value: {}", '    "{}",', "const c = '{}';", "{}"]
+CONFIG_FILES = [".env", "settings.ini", "application.properties", "config.yml",
+                "appsettings.json", "deploy.toml", "web.config"]
+SOURCE_FILES = ["app.py", "server.js", "main.go", "Program.cs", "deploy.sh",
+                "README.md", "notes.txt"]
+ENV_NAME_PARTS = ["DATABASE", "URL", "HOST", "PORT", "LOG", "LEVEL", "REDIS",
+                  "QUEUE", "NAME", "TIMEOUT", "MAX", "RETRIES", "CACHE", "DIR"]
+
+rows = []
+
+
+def add(label, token, line, name):
+    rows.append((label, token, line.replace("{}", token), name))
+
+
+# Secrets: cloud keys, provider tokens, hex and base64 keys and passwords, in
+# assignments 70% of the time and bare values otherwise.
+for _ in range(1500):
+    kind = rng.randrange(7)
+    if kind == 0:
+        token = "AKIA" + random_string(string.ascii_uppercase + string.digits, 16)
+    elif kind == 1:
+        token = random_base64(30)[:40]
+    elif kind == 2:
+        token = "ghp_" + random_string(ALPHANUMERIC, 36)
+    elif kind == 3:
+        token = ("xoxb-" + random_string(string.digits, 12) + "-"
+                 + random_string(string.digits, 12) + "-" + random_string(ALPHANUMERIC, 24))
+    elif kind == 4:
+        token = random_string(HEX, rng.choice([32, 40, 64]))
+    elif kind == 5:
+        token = random_string(ALPHANUMERIC + "!@#$%^&*?", rng.randint(12, 24))
+    else:
+        token = random_string(ALPHANUMERIC, rng.randint(20, 48))
+    line = rng.choice(SECRET_LINES) if rng.random() < 0.7 else rng.choice(PLAIN_LINES)
+    add(1, token, line, rng.choice(CONFIG_FILES + SOURCE_FILES))
+
+# Harmless tokens: UUIDs, commit and lockfile hashes, minified code,
+# identifiers, paths, URLs, versions, environment variable names and data URIs.
+for index in range(2200):
+    kind = rng.randrange(12)
+    name = rng.choice(CONFIG_FILES + SOURCE_FILES)
+    if kind == 0:
+        token = str(uuid.UUID(int=rng.getrandbits(128)))
+        line = rng.choice(['"id": "{}"', "guid = {}", "uuid: {}", "{}", "request_id={}"])
+    elif kind == 1:
+        token = hashlib.sha1(str(index).encode()).hexdigest()
+        line = rng.choice(["commit {}",
+                           '"resolved": "https://registry.npmjs.org/x/-/x-1.0.0.tgz#{}"',
+                           "sha1: {}", "{}"])
+        name = rng.choice(["package-lock.json", "yarn.lock", "README.md", "notes.txt"])
+    elif kind == 2:
+        token = "sha512-" + random_base64(64)
+        line = '      "integrity": "{}",'
+        name = rng.choice(["package-lock.json", "yarn.lock", "pnpm-lock.yaml"])
+    elif kind == 3:
+        token = hashlib.sha256(str(index).encode()).hexdigest()
+        line = rng.choice(['checksum = "{}"', "{} h1:", "sha256 {}", "digest: sha256:{}"])
+        name = rng.choice(["Cargo.lock", "go.sum", "poetry.lock", "Dockerfile", "composer.lock"])
+    elif kind == 4:
+        pieces = ["e.", "t.", "n(", "r,", "i=", "o?", "a&&", "return ", "function"]
+        token = "".join(rng.choice(pieces) for _ in range(5)).replace(" ", "")
+        token = token[:40].ljust(12, "e")
+        line = "{}"
+        name = rng.choice(["bundle.min.js", "vendor.min.js", "app.js.map"])
+    elif kind == 5:
+        words = rng.sample(WORDS, rng.randint(2, 4))
+        token = "".join(w.capitalize() if j else w for j, w in enumerate(words))
+        line = rng.choice(["public void {}()", "const {} = require('x');", "{}", "def {}(self):"])
+    elif kind == 6:
+        token = "/" + "/".join(rng.sample(["usr", "local", "lib", "python3.11", "site-packages",
+                                            "var", "log", "nginx", "home", "opt", "share"],
+                                           rng.randint(3, 5)))
+        line = rng.choice(["path = {}", "cd {}", "{}"])
+    elif kind == 7:
+        token = ("https://" + rng.choice(["example.com", "github.com", "docs.rs", "registry.npmjs.org"])
+                 + "/" + "/".join(rng.sample(WORDS, 2)))
+        line = rng.choice(["url = {}", "See {}", "{}"])
+    elif kind == 8:
+        token = rng.choice(WORDS) + rng.choice(WORDS)
+        line = rng.choice(["{} is enabled", "# {}", "{}"])
+    elif kind == 9:
+        token = "%d.%d.%d-beta.%d+build.%d" % (rng.randint(0, 9), rng.randint(0, 30),
+                                                rng.randint(0, 99), rng.randint(0, 9),
+                                                rng.randint(100, 9999))
+        line = 'version = "{}"'
+    elif kind == 11:
+        token = "_".join(rng.sample(ENV_NAME_PARTS, rng.randint(2, 4)))
+        line = "{}=" + rng.choice(["true", "8080", "info", "postgres://localhost:5432/app", "/var/cache"])
+    else:
+        token = random_base64(rng.randint(60, 150))
+        line = "background: url(data:image/png;base64,{});"
+        name = rng.choice(["site.min.css", "bundle.min.js", "style.css"])
+    add(0, token, line, name)
+
+# Timestamps, which mix digits and letters much as keys do.
+for _ in range(200):
+    token = "%04d-%02d-%02dT%02d:%02d:%02d" % (rng.randint(1990, 2030), rng.randint(1, 12),
+                                              rng.randint(1, 28), rng.randint(0, 23),
+                                              rng.randint(0, 59), rng.randint(0, 59))
+    token += rng.choice(["Z", ".%03dZ" % rng.randint(0, 999), "+00:00", "+02:00"])
+    line = rng.choice(["created_at: {}", '"updated": "{}",', "last_modified={}", "{} INFO started"])
+    add(0, token, line, rng.choice(CONFIG_FILES + SOURCE_FILES + ["server.log", "data.json"]))
+
+with open(os.path.join(os.path.dirname(os.path.abspath(__file__)), "corpus.tsv"), "w") as corpus:
+    for label, token, line, name in rows:
+        assert "\t" not in line
+        corpus.write("%d\t%s\t%s\t%s\n" % (label, token, line, name))
+print(len(rows), "tokens")
@@ -0,0 +1,69 @@
+"""
+Fit the secret classifier's logistic regression to features.tsv and print the
+constants to paste into src/secret_classifier.rs.
+
+Features are standardised with the means and standard deviations of the
+training split. A fifth of the rows, chosen with a fixed seed, are held out
+to report the accuracy. The model is fitted by batch gradient descent with a
+small L2 penalty, so every run gives the same numbers.
+"""
+
+import math
+import os
+import random
+
+ITERATIONS = 1500
+LEARNING_RATE = 0.5
+L2_PENALTY = 0.01
+
+path = os.path.join(os.path.dirname(os.path.abspath(__file__)), "features.tsv")
+rows = [line.rstrip("\n").split("\t") for line in open(path)]
+labels = [int(row[0]) for row in rows]
+values = [[float(x) for x in row[1:]] for row in rows]
+count, width = len(values), len(values[0])
+
+order = list(range(count))
+random.Random(1).shuffle(order)
+held_out = set(order[: count // 5])
+training = [i for i in range(count) if i not in held_out]
+
+means = [sum(values[i][j] for i in training) / len(training) for j in range(width)]
+scales = [math.sqrt(sum((values[i][j] - means[j]) ** 2 for i in training) / len(training)) or 1.0
+          for j in range(width)]
+scaled = [[(values[i][j] - means[j]) / scales[j] for j in range(width)] for i in range(count)]
+
+weights = [0.0] * width
+bias = 0.0
+
+
+def probability(i):
+    score = bias + sum(weights[j] * scaled[i][j] for j in range(width))
+    return 1 / (1 + math.exp(-score))
+
+
+for _ in range(ITERATIONS):
+    weight_gradient = [L2_PENALTY * weights[j] for j in range(width)]
+    bias_gradient = 0.0
+    for i in training:
+        error = probability(i) - labels[i]
+        for j in range(width):
+            weight_gradient[j] += error * scaled[i][j] / len(training)
+        bias_gradient += error / len(training)
+    weights = [weights[j] - LEARNING_RATE * weight_gradient[j] for j in range(width)]
+    bias -= LEARNING_RATE * bias_gradient
+
+false_positives = sum(probability(i) >= 0.5 and not labels[i] for i in held_out)
+false_negatives = sum(probability(i) < 0.5 and labels[i] for i in held_out)
+accuracy = 1 - (false_positives + false_negatives) / len(held_out)
+print("held out accuracy %.4f, %d false positives and %d false negatives of %d"
+      % (accuracy, false_positives, false_negatives, len(held_out)))
+
+
+def constant(name, numbers):
+    return "const %s: [f64; FEATURES] = [%s];" % (name, ", ".join("%.4f" % x for x in numbers))
+
+
+print(constant("FEATURE_MEANS", means))
+print(constant("FEATURE_SCALES", scales))
+print(constant("WEIGHTS", weights))
+print("const BIAS: f64 = %.4f;" % bias)